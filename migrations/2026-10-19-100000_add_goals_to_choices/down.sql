@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE "choices" DROP COLUMN "goal_period_length";
+ALTER TABLE "choices" DROP COLUMN "goal_period";
+ALTER TABLE "choices" DROP COLUMN "goal_times";
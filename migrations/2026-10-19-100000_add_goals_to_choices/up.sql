@@ -0,0 +1,5 @@
+-- Frequency goals for choices, e.g. "3 times per week" or "once every 14 days".
+
+ALTER TABLE "choices" ADD COLUMN "goal_times" INTEGER CHECK ("goal_times" >= 1);
+ALTER TABLE "choices" ADD COLUMN "goal_period" TEXT CHECK ("goal_period" IN ('day', 'week', 'month'));
+ALTER TABLE "choices" ADD COLUMN "goal_period_length" INTEGER NOT NULL DEFAULT 1 CHECK ("goal_period_length" >= 1);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX "sent_reminders_key_index";
+DROP TABLE "sent_reminders";
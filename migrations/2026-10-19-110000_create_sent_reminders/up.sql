@@ -0,0 +1,10 @@
+-- Log of the reminders sent by `jurnalo remind`, used so the same reminder isn't sent on every run.
+
+CREATE TABLE "sent_reminders" (
+	"id"	INTEGER NOT NULL,
+	"reminder_key"	TEXT NOT NULL,
+	"sent_at"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY("id")
+);
+
+CREATE INDEX "sent_reminders_key_index" ON "sent_reminders" ("reminder_key", "sent_at");
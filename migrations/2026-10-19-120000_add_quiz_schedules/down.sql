@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE "quiz_runs";
+ALTER TABLE "quizzes" DROP COLUMN "schedule";
@@ -0,0 +1,15 @@
+-- Schedules for quizzes (e.g. "daily", "weekdays", "weekly sun", "monthly 1"), and a log of when each quiz was answered.
+
+ALTER TABLE "quizzes" ADD COLUMN "schedule" TEXT;
+
+CREATE TABLE "quiz_runs" (
+	"id"	INTEGER NOT NULL,
+	"quiz_label"	TEXT NOT NULL,
+	"for_date"	DATE NOT NULL,
+	"timestamp"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY("id"),
+	FOREIGN KEY("quiz_label")
+		REFERENCES "quizzes" ("label")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE
+);
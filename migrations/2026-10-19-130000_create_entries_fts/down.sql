@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+DROP TRIGGER "entries_fts_after_choice_rename";
+DROP TRIGGER "entries_fts_after_category_rename";
+DROP TRIGGER "entries_fts_after_delete";
+DROP TRIGGER "entries_fts_after_update";
+DROP TRIGGER "entries_fts_after_insert";
+DROP TABLE "entries_fts";
@@ -0,0 +1,50 @@
+-- A full-text index over the details of the entries and the labels of their category and choice.
+-- The rowid of each row is the id of its entry. The triggers keep it in sync with the entries and the labels.
+
+CREATE VIRTUAL TABLE "entries_fts" USING fts5(
+	"details",
+	"category_label",
+	"choice_label",
+	tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO "entries_fts" ("rowid", "details", "category_label", "choice_label")
+	SELECT "entries"."id", "entries"."details", "categories"."label", "choices"."label"
+	FROM "entries"
+	LEFT JOIN "categories" ON "categories"."id" = "entries"."category"
+	LEFT JOIN "choices" ON "choices"."id" = "entries"."value";
+
+CREATE TRIGGER "entries_fts_after_insert" AFTER INSERT ON "entries" BEGIN
+	INSERT INTO "entries_fts" ("rowid", "details", "category_label", "choice_label")
+	VALUES (
+		new."id",
+		new."details",
+		(SELECT "label" FROM "categories" WHERE "id" = new."category"),
+		(SELECT "label" FROM "choices" WHERE "id" = new."value")
+	);
+END;
+
+CREATE TRIGGER "entries_fts_after_update" AFTER UPDATE ON "entries" BEGIN
+	DELETE FROM "entries_fts" WHERE "rowid" = old."id";
+	INSERT INTO "entries_fts" ("rowid", "details", "category_label", "choice_label")
+	VALUES (
+		new."id",
+		new."details",
+		(SELECT "label" FROM "categories" WHERE "id" = new."category"),
+		(SELECT "label" FROM "choices" WHERE "id" = new."value")
+	);
+END;
+
+CREATE TRIGGER "entries_fts_after_delete" AFTER DELETE ON "entries" BEGIN
+	DELETE FROM "entries_fts" WHERE "rowid" = old."id";
+END;
+
+CREATE TRIGGER "entries_fts_after_category_rename" AFTER UPDATE OF "label" ON "categories" BEGIN
+	UPDATE "entries_fts" SET "category_label" = new."label"
+	WHERE "rowid" IN (SELECT "id" FROM "entries" WHERE "category" = new."id");
+END;
+
+CREATE TRIGGER "entries_fts_after_choice_rename" AFTER UPDATE OF "label" ON "choices" BEGIN
+	UPDATE "entries_fts" SET "choice_label" = new."label"
+	WHERE "rowid" IN (SELECT "id" FROM "entries" WHERE "value" = new."id");
+END;
@@ -0,0 +1,26 @@
+-- Tags on entries, either extracted from the #hashtags in the details or added by hand.
+
+CREATE TABLE "tags" (
+	"id"	INTEGER NOT NULL,
+	"label"	TEXT NOT NULL UNIQUE,
+	PRIMARY KEY("id")
+);
+
+-- many-to-many
+CREATE TABLE "entries_to_tags" (
+	"id"	INTEGER NOT NULL,
+	"entry_id"	INTEGER NOT NULL,
+	"tag_id"	INTEGER NOT NULL,
+	PRIMARY KEY("id"),
+	FOREIGN KEY("entry_id")
+		REFERENCES "entries" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE,
+	FOREIGN KEY("tag_id")
+		REFERENCES "tags" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE,
+	UNIQUE("entry_id", "tag_id")
+);
+
+CREATE INDEX "entries_to_tags_tag_id" ON "entries_to_tags" ("tag_id");
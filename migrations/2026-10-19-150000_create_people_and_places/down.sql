@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE "entries_to_places";
+DROP TABLE "entries_to_people";
+DROP TABLE "places";
+DROP TABLE "people";
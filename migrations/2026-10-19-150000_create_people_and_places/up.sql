@@ -0,0 +1,53 @@
+-- People (@name) and places (+name) mentioned in the details of the entries.
+-- The labels keep the spelling of their first mention, but are matched regardless of case.
+
+CREATE TABLE "people" (
+	"id"	INTEGER NOT NULL,
+	"label"	TEXT NOT NULL UNIQUE COLLATE NOCASE,
+	"reminder_timer_in_days" INTEGER,
+	PRIMARY KEY("id"),
+	CHECK ("reminder_timer_in_days" >= 0)
+);
+
+CREATE TABLE "places" (
+	"id"	INTEGER NOT NULL,
+	"label"	TEXT NOT NULL UNIQUE COLLATE NOCASE,
+	PRIMARY KEY("id")
+);
+
+-- many-to-many
+CREATE TABLE "entries_to_people" (
+	"id"	INTEGER NOT NULL,
+	"entry_id"	INTEGER NOT NULL,
+	"person_id"	INTEGER NOT NULL,
+	PRIMARY KEY("id"),
+	FOREIGN KEY("entry_id")
+		REFERENCES "entries" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE,
+	FOREIGN KEY("person_id")
+		REFERENCES "people" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE,
+	UNIQUE("entry_id", "person_id")
+);
+
+-- many-to-many
+CREATE TABLE "entries_to_places" (
+	"id"	INTEGER NOT NULL,
+	"entry_id"	INTEGER NOT NULL,
+	"place_id"	INTEGER NOT NULL,
+	PRIMARY KEY("id"),
+	FOREIGN KEY("entry_id")
+		REFERENCES "entries" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE,
+	FOREIGN KEY("place_id")
+		REFERENCES "places" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE,
+	UNIQUE("entry_id", "place_id")
+);
+
+CREATE INDEX "entries_to_people_person_id" ON "entries_to_people" ("person_id");
+CREATE INDEX "entries_to_places_place_id" ON "entries_to_places" ("place_id");
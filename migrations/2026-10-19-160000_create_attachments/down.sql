@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DELETE FROM "category_types" WHERE "id" = 6;
+DROP TABLE "attachments";
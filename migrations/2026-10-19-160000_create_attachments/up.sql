@@ -0,0 +1,22 @@
+-- Files attached to entries. The files are copied into the attachments directory next to the database, named after
+-- the SHA-256 of their content, so the same file attached twice is only stored once.
+
+CREATE TABLE "attachments" (
+	"id"	INTEGER NOT NULL,
+	"entry_id"	INTEGER NOT NULL,
+	"hash"	TEXT NOT NULL,
+	"file_name"	TEXT NOT NULL,
+	"extension"	TEXT,
+	"timestamp"	TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY("id"),
+	FOREIGN KEY("entry_id")
+		REFERENCES "entries" ("id")
+		ON DELETE CASCADE
+		ON UPDATE CASCADE
+);
+
+CREATE INDEX "attachments_entry_id" ON "attachments" ("entry_id");
+
+INSERT INTO "category_types" ("id", "label")
+	VALUES
+		(6, 'Attachments');
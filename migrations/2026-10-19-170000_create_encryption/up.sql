@@ -0,0 +1,13 @@
+-- The settings of an encrypted journal: the salt and the Argon2id parameters the key is derived from the passphrase
+-- with, and a value encrypted with the key, to check the passphrase. The journal is encrypted when there is a row.
+-- The encrypted details of the entries start with `enc:v1:`.
+
+CREATE TABLE "encryption" (
+	"id"	INTEGER NOT NULL,
+	"salt"	TEXT NOT NULL,
+	"memory_kib"	INTEGER NOT NULL,
+	"iterations"	INTEGER NOT NULL,
+	"parallelism"	INTEGER NOT NULL,
+	"check_value"	TEXT NOT NULL,
+	PRIMARY KEY("id")
+);
@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE "synced_entries";
+DROP TRIGGER "entries_uuid_after_insert";
+DROP INDEX "entries_uuid";
+ALTER TABLE "entries" DROP COLUMN "uuid";
+
+DROP TRIGGER "entries_fts_after_update";
+
+CREATE TRIGGER "entries_fts_after_update" AFTER UPDATE ON "entries" BEGIN
+	DELETE FROM "entries_fts" WHERE "rowid" = old."id";
+	INSERT INTO "entries_fts" ("rowid", "details", "category_label", "choice_label")
+	VALUES (
+		new."id",
+		new."details",
+		(SELECT "label" FROM "categories" WHERE "id" = new."category"),
+		(SELECT "label" FROM "choices" WHERE "id" = new."value")
+	);
+END;
@@ -0,0 +1,45 @@
+-- A globally unique identifier (a random UUID) for each entry, so that `sync` can match the entries of two journals.
+-- The existing entries get one now, and the new ones when they are inserted, unless they come with one (e.g. a copy
+-- of an entry of another journal).
+
+-- The index for `search` is only updated when what it indexes changes, as setting the UUID of a new entry would index
+-- it again before it is indexed as new.
+
+DROP TRIGGER "entries_fts_after_update";
+
+CREATE TRIGGER "entries_fts_after_update" AFTER UPDATE OF "details", "category", "value" ON "entries" BEGIN
+	DELETE FROM "entries_fts" WHERE "rowid" = old."id";
+	INSERT INTO "entries_fts" ("rowid", "details", "category_label", "choice_label")
+	VALUES (
+		new."id",
+		new."details",
+		(SELECT "label" FROM "categories" WHERE "id" = new."category"),
+		(SELECT "label" FROM "choices" WHERE "id" = new."value")
+	);
+END;
+
+ALTER TABLE "entries" ADD COLUMN "uuid" TEXT;
+
+UPDATE "entries" SET "uuid" =
+	lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' || substr(lower(hex(randomblob(2))), 2)
+	|| '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(lower(hex(randomblob(2))), 2)
+	|| '-' || lower(hex(randomblob(6)));
+
+CREATE UNIQUE INDEX "entries_uuid" ON "entries" ("uuid");
+
+CREATE TRIGGER "entries_uuid_after_insert" AFTER INSERT ON "entries" WHEN new."uuid" IS NULL BEGIN
+	UPDATE "entries" SET "uuid" =
+		lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' || substr(lower(hex(randomblob(2))), 2)
+		|| '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(lower(hex(randomblob(2))), 2)
+		|| '-' || lower(hex(randomblob(6)))
+	WHERE "id" = new."id";
+END;
+
+-- The content of the entries (a hash of it) when they were last synced, to tell in which journal an entry changed
+-- since, or whether it changed in both.
+
+CREATE TABLE "synced_entries" (
+	"uuid"	TEXT NOT NULL,
+	"hash"	TEXT NOT NULL,
+	PRIMARY KEY("uuid")
+);
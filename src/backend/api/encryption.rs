@@ -0,0 +1,216 @@
+/// This file contains the encryption of the details of the entries, see `backend::encryption`.
+/// The labels, and the tags and mentions found in the details, aren't encrypted, as they are looked up by value.
+use super::Api;
+use crate::backend::encryption::{self, Key, KeyParameters};
+use crate::backend::{config, schema};
+use crate::errors::Error;
+use crate::models::{insertable as m_ins, queryable_or_selectable as m_qos};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use diesel::prelude::*;
+
+impl Api {
+    /// Whether the journal is encrypted.
+    pub fn is_encrypted(&mut self) -> Result<bool, Error> {
+        Ok(self.encryption_settings()?.is_some())
+    }
+
+    /// Unlocks an encrypted journal, asking for the passphrase unless a recent command already did. Does nothing if the
+    /// journal isn't encrypted. The commands that write entries call it first, so the passphrase isn't asked after the
+    /// answers are typed.
+    pub fn unlock(&mut self) -> Result<(), Error> {
+        self.key().map(|_| ())
+    }
+
+    /// Encrypts the details, if the journal is encrypted.
+    pub(crate) fn seal_details(
+        &mut self,
+        details: Option<String>,
+    ) -> Result<Option<String>, Error> {
+        match (details, self.key()?) {
+            (Some(details), Some(key)) if !details.is_empty() => Ok(Some(key.encrypt(&details))),
+            (details, _) => Ok(details),
+        }
+    }
+
+    /// Decrypts the details, if they are encrypted.
+    pub(crate) fn open_details(
+        &mut self,
+        details: Option<String>,
+    ) -> Result<Option<String>, Error> {
+        match details {
+            Some(details) if encryption::is_encrypted(&details) => {
+                let key = self.key()?.ok_or_else(|| {
+                    Error::Encryption(
+                        "Some details are encrypted, but the journal has no passphrase.".to_owned(),
+                    )
+                })?;
+                Ok(Some(key.decrypt(&details)?))
+            }
+            details => Ok(details),
+        }
+    }
+
+    /// Encrypts the details of all the entries with a key derived from the passphrase, and then those of the new
+    /// entries. Returns how many entries were encrypted.
+    pub fn encrypt_journal(&mut self, passphrase: &str) -> Result<usize, Error> {
+        if self.is_encrypted()? {
+            return Err(Error::InvalidInput(
+                "The journal is already encrypted. Use `jurnalo rekey` to change the passphrase."
+                    .to_owned(),
+            ));
+        }
+        let parameters = KeyParameters::generate();
+        let key = parameters.derive_key(passphrase)?;
+
+        let count = self.transaction(|api| {
+            api.save_encryption_settings(&parameters, &key)?;
+            api.rewrite_all_details(|details| Ok(key.encrypt(details)))
+        })?;
+        self.remove_leftover_plaintext()?;
+        self.keep_key(&parameters, key)?;
+        Ok(count)
+    }
+
+    /// Decrypts the details of all the entries, and stops encrypting the new ones. Returns how many entries were
+    /// decrypted.
+    pub fn decrypt_journal(&mut self) -> Result<usize, Error> {
+        let settings = self.encryption_settings()?.ok_or_else(not_encrypted)?;
+        let key = self.key()?.ok_or_else(not_encrypted)?;
+
+        let count = self.transaction(|api| {
+            diesel::delete(schema::encryption::table).execute(&mut api.connection)?;
+            api.rewrite_all_details(|details| key.decrypt(details))
+        })?;
+        self.key = None;
+        encryption::forget_key(&encryption::journal_id(
+            &self.path,
+            &decode_salt(&settings.salt)?,
+        ));
+        Ok(count)
+    }
+
+    /// Encrypts the details of all the entries again, with a key derived from the new passphrase.
+    /// Returns how many entries were encrypted.
+    pub fn rekey_journal(&mut self, new_passphrase: &str) -> Result<usize, Error> {
+        let settings = self.encryption_settings()?.ok_or_else(not_encrypted)?;
+        let old_key = self.key()?.ok_or_else(not_encrypted)?;
+        let parameters = KeyParameters::generate();
+        let new_key = parameters.derive_key(new_passphrase)?;
+
+        let count = self.transaction(|api| {
+            api.save_encryption_settings(&parameters, &new_key)?;
+            api.rewrite_all_details(|details| Ok(new_key.encrypt(&old_key.decrypt(details)?)))
+        })?;
+        self.remove_leftover_plaintext()?;
+        encryption::forget_key(&encryption::journal_id(
+            &self.path,
+            &decode_salt(&settings.salt)?,
+        ));
+        self.keep_key(&parameters, new_key)?;
+        Ok(count)
+    }
+
+    /// Returns the key of the journal, unlocking it the first time, or `None` if the journal isn't encrypted.
+    fn key(&mut self) -> Result<Option<Key>, Error> {
+        if let Some(ref key) = self.key {
+            return Ok(Some(key.clone()));
+        }
+        let Some(settings) = self.encryption_settings()? else {
+            return Ok(None);
+        };
+        let parameters = KeyParameters {
+            salt: decode_salt(&settings.salt)?,
+            memory_kib: settings.memory_kib as u32,
+            iterations: settings.iterations as u32,
+            parallelism: settings.parallelism as u32,
+        };
+        let journal_id = encryption::journal_id(&self.path, &parameters.salt);
+        let key = match encryption::cached_key(&journal_id) {
+            Some(key) if key.matches(&settings.check_value) => key,
+            _ => encryption::unlock(&parameters, &settings.check_value)?,
+        };
+        self.keep_key(&parameters, key.clone())?;
+        Ok(Some(key))
+    }
+
+    /// Keeps the key for the rest of the command, and caches it for the next ones.
+    fn keep_key(&mut self, parameters: &KeyParameters, key: Key) -> Result<(), Error> {
+        encryption::cache_key(
+            &encryption::journal_id(&self.path, &parameters.salt),
+            &key,
+            config::get().unlock_minutes.value,
+        )?;
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn encryption_settings(
+        &mut self,
+    ) -> Result<Option<m_qos::EncryptionSettings>, diesel::result::Error> {
+        schema::encryption::table
+            .select(m_qos::EncryptionSettings::as_select())
+            .first::<m_qos::EncryptionSettings>(&mut self.connection)
+            .optional()
+    }
+
+    /// Replaces the settings of the encryption, if any, with these ones.
+    fn save_encryption_settings(
+        &mut self,
+        parameters: &KeyParameters,
+        key: &Key,
+    ) -> Result<(), diesel::result::Error> {
+        use schema::encryption;
+
+        diesel::delete(encryption::table).execute(&mut self.connection)?;
+        diesel::insert_into(encryption::table)
+            .values(&m_ins::NewEncryptionSettings {
+                salt: STANDARD.encode(&parameters.salt),
+                memory_kib: parameters.memory_kib as i32,
+                iterations: parameters.iterations as i32,
+                parallelism: parameters.parallelism as i32,
+                check_value: key.check_value(),
+            })
+            .execute(&mut self.connection)?;
+        Ok(())
+    }
+
+    /// Replaces the details of every entry that has some with `rewrite(details)`. Returns how many were rewritten.
+    fn rewrite_all_details(
+        &mut self,
+        rewrite: impl Fn(&str) -> Result<String, Error>,
+    ) -> Result<usize, Error> {
+        use schema::entries;
+
+        let all_details: Vec<(i32, String)> = entries::table
+            .filter(entries::details.is_not_null())
+            .filter(entries::details.ne(""))
+            .select((entries::id, entries::details.assume_not_null()))
+            .load(&mut self.connection)?;
+
+        for (entry_id, details) in all_details.iter() {
+            diesel::update(entries::table.find(entry_id))
+                .set(entries::details.eq(rewrite(details)?))
+                .execute(&mut self.connection)?;
+        }
+        Ok(all_details.len())
+    }
+
+    /// The old details can stay in the file after they are replaced, in the segments of the search index and in the
+    /// free pages of the database, until they are overwritten. This merges the index and rewrites the file without them.
+    fn remove_leftover_plaintext(&mut self) -> Result<(), diesel::result::Error> {
+        diesel::sql_query("INSERT INTO entries_fts (entries_fts) VALUES ('optimize')")
+            .execute(&mut self.connection)?;
+        diesel::sql_query("VACUUM").execute(&mut self.connection)?;
+        Ok(())
+    }
+}
+
+fn not_encrypted() -> Error {
+    Error::InvalidInput("The journal isn't encrypted. See `jurnalo encrypt`.".to_owned())
+}
+
+fn decode_salt(salt: &str) -> Result<Vec<u8>, Error> {
+    STANDARD
+        .decode(salt)
+        .map_err(|_| Error::Encryption("The salt of the encryption is damaged.".to_owned()))
+}
@@ -0,0 +1,429 @@
+/// This file contains the canonical JSON export of the journal: its setup (the categories with their choices, the
+/// quizzes with the categories they ask, and the people) and all its entries, referring to each other by label rather
+/// than by id, so that the document can be imported into any journal.
+///
+/// The document is versioned: `VERSION` is raised when its meaning changes, and the older versions keep being
+/// imported. Since version 2, the id of an entry is its UUID, which `sync` relies on too. The details are exported
+/// decrypted, like in `print`.
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use super::{Api, EntryWithLabelsTuple};
+use crate::backend::schema;
+use crate::errors::Error;
+use crate::models::{insertable as m_ins, queryable_or_selectable as m_qos};
+
+/// Tells the JSON exports of jurnalo apart from other JSON files.
+pub const FORMAT: &str = "jurnalo";
+pub const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub format: String,
+    pub version: u32,
+    /// In UTC, like all the timestamps of the document.
+    pub exported_at: NaiveDateTime,
+    #[serde(flatten)]
+    pub setup: Setup,
+    pub entries: Vec<ExportedEntry>,
+}
+
+/// Everything but the entries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Setup {
+    pub categories: Vec<ExportedCategory>,
+    pub quizzes: Vec<ExportedQuiz>,
+    #[serde(default)]
+    pub people: Vec<ExportedPerson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedCategory {
+    pub label: String,
+    pub prompt: String,
+    pub category_type: i32,
+    #[serde(default)]
+    pub disabled: bool,
+    pub extra_info: Option<String>,
+    pub show_in_streaks: bool,
+    pub reminder_timer_in_days: Option<i32>,
+    pub choices: Vec<ExportedChoice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedChoice {
+    pub label: String,
+    pub shortcut: String,
+    #[serde(default)]
+    pub disabled: bool,
+    pub show_in_streaks: bool,
+    pub reminder_timer_in_days: Option<i32>,
+    pub goal_times: Option<i32>,
+    pub goal_period: Option<String>,
+    pub goal_period_length: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedQuiz {
+    pub label: String,
+    pub command: Option<String>,
+    pub schedule: Option<String>,
+    /// The labels of the categories the quiz asks, in order.
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedPerson {
+    pub label: String,
+    pub reminder_timer_in_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedEntry {
+    /// Identifies the entry across exports and journals: its UUID, which doesn't change with its id in the database.
+    /// Before version 2, it was derived from the content of the entry.
+    pub id: String,
+    pub timestamp: NaiveDateTime,
+    pub category: Option<String>,
+    pub choice: Option<String>,
+    pub details: Option<String>,
+    /// Including the tags added by hand, besides the #hashtags of the details.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<ExportedAttachment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedAttachment {
+    /// The SHA-256 of the file, which names it in the attachments directory.
+    pub hash: String,
+    pub file_name: String,
+    pub extension: Option<String>,
+}
+
+/// How much of the setup of a document was added to the journal.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SetupSummary {
+    pub categories: usize,
+    pub choices: usize,
+    pub quizzes: usize,
+    pub links: usize,
+    pub people: usize,
+}
+
+impl Api {
+    /// Returns the whole journal as a document.
+    pub fn export_document(&mut self) -> Result<Document, Error> {
+        Ok(Document {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            exported_at: chrono::Utc::now().naive_utc(),
+            setup: self.export_setup()?,
+            entries: self.export_entries()?,
+        })
+    }
+
+    /// Returns the categories with their choices, the quizzes and the people.
+    pub fn export_setup(&mut self) -> Result<Setup, Error> {
+        use schema::{people, quizzes, quizzes_to_categories};
+
+        let mut choices_by_category: HashMap<String, Vec<ExportedChoice>> = HashMap::new();
+        for choice in self.get_all_choices()? {
+            choices_by_category
+                .entry(choice.category_label.clone())
+                .or_default()
+                .push(ExportedChoice {
+                    label: choice.label,
+                    shortcut: choice.shortcut,
+                    disabled: choice.disabled_bool != 0,
+                    show_in_streaks: choice.show_in_streaks != 0,
+                    reminder_timer_in_days: choice.reminder_timer_in_days,
+                    goal_times: choice.goal_times,
+                    goal_period: choice.goal_period,
+                    goal_period_length: choice.goal_period_length,
+                });
+        }
+        let categories = self
+            .get_all_categories()?
+            .into_iter()
+            .map(|category| ExportedCategory {
+                choices: choices_by_category
+                    .remove(&category.label)
+                    .unwrap_or_default(),
+                label: category.label,
+                prompt: category.prompt,
+                category_type: category.category_type,
+                disabled: category.disabled_bool != 0,
+                extra_info: category.extra_info,
+                show_in_streaks: category.show_in_streaks != 0,
+                reminder_timer_in_days: category.reminder_timer_in_days,
+            })
+            .collect();
+
+        let mut quizzes: Vec<ExportedQuiz> = quizzes::table
+            .order(quizzes::id)
+            .load::<m_qos::Quiz>(&mut self.connection)?
+            .into_iter()
+            .map(|quiz| ExportedQuiz {
+                label: quiz.label,
+                command: quiz.command,
+                schedule: quiz.schedule,
+                categories: Vec::new(),
+            })
+            .collect();
+        for link in quizzes_to_categories::table
+            .order((quizzes_to_categories::order, quizzes_to_categories::id))
+            .load::<m_qos::QuizToCategory>(&mut self.connection)?
+        {
+            if let Some(quiz) = quizzes
+                .iter_mut()
+                .find(|quiz| quiz.label == link.quiz_label)
+            {
+                quiz.categories.push(link.category_label);
+            }
+        }
+
+        let people = people::table
+            .order(people::label)
+            .load::<m_qos::Person>(&mut self.connection)?
+            .into_iter()
+            .map(|person| ExportedPerson {
+                label: person.label,
+                reminder_timer_in_days: person.reminder_timer_in_days,
+            })
+            .collect();
+
+        Ok(Setup {
+            categories,
+            quizzes,
+            people,
+        })
+    }
+
+    /// Returns all the entries, in the order of the journal.
+    pub fn export_entries(&mut self) -> Result<Vec<ExportedEntry>, Error> {
+        let all_entries = self.get_entries_between_dates(
+            chrono::NaiveDate::from_ymd_opt(1, 1, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .expect("Valid date."),
+            chrono::NaiveDate::from_ymd_opt(9999, 12, 31)
+                .and_then(|date| date.and_hms_opt(23, 59, 59))
+                .expect("Valid date."),
+        )?;
+        let entry_ids: Vec<i32> = all_entries
+            .iter()
+            .map(|EntryWithLabelsTuple(entry, _, _)| entry.id)
+            .collect();
+        let mut attachments = self.get_attachments_of_entries(&entry_ids)?;
+        let mut tags = self.get_tags_of_entries(&entry_ids)?;
+        let mut seen_ids: HashMap<String, usize> = HashMap::new();
+        let entries = all_entries
+            .into_iter()
+            .map(|EntryWithLabelsTuple(entry, category, choice)| {
+                // The migration gave every entry a UUID, so this is only a fallback.
+                let id = entry.uuid.unwrap_or_else(|| {
+                    let id = content_id(entry.timestamp, &category, &choice, &entry.details);
+                    // Identical entries get `-2`, `-3`... in the order of the journal.
+                    let repeats = seen_ids.entry(id.clone()).or_default();
+                    *repeats += 1;
+                    match *repeats {
+                        1 => id,
+                        _ => format!("{}-{}", id, repeats),
+                    }
+                });
+                ExportedEntry {
+                    id,
+                    timestamp: entry.timestamp,
+                    category,
+                    choice,
+                    details: entry.details,
+                    tags: tags.remove(&entry.id).unwrap_or_default(),
+                    attachments: attachments
+                        .remove(&entry.id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|attachment| ExportedAttachment {
+                            hash: attachment.hash,
+                            file_name: attachment.file_name,
+                            extension: attachment.extension,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    /// Adds the categories, choices, quizzes, links between them and people of the setup that the journal doesn't
+    /// have yet, matching them by label. What the journal already has is left as it is, except that a taken shortcut
+    /// is replaced by the next free number.
+    pub fn merge_setup(&mut self, setup: &Setup) -> Result<SetupSummary, Error> {
+        use schema::{categories, choices, people, quizzes, quizzes_to_categories};
+
+        self.transaction(|api| {
+            let mut summary = SetupSummary::default();
+            let mut existing_categories: Vec<String> = api
+                .get_all_categories()?
+                .into_iter()
+                .map(|category| category.label)
+                .collect();
+            for category in setup.categories.iter() {
+                if !existing_categories.contains(&category.label) {
+                    diesel::insert_into(categories::table)
+                        .values(m_ins::NewCategory {
+                            label: category.label.clone(),
+                            prompt: category.prompt.clone(),
+                            category_type: category.category_type,
+                            extra_info: category.extra_info.clone(),
+                            show_in_streaks: Some(category.show_in_streaks as i32),
+                            reminder_timer_in_days: category.reminder_timer_in_days,
+                        })
+                        .execute(&mut api.connection)?;
+                    if category.disabled {
+                        diesel::update(
+                            categories::table.filter(categories::label.eq(&category.label)),
+                        )
+                        .set(categories::disabled_bool.eq(1))
+                        .execute(&mut api.connection)?;
+                    }
+                    existing_categories.push(category.label.clone());
+                    summary.categories += 1;
+                }
+
+                let mut existing_choices = api.get_choices_in_category(&category.label)?;
+                for choice in category.choices.iter() {
+                    if existing_choices.iter().any(|c| c.label == choice.label) {
+                        continue;
+                    }
+                    let shortcut_is_taken = existing_choices
+                        .iter()
+                        .any(|c| c.shortcut == choice.shortcut);
+                    let shortcut = match shortcut_is_taken {
+                        true => super::next_free_shortcut(&existing_choices),
+                        false => choice.shortcut.clone(),
+                    };
+                    diesel::insert_into(choices::table)
+                        .values(m_ins::NewChoice {
+                            label: choice.label.clone(),
+                            shortcut,
+                            category_label: category.label.clone(),
+                            show_in_streaks: Some(choice.show_in_streaks as i32),
+                            reminder_timer_in_days: choice.reminder_timer_in_days,
+                            goal_times: choice.goal_times,
+                            goal_period: choice.goal_period.clone(),
+                            goal_period_length: Some(choice.goal_period_length),
+                        })
+                        .execute(&mut api.connection)?;
+                    if choice.disabled {
+                        diesel::update(
+                            choices::table
+                                .filter(choices::label.eq(&choice.label))
+                                .filter(choices::category_label.eq(&category.label)),
+                        )
+                        .set(choices::disabled_bool.eq(1))
+                        .execute(&mut api.connection)?;
+                    }
+                    existing_choices = api.get_choices_in_category(&category.label)?;
+                    summary.choices += 1;
+                }
+            }
+
+            for quiz in setup.quizzes.iter() {
+                let exists = quizzes::table
+                    .filter(quizzes::label.eq(&quiz.label))
+                    .select(quizzes::id)
+                    .first::<i32>(&mut api.connection)
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    diesel::insert_into(quizzes::table)
+                        .values(m_ins::NewQuiz {
+                            label: quiz.label.clone(),
+                            command: quiz.command.clone(),
+                            schedule: quiz.schedule.clone(),
+                        })
+                        .execute(&mut api.connection)?;
+                    summary.quizzes += 1;
+                }
+                let linked: Vec<String> = quizzes_to_categories::table
+                    .filter(quizzes_to_categories::quiz_label.eq(&quiz.label))
+                    .select(quizzes_to_categories::category_label)
+                    .load(&mut api.connection)?;
+                for category in quiz.categories.iter() {
+                    if linked.contains(category) {
+                        continue;
+                    }
+                    if !existing_categories.contains(category) {
+                        return Err(Error::CategoryNotFound(category.clone()));
+                    }
+                    // Added after the categories the quiz already asks.
+                    api.link_category_to_quiz(category, &quiz.label)?;
+                    summary.links += 1;
+                }
+            }
+
+            for person in setup.people.iter() {
+                summary.people += diesel::insert_or_ignore_into(people::table)
+                    .values(m_ins::NewPerson {
+                        label: person.label.clone(),
+                    })
+                    .execute(&mut api.connection)?;
+                if person.reminder_timer_in_days.is_some() {
+                    diesel::update(
+                        people::table
+                            .filter(people::label.eq(&person.label))
+                            .filter(people::reminder_timer_in_days.is_null()),
+                    )
+                    .set(people::reminder_timer_in_days.eq(person.reminder_timer_in_days))
+                    .execute(&mut api.connection)?;
+                }
+            }
+            Ok(summary)
+        })
+    }
+}
+
+/// The hash of what can be edited in an entry, to tell whether it changed since the last sync.
+pub fn content_hash(entry: &ExportedEntry) -> String {
+    let mut tags = entry.tags.clone();
+    tags.sort();
+    let mut hasher = Sha256::new();
+    for part in [
+        Some(entry.timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+        entry.category.clone(),
+        entry.choice.clone(),
+        entry.details.clone(),
+        Some(tags.join(" ")),
+    ] {
+        let part = part.unwrap_or_default();
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// The identifier of an entry in the exports of version 1: the start of the SHA-256 of its content.
+fn content_id(
+    timestamp: NaiveDateTime,
+    category: &Option<String>,
+    choice: &Option<String>,
+    details: &Option<String>,
+) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        Some(timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+        category.clone(),
+        choice.clone(),
+        details.clone(),
+    ] {
+        // The parts are prefixed with their length, so that moving text from one to the next changes the id.
+        let part = part.unwrap_or_default();
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())[..16].to_owned()
+}
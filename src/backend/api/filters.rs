@@ -0,0 +1,135 @@
+/// This file contains the filters for the entries, built as Diesel expressions so they run in the database.
+use crate::backend::schema;
+use diesel::dsl::not;
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Nullable};
+use diesel::sqlite::Sqlite;
+
+type Condition =
+    Box<dyn BoxableExpression<schema::entries::table, Sqlite, SqlType = Nullable<Bool>>>;
+
+/// Which entries to keep. Each list matches any of its labels. The conditions are combined with AND, or with OR if
+/// `any` is set. The excluded categories are always left out, whatever the other conditions.
+#[derive(Debug, Default, Clone)]
+pub struct EntryFilter {
+    pub categories: Vec<String>,
+    pub choices: Vec<String>,
+    pub quizzes: Vec<String>,
+    /// The tags must already be normalized.
+    pub tags: Vec<String>,
+    pub has_details: bool,
+    /// Only the entries without a category, i.e. the quick notes.
+    pub notes_only: bool,
+    pub exclude_categories: Vec<String>,
+    pub any: bool,
+}
+
+impl EntryFilter {
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+            && self.choices.is_empty()
+            && self.quizzes.is_empty()
+            && self.tags.is_empty()
+            && !self.has_details
+            && !self.notes_only
+            && self.exclude_categories.is_empty()
+    }
+
+    /// Returns the condition for the `entries` table, or `None` if every entry passes.
+    pub fn to_condition(&self) -> Option<Condition> {
+        use schema::{categories, choices, entries, entries_to_tags, quizzes_to_categories, tags};
+
+        let mut conditions: Vec<Condition> = Vec::new();
+        if !self.categories.is_empty() {
+            conditions.push(Box::new(
+                entries::category
+                    .eq_any(
+                        categories::table
+                            .filter(categories::label.eq_any(self.categories.clone()))
+                            .select(categories::id.nullable()),
+                    )
+                    .nullable(),
+            ));
+        }
+        if !self.choices.is_empty() {
+            conditions.push(Box::new(
+                entries::value
+                    .eq_any(
+                        choices::table
+                            .filter(choices::label.eq_any(self.choices.clone()))
+                            .select(choices::id.nullable()),
+                    )
+                    .nullable(),
+            ));
+        }
+        if !self.quizzes.is_empty() {
+            conditions.push(
+                Box::new(
+                    entries::category
+                        .eq_any(
+                            categories::table
+                                .inner_join(quizzes_to_categories::table.on(
+                                    quizzes_to_categories::category_label.eq(categories::label),
+                                ))
+                                .filter(
+                                    quizzes_to_categories::quiz_label.eq_any(self.quizzes.clone()),
+                                )
+                                .select(categories::id.nullable()),
+                        )
+                        .nullable(),
+                ),
+            );
+        }
+        if !self.tags.is_empty() {
+            conditions.push(Box::new(
+                entries::id
+                    .eq_any(
+                        entries_to_tags::table
+                            .inner_join(tags::table)
+                            .filter(tags::label.eq_any(self.tags.clone()))
+                            .select(entries_to_tags::entry_id),
+                    )
+                    .nullable(),
+            ));
+        }
+        if self.has_details {
+            conditions.push(Box::new(
+                entries::details
+                    .is_not_null()
+                    .nullable()
+                    .and(entries::details.ne("")),
+            ));
+        }
+        if self.notes_only {
+            conditions.push(Box::new(entries::category.is_null().nullable()));
+        }
+
+        let combined = conditions.into_iter().reduce(|a, b| {
+            if self.any {
+                Box::new(a.or(b))
+            } else {
+                Box::new(a.and(b))
+            }
+        });
+
+        if self.exclude_categories.is_empty() {
+            return combined;
+        }
+        // A NULL category is never in the list, but `NOT IN` would be NULL too, so it's checked separately.
+        let not_excluded: Condition = Box::new(
+            entries::category
+                .is_null()
+                .nullable()
+                .or(not(entries::category.eq_any(
+                    categories::table
+                        .filter(categories::label.eq_any(self.exclude_categories.clone()))
+                        .select(categories::id.nullable()),
+                ))
+                .nullable()),
+        );
+        Some(match combined {
+            Some(condition) => Box::new(condition.and(not_excluded)),
+            None => not_excluded,
+        })
+    }
+}
@@ -0,0 +1,183 @@
+/// This file contains the functions for the people (`@name`) and places (`+name`) mentioned in the entries.
+use super::Api;
+use crate::backend::schema;
+use crate::errors::Error;
+use crate::models::{insertable as m_ins, queryable_or_selectable as m_qos};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use super::tags::{extract_marked_words, is_tag_character};
+
+/// The people and places mentioned in a text.
+#[derive(Debug, Default, PartialEq)]
+pub struct Mentions {
+    pub people: Vec<String>,
+    pub places: Vec<String>,
+}
+
+/// Returns the label of the person or place without its marker, keeping its case, or `None` if it isn't valid.
+/// A label is made of letters, digits, `_` and `-`, and must contain a letter.
+pub fn normalize_mention(label: &str) -> Option<String> {
+    let label = label.trim().trim_start_matches(['@', '+']);
+    let valid = label.chars().all(is_tag_character) && label.chars().any(char::is_alphabetic);
+    valid.then(|| label.to_owned())
+}
+
+/// Returns the `@people` and `+places` mentioned in the text, without duplicates, in the order they appear.
+/// Like hashtags, a mention starts at the beginning of the text or after a space or an opening bracket,
+/// so e-mail addresses and numbers like `+1` aren't mentions.
+pub fn extract_mentions(text: &str) -> Mentions {
+    Mentions {
+        people: extract_marked_words(text, '@', normalize_mention),
+        places: extract_marked_words(text, '+', normalize_mention),
+    }
+}
+
+/// Links the mentioned people and places to the entry, creating the ones that don't exist yet.
+/// Returns how many links were added. Takes the connection so it can run in the same transaction as the insertion
+/// of the entry.
+pub(crate) fn link_mentions(
+    connection: &mut SqliteConnection,
+    entry_id: i32,
+    mentions: &Mentions,
+) -> Result<usize, diesel::result::Error> {
+    use schema::{entries_to_people, entries_to_places, people, places};
+
+    let mut added = 0;
+    // The labels are unique regardless of case, so the first spelling is kept.
+    for person in mentions.people.iter() {
+        diesel::insert_or_ignore_into(people::table)
+            .values(m_ins::NewPerson {
+                label: person.clone(),
+            })
+            .execute(connection)?;
+        let person_id = people::table
+            .filter(people::label.eq(person))
+            .select(people::id)
+            .first::<i32>(connection)?;
+        added += diesel::insert_or_ignore_into(entries_to_people::table)
+            .values(m_ins::NewEntryToPerson {
+                entry_id,
+                person_id,
+            })
+            .execute(connection)?;
+    }
+    for place in mentions.places.iter() {
+        diesel::insert_or_ignore_into(places::table)
+            .values(m_ins::NewPlace {
+                label: place.clone(),
+            })
+            .execute(connection)?;
+        let place_id = places::table
+            .filter(places::label.eq(place))
+            .select(places::id)
+            .first::<i32>(connection)?;
+        added += diesel::insert_or_ignore_into(entries_to_places::table)
+            .values(m_ins::NewEntryToPlace { entry_id, place_id })
+            .execute(connection)?;
+    }
+    Ok(added)
+}
+
+impl Api {
+    /// Returns every person, with how many entries mention them and when they were last mentioned.
+    pub fn get_people_with_mentions(
+        &mut self,
+    ) -> Result<Vec<(m_qos::Person, i64, Option<chrono::NaiveDateTime>)>, diesel::result::Error>
+    {
+        use schema::{entries, entries_to_people, people};
+
+        people::table
+            .left_join(entries_to_people::table.left_join(entries::table))
+            .group_by(people::id)
+            .select((
+                people::all_columns,
+                diesel::dsl::count(entries_to_people::id.nullable()),
+                diesel::dsl::max(entries::timestamp.nullable()),
+            ))
+            .order(people::label)
+            .load::<(m_qos::Person, i64, Option<chrono::NaiveDateTime>)>(&mut self.connection)
+    }
+
+    /// Returns every place, with how many entries mention it and when it was last mentioned.
+    pub fn get_places_with_mentions(
+        &mut self,
+    ) -> Result<Vec<(String, i64, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
+        use schema::{entries, entries_to_places, places};
+
+        places::table
+            .left_join(entries_to_places::table.left_join(entries::table))
+            .group_by(places::id)
+            .select((
+                places::label,
+                diesel::dsl::count(entries_to_places::id.nullable()),
+                diesel::dsl::max(entries::timestamp.nullable()),
+            ))
+            .order(places::label)
+            .load::<(String, i64, Option<chrono::NaiveDateTime>)>(&mut self.connection)
+    }
+
+    /// Returns the people that have a reminder timer, each paired with the latest timestamp of the entries mentioning them.
+    pub fn get_latest_timestamps_for_people_with_timers(
+        &mut self,
+    ) -> Result<Vec<(m_qos::Person, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
+        Ok(self
+            .get_people_with_mentions()?
+            .into_iter()
+            .filter(|(person, _, _)| person.reminder_timer_in_days.is_some())
+            .map(|(person, _, last_mentioned)| (person, last_mentioned))
+            .collect())
+    }
+
+    /// Sets (or clears, with `None`) the reminder timer of the person. Only the people already mentioned have one, so
+    /// that the timer counts from their last mention.
+    pub fn set_reminder_for_person(
+        &mut self,
+        person: &str,
+        timer_in_days: Option<i32>,
+    ) -> Result<(), Error> {
+        use schema::people;
+
+        let updated = diesel::update(people::table.filter(people::label.eq(person)))
+            .set(people::reminder_timer_in_days.eq(timer_in_days))
+            .execute(&mut self.connection)?;
+        if updated == 0 {
+            return Err(Error::PersonNotFound(person.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Extracts the mentions from the details of every entry again, e.g. for the entries added before mentions existed.
+    /// Never removes mentions. Returns how many links were added.
+    pub fn rescan_mentions(&mut self) -> Result<usize, Error> {
+        use schema::entries;
+
+        let details: Vec<(i32, Option<String>)> = entries::table
+            .filter(entries::details.is_not_null())
+            .select((entries::id, entries::details))
+            .order(entries::timestamp)
+            .load(&mut self.connection)?;
+        let details = details
+            .into_iter()
+            .map(|(entry_id, details)| Ok((entry_id, self.open_details(details)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        self.connection.transaction(|connection| {
+            let mut added = 0;
+            for (entry_id, details) in details {
+                let mentions = extract_mentions(details.as_deref().unwrap_or(""));
+                added += link_mentions(connection, entry_id, &mentions)?;
+            }
+            Ok(added)
+        })
+    }
+}
+
+#[test]
+fn test_extract_mentions() {
+    let mentions =
+        extract_mentions("Lunch with @Monica and @ana-maria at +Central_Perk, mail me at a@b.com or +1 555. @monica!");
+    assert_eq!(mentions.people, vec!["Monica", "ana-maria"]);
+    assert_eq!(mentions.places, vec!["Central_Perk"]);
+    assert_eq!(extract_mentions("nothing here"), Mentions::default());
+}
@@ -1,283 +1,797 @@
-use crate::backend::establish_connection;
 use crate::backend::schema;
+use crate::backend::{establish_connection, get_database_path, migrations};
+use crate::errors::Error;
 use crate::models::{insertable as m_ins, queryable_or_selectable as m_qos};
+use diesel::connection::TransactionManager;
 use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
 use std::collections::BTreeMap;
 
+pub mod encryption;
+pub mod export;
+pub mod filters;
+pub mod mentions;
 pub mod patch;
+pub mod sync;
+pub mod tags;
+
+pub use filters::EntryFilter;
+
+/// The access to the database, through a single connection shared by the whole run.
+/// The functions that check something before changing it run in a transaction, so nothing can change in between.
+pub struct Api {
+    connection: SqliteConnection,
+    /// The path of the database, next to which its attachments are stored.
+    path: String,
+    /// The key of an encrypted journal, once it is unlocked.
+    key: Option<crate::backend::encryption::Key>,
+}
 
-// IDEA: maybe have functions return `queries`, so they can be more modular (e.g. apply a filter on the results of a query from another function)
-// However, this is more abstraction, so only do it when it's actually necessary to refactor.
+impl Api {
+    /// Connects to the database from the settings.
+    pub fn connect() -> Result<Self, Error> {
+        Ok(Self {
+            connection: establish_connection(None)?,
+            path: get_database_path(),
+            key: None,
+        })
+    }
 
-/// Returns a map of categories to choices, from the choices in the quiz label.
-pub fn get_categories_and_choices_from_quiz_label(
-    quiz_label: &str,
-) -> Result<BTreeMap<m_qos::Category, Option<Vec<m_qos::Choice>>>, crate::errors::Error> {
-    use schema::{categories, choices, quizzes, quizzes_to_categories};
+    /// Connects to another journal than the one from the settings, e.g. to sync with it. Like the journal from the
+    /// settings, it is brought up to date with the migrations first.
+    pub fn open(path: &str) -> Result<Self, Error> {
+        use diesel_migrations::MigrationHarness;
 
-    let mut connection = establish_connection(None);
+        if !std::path::Path::new(path).is_file() {
+            return Err(Error::DatabaseNotFound(path.to_owned()));
+        }
+        let mut connection = establish_connection(Some(path))?;
+        // Otherwise the migrations would add the tables of jurnalo to any SQLite database.
+        let is_journal = connection
+            .applied_migrations()
+            .is_ok_and(|applied| !applied.is_empty());
+        if !is_journal {
+            return Err(Error::InvalidInput(format!(
+                "{} isn't a jurnalo database.",
+                path
+            )));
+        }
+        migrations::migrate_if_needed(
+            &mut connection,
+            path,
+            crate::backend::config::get().backups_to_keep.value,
+        )?;
+        Ok(Self {
+            connection,
+            path: path.to_owned(),
+            key: None,
+        })
+    }
 
-    let results: Vec<(m_qos::Category, Option<m_qos::Choice>)> = quizzes::table
-        .inner_join(
-            quizzes_to_categories::table
-                .on(quizzes_to_categories::quiz_label.eq(quiz_label.to_string())),
-        )
-        .inner_join(
-            categories::table.on(quizzes_to_categories::category_label.eq(categories::label)),
-        )
-        .left_outer_join(choices::table.on(categories::label.eq(choices::category_label)))
-        .order((quizzes_to_categories::order, choices::shortcut)) // BUG: this doesn't actually sort by the order, only by the shortcut.
-        .select((categories::all_columns, choices::all_columns.nullable()))
-        .load::<_>(&mut connection)
-        .expect("Error loading data");
-
-    let mut actual_results = BTreeMap::<m_qos::Category, Option<Vec<m_qos::Choice>>>::new();
-
-    for (cat, maybe_choice) in results {
-        if let Some(c) = maybe_choice {
-            if let Some(Some(cur_vec)) = actual_results.get(&cat) {
-                let mut new_vec = cur_vec.clone();
-                new_vec.push(c);
-                actual_results.insert(cat, Some(new_vec));
-            } else {
-                actual_results.insert(cat, Some(vec![c]));
+    /// Connects to a new journal in memory, for the tests.
+    #[cfg(test)]
+    pub(crate) fn in_memory() -> Self {
+        let mut connection = <SqliteConnection as Connection>::establish(":memory:")
+            .expect("Couldn't create the database in memory.");
+        migrations::run_pending_migrations(&mut connection).expect("Couldn't run the migrations.");
+        Self {
+            connection,
+            path: ":memory:".to_owned(),
+            key: None,
+        }
+    }
+
+    /// The path of the database.
+    pub fn database_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Runs `f` in a transaction: its changes are committed if it returns `Ok`, and rolled back otherwise.
+    /// Transactions can be nested, e.g. a function of the api that uses a transaction can be called in `f`.
+    pub fn transaction<T, E>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, E>) -> Result<T, E>
+    where
+        E: From<diesel::result::Error>,
+    {
+        type Manager = <SqliteConnection as Connection>::TransactionManager;
+
+        Manager::begin_transaction(&mut self.connection)?;
+        match f(self) {
+            Ok(value) => {
+                Manager::commit_transaction(&mut self.connection)?;
+                Ok(value)
+            }
+            Err(e) => {
+                Manager::rollback_transaction(&mut self.connection)?;
+                Err(e)
             }
-        } else {
-            actual_results.insert(cat, None);
         }
     }
 
-    Ok(actual_results)
+    /// Runs `f` in a transaction that is always rolled back, to see what a change would do without making it.
+    pub fn rolled_back<T, E>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, E>) -> Result<T, E>
+    where
+        E: From<diesel::result::Error>,
+    {
+        type Manager = <SqliteConnection as Connection>::TransactionManager;
+
+        Manager::begin_transaction(&mut self.connection)?;
+        let result = f(self);
+        Manager::rollback_transaction(&mut self.connection)?;
+        result
+    }
 }
 
-/// Returns the entries between the starting and ending dates, inclusive.
-pub fn get_entries_between_dates(
-    starting_date: chrono::NaiveDateTime,
-    end_date: chrono::NaiveDateTime,
-) -> Result<Vec<EntryWithLabelsTuple>, diesel::result::Error> {
-    // TODO: review: maybe convert directly from a date instead of a datetime.
-
-    use schema::{categories, choices, entries};
-
-    let sd = starting_date;
-    let ed = end_date;
-
-    let mut connection = establish_connection(None);
-
-    let results: Vec<EntryWithLabelsTuple> = entries::table
-        .filter(entries::timestamp.le(sd))
-        .filter(entries::timestamp.ge(ed))
-        .inner_join(categories::table)
-        .left_outer_join(choices::table)
-        .order(entries::timestamp)
-        .select((
-            entries::all_columns,
-            categories::label.nullable(),
-            choices::label.nullable(),
-        ))
-        .load::<EntryCatLabelChoiceLabel>(&mut connection)
-        .expect("Error loading entries between the dates.")
-        .iter()
-        .map(|(ent, cat, cho)| EntryWithLabelsTuple((*ent).clone(), cat.clone(), cho.clone()))
-        .collect();
+// IDEA: maybe have functions return `queries`, so they can be more modular (e.g. apply a filter on the results of a query from another function)
+// However, this is more abstraction, so only do it when it's actually necessary to refactor.
 
-    Ok(results)
-}
+impl Api {
+    /// Returns a map of categories to choices, from the choices in the quiz label.
+    pub fn get_categories_and_choices_from_quiz_label(
+        &mut self,
+        quiz_label: &str,
+    ) -> Result<BTreeMap<m_qos::Category, Option<Vec<m_qos::Choice>>>, Error> {
+        use schema::{categories, choices, quizzes, quizzes_to_categories};
+
+        let results: Vec<(m_qos::Category, Option<m_qos::Choice>)> = quizzes::table
+            .inner_join(
+                quizzes_to_categories::table
+                    .on(quizzes_to_categories::quiz_label.eq(quiz_label.to_string())),
+            )
+            .inner_join(
+                categories::table.on(quizzes_to_categories::category_label.eq(categories::label)),
+            )
+            .left_outer_join(choices::table.on(categories::label.eq(choices::category_label)))
+            .order((quizzes_to_categories::order, choices::shortcut)) // BUG: this doesn't actually sort by the order, only by the shortcut.
+            .select((categories::all_columns, choices::all_columns.nullable()))
+            .load::<_>(&mut self.connection)?;
+
+        let mut actual_results = BTreeMap::<m_qos::Category, Option<Vec<m_qos::Choice>>>::new();
+
+        for (cat, maybe_choice) in results {
+            if let Some(c) = maybe_choice {
+                if let Some(Some(cur_vec)) = actual_results.get(&cat) {
+                    let mut new_vec = cur_vec.clone();
+                    new_vec.push(c);
+                    actual_results.insert(cat, Some(new_vec));
+                } else {
+                    actual_results.insert(cat, Some(vec![c]));
+                }
+            } else {
+                actual_results.insert(cat, None);
+            }
+        }
 
-pub fn post_multiple_entries(
-    entries: Vec<(Option<i32>, Option<i32>, Option<String>)>,
-) -> Result<(), diesel::result::Error> {
-    use schema::entries;
-    let new_entries_obj: Vec<m_ins::NewEntry> = entries
-        .iter()
-        .map(|(cat_id, choice_id, comment)| m_ins::NewEntry {
-            category: *cat_id,
-            value: *choice_id,
-            details: comment.clone(),
+        Ok(actual_results)
+    }
+
+    /// Returns the entries between the starting and ending dates, inclusive.
+    pub fn get_entries_between_dates(
+        &mut self,
+        starting_date: chrono::NaiveDateTime,
+        end_date: chrono::NaiveDateTime,
+    ) -> Result<Vec<EntryWithLabelsTuple>, Error> {
+        self.get_filtered_entries_between_dates(starting_date, end_date, &EntryFilter::default())
+    }
+
+    /// Returns the entries between the starting and ending dates, inclusive, that pass the filter.
+    /// The entries without a category (the quick notes) are included, with no labels. Their details are decrypted.
+    pub fn get_filtered_entries_between_dates(
+        &mut self,
+        starting_date: chrono::NaiveDateTime,
+        end_date: chrono::NaiveDateTime,
+        filter: &EntryFilter,
+    ) -> Result<Vec<EntryWithLabelsTuple>, Error> {
+        // TODO: review: maybe convert directly from a date instead of a datetime.
+
+        use schema::{categories, choices, entries};
+
+        let sd = starting_date;
+        let ed = end_date;
+
+        let mut query = entries::table
+            .filter(entries::timestamp.ge(sd))
+            .filter(entries::timestamp.le(ed))
+            .into_boxed();
+        if let Some(condition) = filter.to_condition() {
+            query = query.filter(condition);
+        }
+
+        let mut results: Vec<EntryWithLabelsTuple> = query
+            .left_outer_join(categories::table)
+            .left_outer_join(choices::table)
+            .order(entries::timestamp)
+            .select((
+                m_qos::Entry::as_select(),
+                categories::label.nullable(),
+                choices::label.nullable(),
+            ))
+            .load::<EntryCatLabelChoiceLabel>(&mut self.connection)?
+            .iter()
+            .map(|(ent, cat, cho)| EntryWithLabelsTuple((*ent).clone(), cat.clone(), cho.clone()))
+            .collect();
+
+        for EntryWithLabelsTuple(entry, _, _) in results.iter_mut() {
+            entry.details = self.open_details(entry.details.take())?;
+        }
+        Ok(results)
+    }
+
+    /// Adds the entries to the database. If `timestamp` is `None`, the entries are timestamped with the current time.
+    /// The #hashtags in the details are added as tags of their entry, and the @people and +places as its mentions.
+    /// The details are encrypted if the journal is. Returns the ids of the new entries, in the same order.
+    pub fn post_multiple_entries(
+        &mut self,
+        entries: Vec<(Option<i32>, Option<i32>, Option<String>)>,
+        timestamp: Option<chrono::NaiveDateTime>,
+    ) -> Result<Vec<i32>, Error> {
+        use schema::entries;
+        let mut new_entries_obj: Vec<(m_ins::NewEntry, String)> = Vec::new();
+        for (cat_id, choice_id, comment) in entries {
+            let details = comment.clone().unwrap_or_default();
+            let new_entry = m_ins::NewEntry {
+                timestamp,
+                category: cat_id,
+                value: choice_id,
+                details: self.seal_details(comment)?,
+                uuid: None,
+            };
+            new_entries_obj.push((new_entry, details));
+        }
+
+        self.connection.transaction(|connection| {
+            let mut ids = Vec::new();
+            for (new_entry, details) in new_entries_obj {
+                let hashtags = tags::extract_hashtags(&details);
+                let mentions = mentions::extract_mentions(&details);
+                diesel::insert_into(entries::dsl::entries)
+                    .values(new_entry)
+                    .execute(connection)?;
+                let entry_id = diesel::select(diesel::dsl::sql::<diesel::sql_types::Integer>(
+                    "last_insert_rowid()",
+                ))
+                .get_result::<i32>(connection)?;
+                tags::link_tags(connection, entry_id, &hashtags)?;
+                mentions::link_mentions(connection, entry_id, &mentions)?;
+                ids.push(entry_id);
+            }
+            Ok(ids)
         })
-        .collect();
+    }
 
-    let mut connection = establish_connection(None);
+    /// Adds a single entry to the database, timestamped with the current time. Returns the id of the new entry.
+    pub fn post_entry(
+        &mut self,
+        category: Option<i32>,
+        value: Option<i32>,
+        details: Option<String>,
+    ) -> Result<i32, Error> {
+        let ids = self.post_multiple_entries(vec![(category, value, details)], None)?;
+        Ok(ids[0])
+    }
 
-    diesel::insert_into(entries::dsl::entries)
-        .values(new_entries_obj)
-        .execute(&mut connection)?;
+    /// Records the files, already stored in the attachments directory, as attachments of the entry.
+    pub fn post_attachments(
+        &mut self,
+        entry_id: i32,
+        files: &[crate::backend::attachments::StoredFile],
+    ) -> Result<(), diesel::result::Error> {
+        use schema::attachments;
+
+        let new_attachments: Vec<m_ins::NewAttachment> = files
+            .iter()
+            .map(|file| m_ins::NewAttachment {
+                entry_id,
+                hash: file.hash.clone(),
+                file_name: file.file_name.clone(),
+                extension: file.extension.clone(),
+            })
+            .collect();
+
+        diesel::insert_into(attachments::table)
+            .values(new_attachments)
+            .execute(&mut self.connection)?;
+
+        Ok(())
+    }
 
-    Ok(())
-}
+    /// Returns the attachments of the entries, grouped by entry id.
+    pub fn get_attachments_of_entries(
+        &mut self,
+        entry_ids: &[i32],
+    ) -> Result<BTreeMap<i32, Vec<m_qos::Attachment>>, diesel::result::Error> {
+        use schema::attachments;
+
+        let mut results: BTreeMap<i32, Vec<m_qos::Attachment>> = BTreeMap::new();
+        // SQLite limits the number of parameters of a query, so the ids are sent in chunks.
+        for chunk in entry_ids.chunks(500) {
+            for attachment in attachments::table
+                .filter(attachments::entry_id.eq_any(chunk))
+                .order(attachments::id)
+                .select(m_qos::Attachment::as_select())
+                .load::<m_qos::Attachment>(&mut self.connection)?
+            {
+                results
+                    .entry(attachment.entry_id)
+                    .or_default()
+                    .push(attachment);
+            }
+        }
 
-/// Returns pairs of choice_label + timestamps for the choices that are shown in streaks.
-pub fn get_timestamps_for_streaks_of_choices(
-) -> Result<Vec<(String, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
-    use schema::{choices, entries};
+        Ok(results)
+    }
 
-    let mut connection = establish_connection(None);
+    /// Returns the id of the latest entry.
+    pub fn get_latest_entry_id(&mut self) -> Result<i32, diesel::result::Error> {
+        use schema::entries;
 
-    let results: Vec<(String, Option<chrono::NaiveDateTime>)> = choices::table
-        .filter(choices::show_in_streaks.eq(1))
-        .left_join(entries::table)
-        .select((choices::label, entries::timestamp.nullable()))
-        .load::<_>(&mut connection)
-        .expect("Couldn't load data");
+        entries::table
+            .order((entries::timestamp.desc(), entries::id.desc()))
+            .select(entries::id)
+            .first::<i32>(&mut self.connection)
+    }
 
-    Ok(results)
-}
+    /// Returns pairs of choice_label + timestamps for the choices that are shown in streaks.
+    pub fn get_timestamps_for_streaks_of_choices(
+        &mut self,
+    ) -> Result<Vec<(String, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
+        use schema::{choices, entries};
 
-/// Returns the latest timestamp for the choice with the given id.
-pub fn get_latest_timestamp_for_choice(
-    choice_id: i32,
-) -> Result<Option<chrono::NaiveDateTime>, diesel::result::Error> {
-    use schema::{choices, entries};
+        let results: Vec<(String, Option<chrono::NaiveDateTime>)> = choices::table
+            .filter(choices::show_in_streaks.eq(1))
+            .left_join(entries::table)
+            .select((choices::label, entries::timestamp.nullable()))
+            .load::<_>(&mut self.connection)?;
 
-    let mut connection = establish_connection(None);
+        Ok(results)
+    }
 
-    let result: Option<chrono::NaiveDateTime> = choices::table
-        .filter(choices::id.eq(choice_id))
-        .left_join(entries::table)
-        .select(diesel::dsl::max(entries::timestamp).nullable())
-        .first(&mut connection)
-        .expect("Couldn't load data");
+    /// Returns the latest timestamp for the choice with the given id.
+    pub fn get_latest_timestamp_for_choice(
+        &mut self,
+        choice_id: i32,
+    ) -> Result<Option<chrono::NaiveDateTime>, diesel::result::Error> {
+        use schema::{choices, entries};
 
-    Ok(result)
-}
+        let result: Option<chrono::NaiveDateTime> = choices::table
+            .filter(choices::id.eq(choice_id))
+            .left_join(entries::table)
+            .select(diesel::dsl::max(entries::timestamp).nullable())
+            .first(&mut self.connection)?;
+
+        Ok(result)
+    }
+
+    /// Returns the choices that have a goal set, each paired with the timestamps of its entries.
+    /// Choices without any entries are paired with `None`.
+    pub fn get_timestamps_for_choices_with_goals(
+        &mut self,
+    ) -> Result<Vec<(m_qos::Choice, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
+        use schema::{choices, entries};
+
+        let results: Vec<(m_qos::Choice, Option<chrono::NaiveDateTime>)> = choices::table
+            .filter(choices::goal_times.is_not_null())
+            .filter(choices::goal_period.is_not_null())
+            .left_join(entries::table)
+            .order((choices::category_label, choices::label, entries::timestamp))
+            .select((choices::all_columns, entries::timestamp.nullable()))
+            .load::<_>(&mut self.connection)?;
+
+        Ok(results)
+    }
 
-pub fn post_category(label: &str, prompt: &str) -> Result<(), diesel::result::Error> {
-    use schema::categories;
+    /// Returns the enabled choices that have a reminder timer, each paired with the latest timestamp of its entries.
+    pub fn get_latest_timestamps_for_choices_with_timers(
+        &mut self,
+    ) -> Result<Vec<(m_qos::Choice, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
+        use schema::{choices, entries};
+
+        let results: Vec<(m_qos::Choice, Option<chrono::NaiveDateTime>)> = choices::table
+            .filter(choices::reminder_timer_in_days.is_not_null())
+            .filter(choices::disabled_bool.eq(0))
+            .left_join(entries::table)
+            .group_by(choices::id)
+            .select((
+                choices::all_columns,
+                diesel::dsl::max(entries::timestamp).nullable(),
+            ))
+            .load::<_>(&mut self.connection)?;
+
+        Ok(results)
+    }
 
-    let new_category = m_ins::NewCategory {
-        label: label.to_string(),
-        prompt: prompt.to_string(),
-        ..Default::default()
-    };
+    /// Returns the enabled categories that have a reminder timer, each paired with the latest timestamp of its entries.
+    pub fn get_latest_timestamps_for_categories_with_timers(
+        &mut self,
+    ) -> Result<Vec<(m_qos::Category, Option<chrono::NaiveDateTime>)>, diesel::result::Error> {
+        use schema::{categories, entries};
+
+        let results: Vec<(m_qos::Category, Option<chrono::NaiveDateTime>)> = categories::table
+            .filter(categories::reminder_timer_in_days.is_not_null())
+            .filter(categories::disabled_bool.eq(0))
+            .left_join(entries::table)
+            .group_by(categories::id)
+            .select((
+                categories::all_columns,
+                diesel::dsl::max(entries::timestamp).nullable(),
+            ))
+            .load::<_>(&mut self.connection)?;
+
+        Ok(results)
+    }
 
-    let mut connection = establish_connection(None);
+    /// Returns the latest timestamp for the category with the given id.
+    pub fn get_latest_timestamp_for_category(
+        &mut self,
+        category_id: i32,
+    ) -> Result<Option<chrono::NaiveDateTime>, diesel::result::Error> {
+        use schema::{categories, entries};
 
-    diesel::insert_into(categories::table)
-        .values(&new_category)
-        .execute(&mut connection)?;
+        let result: Option<chrono::NaiveDateTime> = categories::table
+            .filter(categories::id.eq(category_id))
+            .left_join(entries::table)
+            .select(diesel::dsl::max(entries::timestamp).nullable())
+            .first(&mut self.connection)?;
 
-    Ok(())
-}
+        Ok(result)
+    }
 
-pub fn post_choice(
-    label: &str,
-    shortcut: &str,
-    category_label: &str,
-) -> Result<(), diesel::result::Error> {
-    use schema::{categories, choices};
+    /// Returns when the reminder with the given key was last sent, if ever.
+    pub fn get_latest_sent_reminder(
+        &mut self,
+        reminder_key: &str,
+    ) -> Result<Option<chrono::NaiveDateTime>, diesel::result::Error> {
+        use schema::sent_reminders;
 
-    // check that category exists:
-    let mut connection = establish_connection(None);
+        let result: Option<chrono::NaiveDateTime> = sent_reminders::table
+            .filter(sent_reminders::reminder_key.eq(reminder_key))
+            .select(diesel::dsl::max(sent_reminders::sent_at))
+            .first(&mut self.connection)?;
 
-    categories::table
-        .filter(categories::label.eq(category_label))
-        .select(categories::id)
-        .first::<i32>(&mut connection)?;
+        Ok(result)
+    }
 
-    // TODO: check that shortcut is unique among the choices in the category.
+    /// Records that the reminder with the given key was sent now.
+    pub fn post_sent_reminder(&mut self, reminder_key: &str) -> Result<(), diesel::result::Error> {
+        use schema::sent_reminders;
 
-    let new_choice = m_ins::NewChoice {
-        label: label.to_string(),
-        shortcut: shortcut.to_string(),
-        category_label: category_label.to_string(),
-        ..Default::default()
-    };
+        let new_sent_reminder = m_ins::NewSentReminder {
+            reminder_key: reminder_key.to_string(),
+        };
 
-    diesel::insert_into(choices::table)
-        .values(&new_choice)
-        .execute(&mut connection)?;
+        diesel::insert_into(sent_reminders::table)
+            .values(&new_sent_reminder)
+            .execute(&mut self.connection)?;
 
-    Ok(())
-}
+        Ok(())
+    }
 
-pub fn post_quiz(label: &str) -> Result<(), diesel::result::Error> {
-    use schema::quizzes;
+    /// Returns the quizzes that have a schedule.
+    pub fn get_scheduled_quizzes(&mut self) -> Result<Vec<m_qos::Quiz>, diesel::result::Error> {
+        use schema::quizzes;
 
-    let new_quiz = m_ins::NewQuiz {
-        label: label.to_string(),
-        ..Default::default()
-    };
+        let results: Vec<m_qos::Quiz> = quizzes::table
+            .filter(quizzes::schedule.is_not_null())
+            .order(quizzes::label)
+            .load::<m_qos::Quiz>(&mut self.connection)?;
 
-    let mut connection = establish_connection(None);
+        Ok(results)
+    }
 
-    diesel::insert_into(quizzes::table)
-        .values(&new_quiz)
-        .execute(&mut connection)?;
+    /// Returns pairs of quiz_label + date for the quizzes answered for a date on or after `since`.
+    pub fn get_quiz_runs_since(
+        &mut self,
+        since: chrono::NaiveDate,
+    ) -> Result<Vec<(String, chrono::NaiveDate)>, diesel::result::Error> {
+        use schema::quiz_runs;
 
-    // This will return a unique constraint error if the quiz already exists.
+        let results: Vec<(String, chrono::NaiveDate)> = quiz_runs::table
+            .filter(quiz_runs::for_date.ge(since))
+            .select((quiz_runs::quiz_label, quiz_runs::for_date))
+            .load::<_>(&mut self.connection)?;
 
-    Ok(())
-}
+        Ok(results)
+    }
 
-pub fn get_all_categories() -> Result<Vec<m_qos::Category>, diesel::result::Error> {
-    use schema::categories;
+    /// Records that the quiz was answered for the given date.
+    pub fn post_quiz_run(
+        &mut self,
+        quiz_label: &str,
+        for_date: chrono::NaiveDate,
+    ) -> Result<(), diesel::result::Error> {
+        use schema::quiz_runs;
 
-    let mut connection = establish_connection(None);
+        let new_quiz_run = m_ins::NewQuizRun {
+            quiz_label: quiz_label.to_string(),
+            for_date,
+        };
 
-    let results: Vec<m_qos::Category> = categories::table
-        .load::<m_qos::Category>(&mut connection)
-        .expect("Error loading categories");
+        diesel::insert_into(quiz_runs::table)
+            .values(&new_quiz_run)
+            .execute(&mut self.connection)?;
 
-    Ok(results)
-}
+        Ok(())
+    }
 
-pub fn get_choices_in_category(
-    category_label: &str,
-) -> Result<Vec<m_qos::Choice>, diesel::result::Error> {
-    use schema::{categories, choices};
+    pub fn post_category(&mut self, label: &str, prompt: &str) -> Result<(), Error> {
+        use schema::categories;
+
+        self.connection.transaction(|connection| {
+            // check that the label is not already taken:
+            let existing = categories::table
+                .filter(categories::label.eq(label))
+                .select(categories::id)
+                .first::<i32>(connection)
+                .optional()?;
+            if existing.is_some() {
+                return Err(Error::CategoryAlreadyExists(label.to_owned()));
+            }
 
-    let mut connection = establish_connection(None);
+            let new_category = m_ins::NewCategory {
+                label: label.to_string(),
+                prompt: prompt.to_string(),
+                ..Default::default()
+            };
 
-    let results: Vec<m_qos::Choice> = categories::table
-        .filter(categories::label.eq(category_label))
-        .inner_join(choices::table.on(categories::label.eq(choices::category_label)))
-        .select(choices::all_columns)
-        .load::<m_qos::Choice>(&mut connection)
-        .expect("Error loading choices");
+            diesel::insert_into(categories::table)
+                .values(&new_category)
+                .execute(connection)?;
 
-    Ok(results)
-}
+            Ok(())
+        })
+    }
 
-pub fn get_categories_in_quiz(
-    quiz_label: &str,
-) -> Result<Vec<m_qos::Category>, diesel::result::Error> {
-    use schema::{categories, quizzes, quizzes_to_categories};
+    pub fn post_choice(
+        &mut self,
+        label: &str,
+        shortcut: &str,
+        category_label: &str,
+    ) -> Result<(), Error> {
+        use schema::{categories, choices};
+
+        self.connection.transaction(|connection| {
+            // check that category exists:
+            categories::table
+                .filter(categories::label.eq(category_label))
+                .select(categories::id)
+                .first::<i32>(connection)
+                .optional()?
+                .ok_or_else(|| Error::CategoryNotFound(category_label.to_owned()))?;
+
+            // check that the label is not already taken within the category:
+            let existing = choices::table
+                .filter(
+                    choices::label
+                        .eq(label)
+                        .and(choices::category_label.eq(category_label)),
+                )
+                .select(choices::id)
+                .first::<i32>(connection)
+                .optional()?;
+            if existing.is_some() {
+                return Err(Error::ChoiceAlreadyExists(label.to_owned()));
+            }
 
-    let mut connection = establish_connection(None);
+            // TODO: check that shortcut is unique among the choices in the category.
 
-    let results: Vec<m_qos::Category> = quizzes::table
-        .filter(quizzes::label.eq(quiz_label))
-        .inner_join(
-            quizzes_to_categories::table.on(quizzes::label.eq(quizzes_to_categories::quiz_label)),
-        )
-        .inner_join(
-            categories::table.on(quizzes_to_categories::category_label.eq(categories::label)),
-        )
-        .select(categories::all_columns)
-        .load::<m_qos::Category>(&mut connection)
-        .expect("Error loading categories");
+            let new_choice = m_ins::NewChoice {
+                label: label.to_string(),
+                shortcut: shortcut.to_string(),
+                category_label: category_label.to_string(),
+                ..Default::default()
+            };
 
-    Ok(results)
-}
+            diesel::insert_into(choices::table)
+                .values(&new_choice)
+                .execute(connection)?;
 
-/// Returns a vector of all quizzes, with their categories.
-pub fn get_all_quizzes() -> Result<Vec<(m_qos::Quiz, m_qos::Category)>, diesel::result::Error> {
-    use schema::{categories, quizzes, quizzes_to_categories};
+            Ok(())
+        })
+    }
+
+    pub fn post_quiz(&mut self, label: &str) -> Result<(), Error> {
+        use schema::quizzes;
+
+        self.connection.transaction(|connection| {
+            // check that the label is not already taken:
+            let existing = quizzes::table
+                .filter(quizzes::label.eq(label))
+                .select(quizzes::id)
+                .first::<i32>(connection)
+                .optional()?;
+            if existing.is_some() {
+                return Err(Error::QuizAlreadyExists(label.to_owned()));
+            }
+
+            let new_quiz = m_ins::NewQuiz {
+                label: label.to_string(),
+                ..Default::default()
+            };
 
-    let mut connection = establish_connection(None);
+            diesel::insert_into(quizzes::table)
+                .values(&new_quiz)
+                .execute(connection)?;
 
-    let results: Vec<(m_qos::Quiz, m_qos::Category)> = quizzes::table
-        .inner_join(
-            quizzes_to_categories::table.on(quizzes_to_categories::quiz_label.eq(quizzes::label)),
+            Ok(())
+        })
+    }
+
+    pub fn get_all_categories(&mut self) -> Result<Vec<m_qos::Category>, diesel::result::Error> {
+        use schema::categories;
+
+        let results: Vec<m_qos::Category> =
+            categories::table.load::<m_qos::Category>(&mut self.connection)?;
+
+        Ok(results)
+    }
+
+    pub fn get_all_choices(&mut self) -> Result<Vec<m_qos::Choice>, diesel::result::Error> {
+        use schema::choices;
+
+        let results: Vec<m_qos::Choice> = choices::table
+            .order((choices::category_label, choices::shortcut))
+            .load::<m_qos::Choice>(&mut self.connection)?;
+
+        Ok(results)
+    }
+
+    pub fn get_category_by_label(&mut self, label: &str) -> Result<m_qos::Category, Error> {
+        use schema::categories;
+
+        categories::table
+            .filter(categories::label.eq(label))
+            .first::<m_qos::Category>(&mut self.connection)
+            .optional()?
+            .ok_or_else(|| Error::CategoryNotFound(label.to_owned()))
+    }
+
+    pub fn get_choices_in_category(
+        &mut self,
+        category_label: &str,
+    ) -> Result<Vec<m_qos::Choice>, diesel::result::Error> {
+        use schema::{categories, choices};
+
+        let results: Vec<m_qos::Choice> = categories::table
+            .filter(categories::label.eq(category_label))
+            .inner_join(choices::table.on(categories::label.eq(choices::category_label)))
+            .select(choices::all_columns)
+            .load::<m_qos::Choice>(&mut self.connection)?;
+
+        Ok(results)
+    }
+
+    pub fn get_categories_in_quiz(
+        &mut self,
+        quiz_label: &str,
+    ) -> Result<Vec<m_qos::Category>, diesel::result::Error> {
+        use schema::{categories, quizzes, quizzes_to_categories};
+
+        let results: Vec<m_qos::Category> = quizzes::table
+            .filter(quizzes::label.eq(quiz_label))
+            .inner_join(
+                quizzes_to_categories::table
+                    .on(quizzes::label.eq(quizzes_to_categories::quiz_label)),
+            )
+            .inner_join(
+                categories::table.on(quizzes_to_categories::category_label.eq(categories::label)),
+            )
+            .select(categories::all_columns)
+            .load::<m_qos::Category>(&mut self.connection)?;
+
+        Ok(results)
+    }
+
+    /// Returns a vector of all quizzes, with their categories.
+    pub fn get_all_quizzes(
+        &mut self,
+    ) -> Result<Vec<(m_qos::Quiz, m_qos::Category)>, diesel::result::Error> {
+        use schema::{categories, quizzes, quizzes_to_categories};
+
+        let results: Vec<(m_qos::Quiz, m_qos::Category)> = quizzes::table
+            .inner_join(
+                quizzes_to_categories::table
+                    .on(quizzes_to_categories::quiz_label.eq(quizzes::label)),
+            )
+            .inner_join(
+                categories::table.on(quizzes_to_categories::category_label.eq(categories::label)),
+            )
+            .select((quizzes::all_columns, categories::all_columns))
+            .load::<(m_qos::Quiz, m_qos::Category)>(&mut self.connection)?;
+
+        Ok(results)
+    }
+
+    /// Searches the details and labels of the entries between the dates with the full-text index, best matches first.
+    /// The query uses the FTS5 syntax, e.g. `"a phrase"`, `prefix*`, `this OR that`.
+    /// The matches in the snippets are wrapped in the `highlight` markers.
+    #[allow(clippy::too_many_arguments)] // reason: each argument is an independent filter of the search
+    pub fn search_entries(
+        &mut self,
+        query: &str,
+        starting_date: chrono::NaiveDateTime,
+        end_date: chrono::NaiveDateTime,
+        category_label: Option<&str>,
+        tag: Option<&str>,
+        limit: i64,
+        highlight: (&str, &str),
+    ) -> Result<Vec<m_qos::SearchResult>, diesel::result::Error> {
+        use diesel::sql_types::{BigInt, Nullable, Text, Timestamp};
+
+        diesel::sql_query(
+            "SELECT entries.id, entries.timestamp, entries_fts.category_label, entries_fts.choice_label, \
+                snippet(entries_fts, -1, ?, ?, '...', 12) AS snippet \
+            FROM entries_fts INNER JOIN entries ON entries.id = entries_fts.rowid \
+            WHERE entries_fts MATCH ? \
+                AND entries.timestamp >= ? AND entries.timestamp <= ? \
+                AND (? IS NULL OR entries_fts.category_label = ?) \
+                AND (? IS NULL OR entries.id IN (SELECT entries_to_tags.entry_id FROM entries_to_tags \
+                    INNER JOIN tags ON tags.id = entries_to_tags.tag_id WHERE tags.label = ?)) \
+            ORDER BY rank \
+            LIMIT ?",
         )
-        .inner_join(
-            categories::table.on(quizzes_to_categories::category_label.eq(categories::label)),
+        .bind::<Text, _>(highlight.0)
+        .bind::<Text, _>(highlight.1)
+        .bind::<Text, _>(query)
+        .bind::<Timestamp, _>(starting_date)
+        .bind::<Timestamp, _>(end_date)
+        .bind::<Nullable<Text>, _>(category_label)
+        .bind::<Nullable<Text>, _>(category_label)
+        .bind::<Nullable<Text>, _>(tag)
+        .bind::<Nullable<Text>, _>(tag)
+        .bind::<BigInt, _>(limit)
+        .load::<m_qos::SearchResult>(&mut self.connection)
+    }
+
+    /// Returns the entries between the dates, newest first, each with its labels and, as the snippet, its tags and
+    /// mentions. Used instead of `search_entries` in an encrypted journal, whose index only has encrypted details.
+    pub fn get_entries_with_labels_and_mentions(
+        &mut self,
+        starting_date: chrono::NaiveDateTime,
+        end_date: chrono::NaiveDateTime,
+        category_label: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<Vec<m_qos::SearchResult>, diesel::result::Error> {
+        use diesel::sql_types::{Nullable, Text, Timestamp};
+
+        diesel::sql_query(
+            "SELECT entries.id, entries.timestamp, categories.label AS category_label, choices.label AS choice_label, \
+                trim(\
+                    coalesce((SELECT group_concat('#' || tags.label, ' ') FROM entries_to_tags \
+                        INNER JOIN tags ON tags.id = entries_to_tags.tag_id \
+                        WHERE entries_to_tags.entry_id = entries.id), '') || ' ' || \
+                    coalesce((SELECT group_concat('@' || people.label, ' ') FROM entries_to_people \
+                        INNER JOIN people ON people.id = entries_to_people.person_id \
+                        WHERE entries_to_people.entry_id = entries.id), '') || ' ' || \
+                    coalesce((SELECT group_concat('+' || places.label, ' ') FROM entries_to_places \
+                        INNER JOIN places ON places.id = entries_to_places.place_id \
+                        WHERE entries_to_places.entry_id = entries.id), '')\
+                ) AS snippet \
+            FROM entries LEFT JOIN categories ON categories.id = entries.category \
+                LEFT JOIN choices ON choices.id = entries.value \
+            WHERE entries.timestamp >= ? AND entries.timestamp <= ? \
+                AND (? IS NULL OR categories.label = ?) \
+                AND (? IS NULL OR entries.id IN (SELECT entries_to_tags.entry_id FROM entries_to_tags \
+                    INNER JOIN tags ON tags.id = entries_to_tags.tag_id WHERE tags.label = ?)) \
+            ORDER BY entries.timestamp DESC",
         )
-        .select((quizzes::all_columns, categories::all_columns))
-        .load::<(m_qos::Quiz, m_qos::Category)>(&mut connection)
-        .expect("Error loading quizzes");
+        .bind::<Timestamp, _>(starting_date)
+        .bind::<Timestamp, _>(end_date)
+        .bind::<Nullable<Text>, _>(category_label)
+        .bind::<Nullable<Text>, _>(category_label)
+        .bind::<Nullable<Text>, _>(tag)
+        .bind::<Nullable<Text>, _>(tag)
+        .load::<m_qos::SearchResult>(&mut self.connection)
+    }
+}
 
-    Ok(results)
+/// Returns the shortcut of a new choice: the number after the highest numeric shortcut of the other choices of its
+/// category, like in the starter setup.
+pub(crate) fn next_free_shortcut(choices: &[m_qos::Choice]) -> String {
+    let highest = choices
+        .iter()
+        .filter_map(|choice| choice.shortcut.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    (highest + 1).to_string()
 }
 
 // type-aliases
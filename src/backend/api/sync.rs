@@ -0,0 +1,147 @@
+/// This file contains what `sync` needs besides the export: the UUIDs of the entries, which identify them across
+/// journals, and the hashes of the entries as they were when last in sync, which tell which side changed an entry.
+use super::export::ExportedEntry;
+use super::{mentions, tags, Api};
+use crate::backend::schema;
+use crate::errors::Error;
+use crate::models::insertable as m_ins;
+use diesel::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+impl Api {
+    /// Returns the hashes of the entries as they were when last in sync, by UUID.
+    pub fn get_synced_hashes(&mut self) -> Result<HashMap<String, String>, diesel::result::Error> {
+        use schema::synced_entries;
+
+        Ok(synced_entries::table
+            .select((synced_entries::uuid, synced_entries::hash))
+            .load::<(String, String)>(&mut self.connection)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Records the hashes of the entries, by UUID, as they are now in sync.
+    pub fn mark_synced(
+        &mut self,
+        hashes: &[(String, String)],
+    ) -> Result<(), diesel::result::Error> {
+        use schema::synced_entries;
+
+        self.connection.transaction(|connection| {
+            for (uuid, hash) in hashes {
+                diesel::replace_into(synced_entries::table)
+                    .values(m_ins::NewSyncedEntry {
+                        uuid: uuid.clone(),
+                        hash: hash.clone(),
+                    })
+                    .execute(connection)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns which of the UUIDs belong to an entry of the journal.
+    pub fn existing_entry_uuids(
+        &mut self,
+        uuids: &[String],
+    ) -> Result<HashSet<String>, diesel::result::Error> {
+        use schema::entries;
+
+        let mut existing = HashSet::new();
+        // SQLite limits the number of parameters of a query.
+        for chunk in uuids.chunks(500) {
+            existing.extend(
+                entries::table
+                    .filter(entries::uuid.eq_any(chunk))
+                    .select(entries::uuid.assume_not_null())
+                    .load::<String>(&mut self.connection)?,
+            );
+        }
+        Ok(existing)
+    }
+
+    /// Gives the entry the UUID of the entry it was copied from.
+    pub fn set_entry_uuid(
+        &mut self,
+        entry_id: i32,
+        uuid: &str,
+    ) -> Result<(), diesel::result::Error> {
+        use schema::entries;
+
+        diesel::update(entries::table.find(entry_id))
+            .set(entries::uuid.eq(uuid))
+            .execute(&mut self.connection)?;
+        Ok(())
+    }
+
+    /// Replaces the time, labels, details and tags of the entry with the UUID of `entry` with those of `entry`. The
+    /// mentions are found again in the new details.
+    pub fn update_entry_by_uuid(&mut self, entry: &ExportedEntry) -> Result<(), Error> {
+        use schema::{categories, choices, entries, entries_to_people, entries_to_places};
+
+        let details = self.seal_details(entry.details.clone())?;
+        self.transaction(|api| {
+            let entry_id = entries::table
+                .filter(entries::uuid.eq(&entry.id))
+                .select(entries::id)
+                .first::<i32>(&mut api.connection)?;
+            let category_id = match entry.category {
+                Some(ref category) => Some(
+                    categories::table
+                        .filter(categories::label.eq(category))
+                        .select(categories::id)
+                        .first::<i32>(&mut api.connection)
+                        .optional()?
+                        .ok_or_else(|| Error::CategoryNotFound(category.clone()))?,
+                ),
+                None => None,
+            };
+            let choice_id = match (&entry.category, &entry.choice) {
+                (Some(category), Some(choice)) => Some(
+                    choices::table
+                        .filter(choices::category_label.eq(category))
+                        .filter(choices::label.eq(choice))
+                        .select(choices::id)
+                        .first::<i32>(&mut api.connection)
+                        .optional()?
+                        .ok_or_else(|| Error::ChoiceNotFound {
+                            category: category.clone(),
+                            choice: choice.clone(),
+                        })?,
+                ),
+                _ => None,
+            };
+            diesel::update(entries::table.find(entry_id))
+                .set((
+                    entries::timestamp.eq(entry.timestamp),
+                    entries::category.eq(category_id),
+                    entries::value.eq(choice_id),
+                    entries::details.eq(&details),
+                ))
+                .execute(&mut api.connection)?;
+
+            let removed: Vec<String> = api
+                .get_tags_of_entry(entry_id)?
+                .into_iter()
+                .filter(|tag| !entry.tags.contains(tag))
+                .collect();
+            api.remove_tags_from_entry(entry_id, &removed)?;
+            tags::link_tags(&mut api.connection, entry_id, &entry.tags)?;
+
+            diesel::delete(
+                entries_to_people::table.filter(entries_to_people::entry_id.eq(entry_id)),
+            )
+            .execute(&mut api.connection)?;
+            diesel::delete(
+                entries_to_places::table.filter(entries_to_places::entry_id.eq(entry_id)),
+            )
+            .execute(&mut api.connection)?;
+            mentions::link_mentions(
+                &mut api.connection,
+                entry_id,
+                &mentions::extract_mentions(entry.details.as_deref().unwrap_or_default()),
+            )?;
+            Ok(())
+        })
+    }
+}
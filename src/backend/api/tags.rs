@@ -0,0 +1,225 @@
+/// This file contains the functions for the tags of the entries.
+use super::Api;
+use crate::backend::schema;
+use crate::errors::Error;
+use crate::models::insertable as m_ins;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use std::collections::BTreeMap;
+
+use super::EntryFilter;
+
+pub(crate) fn is_tag_character(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Returns the tag for the label, lowercased and without the leading `#`, or `None` if it isn't a valid tag.
+/// A tag is made of letters, digits, `_` and `-`, and must contain a letter.
+pub fn normalize_tag(label: &str) -> Option<String> {
+    let tag = label.trim().trim_start_matches('#').to_lowercase();
+    let valid = tag.chars().all(is_tag_character) && tag.chars().any(char::is_alphabetic);
+    valid.then_some(tag)
+}
+
+/// Returns the hashtags in the text, normalized and without duplicates, in the order they appear.
+/// A hashtag starts at the beginning of the text or after a space or an opening bracket, so `C#` and URLs aren't tags.
+pub fn extract_hashtags(text: &str) -> Vec<String> {
+    extract_marked_words(text, '#', normalize_tag)
+}
+
+/// Returns the words that start with the marker (e.g. `#tag` or `@person`), normalized and without duplicates
+/// (ignoring case), in the order they appear. The marker must be at the beginning of the text or after a space or an
+/// opening bracket. The words are made of letters, digits, `_` and `-`.
+pub(crate) fn extract_marked_words(
+    text: &str,
+    marker: char,
+    normalize: fn(&str) -> Option<String>,
+) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        if c == marker && (previous.is_whitespace() || matches!(previous, '(' | '[')) {
+            let rest = &text[i + c.len_utf8()..];
+            let end = rest.find(|c| !is_tag_character(c)).unwrap_or(rest.len());
+            // A trailing `-` is more likely punctuation than part of the word.
+            let candidate = rest[..end].trim_end_matches('-');
+            if let Some(word) = normalize(candidate) {
+                if !words
+                    .iter()
+                    .any(|w| w.to_lowercase() == word.to_lowercase())
+                {
+                    words.push(word);
+                }
+            }
+        }
+        previous = c;
+    }
+    words
+}
+
+/// Links the tags to the entry, creating the tags that don't exist yet. Returns how many links were added.
+/// Takes the connection so it can run in the same transaction as the insertion of the entry.
+pub(crate) fn link_tags(
+    connection: &mut SqliteConnection,
+    entry_id: i32,
+    tags: &[String],
+) -> Result<usize, diesel::result::Error> {
+    use schema::{entries_to_tags, tags};
+
+    let mut added = 0;
+    for tag in tags {
+        diesel::insert_or_ignore_into(tags::table)
+            .values(m_ins::NewTag { label: tag.clone() })
+            .execute(connection)?;
+        let tag_id = tags::table
+            .filter(tags::label.eq(tag))
+            .select(tags::id)
+            .first::<i32>(connection)?;
+        added += diesel::insert_or_ignore_into(entries_to_tags::table)
+            .values(m_ins::NewEntryToTag { entry_id, tag_id })
+            .execute(connection)?;
+    }
+    Ok(added)
+}
+
+impl Api {
+    /// Adds the tags to the entry. The tags must already be normalized.
+    pub fn add_tags_to_entry(&mut self, entry_id: i32, tags: &[String]) -> Result<(), Error> {
+        use schema::entries;
+
+        self.connection.transaction(|connection| {
+            // confirm that the entry exists in the database:
+            entries::table
+                .filter(entries::id.eq(entry_id))
+                .select(entries::id)
+                .first::<i32>(connection)
+                .optional()?
+                .ok_or(Error::EntryNotFound(entry_id))?;
+
+            link_tags(connection, entry_id, tags)?;
+            Ok(())
+        })
+    }
+
+    /// Removes the tags from the entry. Returns how many tags were removed.
+    pub fn remove_tags_from_entry(
+        &mut self,
+        entry_id: i32,
+        tags: &[String],
+    ) -> Result<usize, diesel::result::Error> {
+        use schema::{entries_to_tags, tags};
+
+        diesel::delete(
+            entries_to_tags::table
+                .filter(entries_to_tags::entry_id.eq(entry_id))
+                .filter(
+                    entries_to_tags::tag_id.eq_any(
+                        tags::table
+                            .filter(tags::label.eq_any(tags))
+                            .select(tags::id),
+                    ),
+                ),
+        )
+        .execute(&mut self.connection)
+    }
+
+    /// Returns the tags of the entry, in alphabetical order.
+    pub fn get_tags_of_entry(
+        &mut self,
+        entry_id: i32,
+    ) -> Result<Vec<String>, diesel::result::Error> {
+        use schema::{entries_to_tags, tags};
+
+        entries_to_tags::table
+            .inner_join(tags::table)
+            .filter(entries_to_tags::entry_id.eq(entry_id))
+            .select(tags::label)
+            .order(tags::label)
+            .load::<String>(&mut self.connection)
+    }
+
+    /// Returns the tags of the entries, in alphabetical order, grouped by entry id.
+    pub fn get_tags_of_entries(
+        &mut self,
+        entry_ids: &[i32],
+    ) -> Result<BTreeMap<i32, Vec<String>>, diesel::result::Error> {
+        use schema::{entries_to_tags, tags};
+
+        let mut results: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+        // SQLite limits the number of parameters of a query, so the ids are sent in chunks.
+        for chunk in entry_ids.chunks(500) {
+            for (entry_id, tag) in entries_to_tags::table
+                .inner_join(tags::table)
+                .filter(entries_to_tags::entry_id.eq_any(chunk))
+                .select((entries_to_tags::entry_id, tags::label))
+                .order(tags::label)
+                .load::<(i32, String)>(&mut self.connection)?
+            {
+                results.entry(entry_id).or_default().push(tag);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Returns how many entries between the dates that pass the filter have each tag, most used first.
+    pub fn get_tag_counts_between_dates(
+        &mut self,
+        starting_date: chrono::NaiveDateTime,
+        end_date: chrono::NaiveDateTime,
+        filter: &EntryFilter,
+    ) -> Result<Vec<(String, i64)>, diesel::result::Error> {
+        use schema::{entries, entries_to_tags, tags};
+
+        let mut matching_entries = entries::table
+            .filter(entries::timestamp.ge(starting_date))
+            .filter(entries::timestamp.le(end_date))
+            .select(entries::id)
+            .into_boxed();
+        if let Some(condition) = filter.to_condition() {
+            matching_entries = matching_entries.filter(condition);
+        }
+
+        entries_to_tags::table
+            .inner_join(tags::table)
+            .filter(entries_to_tags::entry_id.eq_any(matching_entries))
+            .group_by(tags::label)
+            .select((tags::label, diesel::dsl::count_star()))
+            .order((diesel::dsl::count_star().desc(), tags::label))
+            .load::<(String, i64)>(&mut self.connection)
+    }
+
+    /// Extracts the hashtags from the details of every entry again, e.g. for the entries added before tags existed.
+    /// Never removes tags. Returns how many links were added.
+    pub fn rescan_hashtags(&mut self) -> Result<usize, Error> {
+        use schema::entries;
+
+        let details: Vec<(i32, Option<String>)> = entries::table
+            .filter(entries::details.is_not_null())
+            .select((entries::id, entries::details))
+            .load(&mut self.connection)?;
+        let details = details
+            .into_iter()
+            .map(|(entry_id, details)| Ok((entry_id, self.open_details(details)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        self.connection.transaction(|connection| {
+            let mut added = 0;
+            for (entry_id, details) in details {
+                let tags = extract_hashtags(details.as_deref().unwrap_or(""));
+                added += link_tags(connection, entry_id, &tags)?;
+            }
+            Ok(added)
+        })
+    }
+}
+
+#[test]
+fn test_extract_hashtags() {
+    assert_eq!(
+        extract_hashtags("#Coffee with Ana (#friends), #coffee again #work-life- #2026"),
+        vec!["coffee", "friends", "work-life"]
+    );
+    assert!(extract_hashtags("I code in C# and read https://example.com/#top").is_empty());
+    assert_eq!(normalize_tag("#Deep_Work"), Some("deep_work".to_owned()));
+    assert_eq!(normalize_tag("two words"), None);
+}
@@ -0,0 +1,89 @@
+/// This file contains the storage of the attached files, in the attachments directory next to the database.
+/// Each file is named after the SHA-256 of its content, so the same file is only stored once.
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const ATTACHMENTS_DIRECTORY: &str = "attachments";
+
+/// A file copied into the attachments directory.
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub hash: String,
+    /// The name of the original file, shown in the exports.
+    pub file_name: String,
+    pub extension: Option<String>,
+}
+
+/// Returns the attachments directory of the database from the settings.
+pub fn attachments_dir() -> PathBuf {
+    attachments_dir_of(Path::new(&super::get_database_path()))
+}
+
+/// Returns the attachments directory of the database, next to it, as an absolute path so the exported links work from
+/// anywhere.
+pub fn attachments_dir_of(database_path: &Path) -> PathBuf {
+    let directory = database_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(ATTACHMENTS_DIRECTORY);
+    std::path::absolute(&directory).unwrap_or(directory)
+}
+
+/// Returns where the file with this hash and extension is stored, e.g. `attachments/ab/abcdef....jpg`.
+pub fn stored_path(hash: &str, extension: Option<&str>) -> PathBuf {
+    stored_path_in(&attachments_dir(), hash, extension)
+}
+
+/// Returns where the file with this hash and extension is stored in the attachments directory.
+pub fn stored_path_in(directory: &Path, hash: &str, extension: Option<&str>) -> PathBuf {
+    let file_name = match extension {
+        Some(extension) => format!("{}.{}", hash, extension),
+        None => hash.to_owned(),
+    };
+    directory.join(&hash[..2]).join(file_name)
+}
+
+/// Copies the file into the attachments directory, unless a file with the same content is already there.
+pub fn store_file(path: &Path) -> Result<StoredFile, std::io::Error> {
+    store_file_in(&attachments_dir(), path)
+}
+
+/// Copies the file into the attachments directory, e.g. of another journal than the one from the settings.
+pub fn store_file_in(directory: &Path, path: &Path) -> Result<StoredFile, std::io::Error> {
+    let content = std::fs::read(path)?;
+    let hash = format!("{:x}", Sha256::digest(&content));
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| hash.clone());
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+
+    let destination = stored_path_in(directory, &hash, extension.as_deref());
+    if !destination.exists() {
+        std::fs::create_dir_all(destination.parent().expect("The path has a directory."))?;
+        // Written to a temporary file first, so an interrupted copy never looks like a stored file.
+        let temporary = destination.with_extension("partial");
+        std::fs::write(&temporary, &content)?;
+        std::fs::rename(&temporary, &destination)?;
+    }
+
+    Ok(StoredFile {
+        hash,
+        file_name,
+        extension,
+    })
+}
+
+/// Returns the MIME type of an image extension, or `None` if it isn't an image.
+pub fn image_mime_type(extension: Option<&str>) -> Option<&'static str> {
+    match extension? {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        _ => None,
+    }
+}
@@ -0,0 +1,275 @@
+/// This file contains the backups of the database, made with SQLite's online backup API so they are consistent even
+/// while the database is in use. They are kept in the backups directory next to the database:
+/// - `<name>-<timestamp>.sqlite`, made by `jurnalo backup`, are kept until they are deleted by hand.
+/// - `<name>-auto-<timestamp>.sqlite` are made automatically: once a day before the commands that change or remove
+///   existing data, and every time before a migration or a restore. Only the latest `backups_to_keep` are kept.
+///
+/// The attachments aren't part of the backups: they are never modified or removed, so they don't need one.
+use rusqlite::{backup::Backup, Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::migrations;
+use crate::errors::Error;
+
+const BACKUPS_DIRECTORY: &str = "backups";
+const AUTOMATIC_MARKER: &str = "auto";
+const BACKUP_EXTENSION: &str = "sqlite";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// The database is copied a few pages at a time, pausing in between so the other connections can use it.
+const PAGES_PER_STEP: std::os::raw::c_int = 256;
+const PAUSE_BETWEEN_STEPS: Duration = Duration::from_millis(5);
+
+/// Returns the backups directory of the database, next to it.
+pub fn backups_dir(database_path: &Path) -> PathBuf {
+    database_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(BACKUPS_DIRECTORY)
+}
+
+/// Makes a backup of the database, to `destination` if given (a file, or a directory to put it in), or else to the
+/// backups directory. Returns the path of the backup.
+pub fn backup(database_path: &Path, destination: Option<&Path>) -> Result<PathBuf, Error> {
+    if !database_path.exists() {
+        return Err(Error::DatabaseNotFound(database_path.display().to_string()));
+    }
+    let destination = match destination {
+        Some(path) if path.is_dir() => new_backup_path(path, database_path, false),
+        Some(path) => path.to_owned(),
+        None => {
+            let directory = backups_dir(database_path);
+            std::fs::create_dir_all(&directory)?;
+            new_backup_path(&directory, database_path, false)
+        }
+    };
+    if destination.exists() {
+        return Err(Error::InvalidInput(format!(
+            "{} already exists.",
+            destination.display()
+        )));
+    }
+    copy_database(database_path, &destination)?;
+    Ok(destination)
+}
+
+/// Makes an automatic backup of the database, unless there is already one from today (or `always` is set), then removes
+/// the oldest automatic backups beyond `keep`. Nothing is done if `keep` is 0.
+/// Returns the path of the new backup, if one was made.
+pub fn automatic_backup(
+    database_path: &Path,
+    keep: u32,
+    always: bool,
+) -> Result<Option<PathBuf>, Error> {
+    if keep == 0 || !database_path.exists() {
+        return Ok(None);
+    }
+
+    let today = format!(
+        "{}-{}-{}",
+        database_name(database_path),
+        AUTOMATIC_MARKER,
+        chrono::Local::now().format("%Y%m%d")
+    );
+    let has_backup_from_today = automatic_backups(database_path)?.iter().any(|path| {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(&today))
+    });
+
+    let mut made = None;
+    if always || !has_backup_from_today {
+        let directory = backups_dir(database_path);
+        std::fs::create_dir_all(&directory)?;
+        let destination = new_backup_path(&directory, database_path, true);
+        copy_database(database_path, &destination)?;
+        made = Some(destination);
+    }
+
+    let backups = automatic_backups(database_path)?;
+    let excess = backups.len().saturating_sub(keep as usize);
+    for old_backup in backups.iter().take(excess) {
+        std::fs::remove_file(old_backup)?;
+    }
+    Ok(made)
+}
+
+/// Replaces the database with the backup, after checking that it is a journal this version of jurnalo can use.
+/// The current database gets an automatic backup first, even if they are turned off, since it is about to be replaced.
+/// The backup is copied next to the database and then renamed over it, so the database is never left half-written.
+/// Returns the path of the backup of the replaced database, if there was one.
+pub fn restore(
+    database_path: &Path,
+    backup_path: &Path,
+    keep: u32,
+) -> Result<Option<PathBuf>, Error> {
+    if !backup_path.is_file() {
+        return Err(Error::InvalidInput(format!(
+            "There is no backup at {}.",
+            backup_path.display()
+        )));
+    }
+    check_backup(backup_path)?;
+
+    let mut temporary = database_path.as_os_str().to_owned();
+    temporary.push(".restoring");
+    let temporary = PathBuf::from(temporary);
+    copy_database(backup_path, &temporary)?;
+
+    let saved = match automatic_backup(database_path, keep.max(1), true) {
+        Ok(saved) => saved,
+        Err(e) => {
+            std::fs::remove_file(&temporary).ok();
+            return Err(e);
+        }
+    };
+    std::fs::rename(&temporary, database_path)?;
+    Ok(saved)
+}
+
+/// Checks that the file is an intact jurnalo database, with no migration newer than this version of jurnalo.
+/// Older ones are fine: they are migrated when they are next opened.
+fn check_backup(backup_path: &Path) -> Result<(), Error> {
+    let not_a_journal = || {
+        Error::InvalidInput(format!(
+            "{} isn't a jurnalo database.",
+            backup_path.display()
+        ))
+    };
+
+    let connection = Connection::open_with_flags(backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity: String = connection
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|_| not_a_journal())?;
+    if integrity != "ok" {
+        return Err(Error::InvalidInput(format!(
+            "{} is damaged: {}",
+            backup_path.display(),
+            integrity
+        )));
+    }
+
+    let mut statement = connection
+        .prepare("SELECT version FROM __diesel_schema_migrations")
+        .map_err(|_| not_a_journal())?;
+    let applied = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    if applied.is_empty() {
+        return Err(not_a_journal());
+    }
+    migrations::check_applied_versions(&applied)
+}
+
+/// Copies the database with the online backup API, through a temporary file so an interrupted copy never looks like a
+/// backup.
+fn copy_database(source: &Path, destination: &Path) -> Result<(), Error> {
+    let mut temporary = destination.as_os_str().to_owned();
+    temporary.push(".partial");
+    let temporary = PathBuf::from(temporary);
+
+    let copy = || -> Result<(), rusqlite::Error> {
+        let source = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut target = Connection::open(&temporary)?;
+        let backup = Backup::new(&source, &mut target)?;
+        backup.run_to_completion(PAGES_PER_STEP, PAUSE_BETWEEN_STEPS, None)
+    };
+    if let Err(e) = copy() {
+        std::fs::remove_file(&temporary).ok();
+        return Err(e.into());
+    }
+    std::fs::rename(&temporary, destination)?;
+    Ok(())
+}
+
+/// Returns the automatic backups of the database, the oldest first.
+fn automatic_backups(database_path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let directory = backups_dir(database_path);
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let prefix = format!("{}-{}-", database_name(database_path), AUTOMATIC_MARKER);
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_automatic_backup = path.file_name().is_some_and(|name| {
+            let name = name.to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(&suffix)
+        });
+        if is_automatic_backup {
+            backups.push(path);
+        }
+    }
+    // The timestamps (and the suffixes of the backups made in the same second) sort in chronological order, once the
+    // extension is left out.
+    backups.sort_by_key(|path| path.file_stem().map(|stem| stem.to_owned()));
+    Ok(backups)
+}
+
+/// Returns `<name>-<timestamp>.sqlite` in the directory, or `<name>-auto-<timestamp>.sqlite` for the automatic
+/// backups. A backup made in the same second as another one gets a suffix, e.g. `<name>-<timestamp>-2.sqlite`.
+fn new_backup_path(directory: &Path, database_path: &Path, automatic: bool) -> PathBuf {
+    let marker = if automatic {
+        format!("{}-", AUTOMATIC_MARKER)
+    } else {
+        String::new()
+    };
+    let stem = format!(
+        "{}-{}{}",
+        database_name(database_path),
+        marker,
+        chrono::Local::now().format(TIMESTAMP_FORMAT)
+    );
+    let mut path = directory.join(format!("{}.{}", stem, BACKUP_EXTENSION));
+    let mut suffix = 1;
+    while path.exists() {
+        suffix += 1;
+        path = directory.join(format!("{}-{}.{}", stem, suffix, BACKUP_EXTENSION));
+    }
+    path
+}
+
+/// The name of the database file, without its extension, e.g. `jurnalo` for `jurnalo.sqlite`.
+fn database_name(database_path: &Path) -> String {
+    database_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "jurnalo".to_owned())
+}
+
+#[test]
+fn test_automatic_backups_are_rotated_and_restored() {
+    let directory = std::env::temp_dir().join(format!("jurnalo-backups-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let database_path = directory.join("journal.sqlite");
+    let database_url = database_path.display().to_string();
+    let mut connection =
+        <diesel::SqliteConnection as diesel::Connection>::establish(&database_url).unwrap();
+    migrations::run_pending_migrations(&mut connection).unwrap();
+    drop(connection);
+
+    // The timestamps are to the second, so older backups are faked with older names.
+    let backups_directory = backups_dir(&database_path);
+    std::fs::create_dir_all(&backups_directory).unwrap();
+    for day in 1..=3 {
+        let name = format!("journal-auto-2000010{}-000000.sqlite", day);
+        copy_database(&database_path, &backups_directory.join(name)).unwrap();
+    }
+    let made = automatic_backup(&database_path, 2, false).unwrap().unwrap();
+    assert_eq!(automatic_backups(&database_path).unwrap().len(), 2);
+    assert!(automatic_backups(&database_path).unwrap().contains(&made));
+    // There is already one from today.
+    assert!(automatic_backup(&database_path, 2, false)
+        .unwrap()
+        .is_none());
+
+    assert!(restore(&database_path, &made, 2).is_ok());
+    let not_a_journal = directory.join("notes.sqlite");
+    rusqlite::Connection::open(&not_a_journal).unwrap();
+    assert!(matches!(
+        restore(&database_path, &not_a_journal, 2),
+        Err(Error::InvalidInput(_))
+    ));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,322 @@
+/// This file contains the settings of jurnalo, read from `config.toml` in the `jurnalo` directory of the user's config
+/// directory (e.g. `~/.config/jurnalo/config.toml`):
+/// ```toml
+/// database = "/home/me/journal.sqlite"
+/// default_quiz = "daily"
+/// day_starts_at = 4
+/// print_days = 30
+/// print_format = "html"
+/// color = "never"
+/// editor = "nvim"
+/// notify_command = "notify-send {title} {message}"
+/// backups_to_keep = 7
+/// unlock_minutes = 15
+/// ```
+/// Each setting can be overridden with its environment variable (which can be set in the .env), and some with
+/// arguments of the commands: the arguments take precedence over the environment, which takes precedence over the
+/// config file, which takes precedence over the defaults.
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde_derive::Deserialize;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use super::profiles;
+use crate::errors::Error;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+const DEFAULT_DATABASE_FILE_NAME: &str = "jurnalo.sqlite";
+pub const DATABASE_ENV_VAR: &str = "DATABASE_URL";
+const DEFAULT_PRINT_DAYS: u32 = 7;
+const DEFAULT_NOTIFY_TEMPLATE: &str = "{label}: {status}";
+const DEFAULT_EDITOR: &str = "vi";
+const DEFAULT_BACKUPS_TO_KEEP: u32 = 7;
+const DEFAULT_UNLOCK_MINUTES: u32 = 15;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The settings as written in the config file. All of them are optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    database: Option<String>,
+    default_quiz: Option<String>,
+    day_starts_at: Option<u32>,
+    print_days: Option<u32>,
+    print_format: Option<String>,
+    color: Option<String>,
+    editor: Option<String>,
+    notify_command: Option<String>,
+    notify_template: Option<String>,
+    backups_to_keep: Option<u32>,
+    unlock_minutes: Option<u32>,
+}
+
+/// Whether the output is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Only when the output is a terminal.
+    Auto,
+    Always,
+    Never,
+}
+
+/// The value of a setting and where it came from, e.g. `JURNALO_EDITOR` or `config file`.
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: String,
+}
+
+/// The effective settings.
+#[derive(Debug)]
+pub struct Config {
+    pub database: Setting<String>,
+    /// The quiz run by `jurnalo` without arguments.
+    pub default_quiz: Setting<Option<String>>,
+    /// The hour (0 to 23) at which a new day starts: the quizzes answered and the entries added before it count for the
+    /// previous day, e.g. in the streaks, goals and stats.
+    pub day_starts_at: Setting<u32>,
+    /// How many days `print` prints by default.
+    pub print_days: Setting<u32>,
+    /// The default format of `print`, `markdown` or `html`.
+    pub print_format: Setting<Option<String>>,
+    pub color: Setting<ColorChoice>,
+    /// The editor opened by `log` without a note.
+    pub editor: Setting<String>,
+    /// The command `remind` sends the notifications with.
+    pub notify_command: Setting<Option<String>>,
+    pub notify_template: Setting<String>,
+    /// How many automatic backups of the database are kept, 0 to make none.
+    pub backups_to_keep: Setting<u32>,
+    /// How long an encrypted journal stays unlocked after the passphrase is typed, 0 to ask for it every time.
+    pub unlock_minutes: Setting<u32>,
+}
+
+/// Returns the directory with the config files of jurnalo, e.g. `~/.config/jurnalo`.
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("jurnalo")
+}
+
+/// Returns the directory with the data of jurnalo, where the databases are created by default,
+/// e.g. `~/.local/share/jurnalo`.
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("jurnalo")
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join(CONFIG_FILE_NAME)
+}
+
+/// Loads the settings, the first time it is called. It is called before running the commands that use them, so that an
+/// invalid config is reported as an error.
+pub fn init() -> Result<&'static Config, Error> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(config);
+    }
+    let config = load()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Returns the effective settings, loaded by `init`.
+pub fn get() -> &'static Config {
+    CONFIG
+        .get()
+        .expect("The settings are loaded before running the commands.")
+}
+
+fn load() -> Result<Config, Error> {
+    dotenvy::dotenv().ok();
+    let file = load_config_file()?;
+
+    let database = match profiles::current_profile()? {
+        Some((name, profile, selected_by)) => Setting {
+            value: profile.database,
+            source: format!("profile `{}` ({})", name, selected_by),
+        },
+        None => resolve(DATABASE_ENV_VAR, file.database, parse_text)?.unwrap_or_else(|| Setting {
+            value: data_dir()
+                .join(DEFAULT_DATABASE_FILE_NAME)
+                .display()
+                .to_string(),
+            source: "default".to_owned(),
+        }),
+    };
+
+    let day_starts_at = resolve("JURNALO_DAY_STARTS_AT", file.day_starts_at, parse_number)?
+        .unwrap_or_else(|| default(0));
+    if day_starts_at.value > 23 {
+        return Err(Error::InvalidConfig(format!(
+            "`day_starts_at` must be an hour between 0 and 23, got {} from {}.",
+            day_starts_at.value, day_starts_at.source
+        )));
+    }
+
+    let color = match file.color {
+        Some(ref color) => Some(parse_color(color)?),
+        None => None,
+    };
+
+    Ok(Config {
+        database,
+        default_quiz: optional(resolve(
+            "JURNALO_DEFAULT_QUIZ",
+            file.default_quiz,
+            parse_text,
+        )?),
+        day_starts_at,
+        print_days: resolve("JURNALO_PRINT_DAYS", file.print_days, parse_number)?
+            .unwrap_or_else(|| default(DEFAULT_PRINT_DAYS)),
+        print_format: optional(resolve(
+            "JURNALO_PRINT_FORMAT",
+            file.print_format,
+            parse_text,
+        )?),
+        color: resolve("JURNALO_COLOR", color, parse_color)?
+            .unwrap_or_else(|| default(ColorChoice::Auto)),
+        editor: resolve("JURNALO_EDITOR", file.editor, parse_text)?
+            .or_else(|| from_env("VISUAL"))
+            .or_else(|| from_env("EDITOR"))
+            .unwrap_or_else(|| default(DEFAULT_EDITOR.to_owned())),
+        notify_command: optional(resolve(
+            "JURNALO_NOTIFY_COMMAND",
+            file.notify_command,
+            parse_text,
+        )?),
+        notify_template: resolve("JURNALO_NOTIFY_TEMPLATE", file.notify_template, parse_text)?
+            .unwrap_or_else(|| default(DEFAULT_NOTIFY_TEMPLATE.to_owned())),
+        backups_to_keep: resolve(
+            "JURNALO_BACKUPS_TO_KEEP",
+            file.backups_to_keep,
+            parse_number,
+        )?
+        .unwrap_or_else(|| default(DEFAULT_BACKUPS_TO_KEEP)),
+        unlock_minutes: resolve("JURNALO_UNLOCK_MINUTES", file.unlock_minutes, parse_number)?
+            .unwrap_or_else(|| default(DEFAULT_UNLOCK_MINUTES)),
+    })
+}
+
+fn load_config_file() -> Result<ConfigFile, Error> {
+    let path = config_path();
+    if !path.exists() {
+        return Ok(ConfigFile::default());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    toml::from_str(&content).map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))
+}
+
+/// Returns the value of the environment variable if it is set, or else the value from the config file.
+fn resolve<T>(
+    env_var: &str,
+    file_value: Option<T>,
+    parse: impl Fn(&str) -> Result<T, Error>,
+) -> Result<Option<Setting<T>>, Error> {
+    if let Some(Setting { value, source }) = from_env(env_var) {
+        return Ok(Some(Setting {
+            value: parse(&value)?,
+            source,
+        }));
+    }
+    Ok(file_value.map(|value| Setting {
+        value,
+        source: "config file".to_owned(),
+    }))
+}
+
+fn from_env(env_var: &str) -> Option<Setting<String>> {
+    std::env::var(env_var)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(|value| Setting {
+            value,
+            source: env_var.to_owned(),
+        })
+}
+
+fn default<T>(value: T) -> Setting<T> {
+    Setting {
+        value,
+        source: "default".to_owned(),
+    }
+}
+
+/// Turns a setting without a default into a setting whose value may be missing.
+fn optional<T>(setting: Option<Setting<T>>) -> Setting<Option<T>> {
+    match setting {
+        Some(Setting { value, source }) => Setting {
+            value: Some(value),
+            source,
+        },
+        None => default(None),
+    }
+}
+
+fn parse_text(value: &str) -> Result<String, Error> {
+    Ok(value.to_owned())
+}
+
+fn parse_number(value: &str) -> Result<u32, Error> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::InvalidConfig(format!("`{}` isn't a valid number.", value)))
+}
+
+fn parse_color(value: &str) -> Result<ColorChoice, Error> {
+    match value.trim().to_lowercase().as_str() {
+        "auto" => Ok(ColorChoice::Auto),
+        "always" => Ok(ColorChoice::Always),
+        "never" => Ok(ColorChoice::Never),
+        _ => Err(Error::InvalidConfig(format!(
+            "The color must be `auto`, `always` or `never`, got `{}`.",
+            value
+        ))),
+    }
+}
+
+/// Returns whether the output should be colored.
+pub fn use_color() -> bool {
+    match get().color.value {
+        ColorChoice::Auto => std::io::stdout().is_terminal(),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// Returns the current date, which only changes at the hour set by `day_starts_at`.
+pub fn today() -> NaiveDate {
+    local_date(chrono::Utc::now().naive_utc())
+}
+
+/// Returns the day a timestamp from the database (in UTC) belongs to: its local date, which only changes at the hour
+/// set by `day_starts_at`, like `today`. The commands that count the entries by day all use it, so they agree.
+pub fn local_date(timestamp: NaiveDateTime) -> NaiveDate {
+    (Local.from_utc_datetime(&timestamp) - Duration::hours(get().day_starts_at.value.into()))
+        .date_naive()
+}
+
+/// Returns when the day starts, in UTC like the timestamps in the database: the first timestamp whose `local_date` is
+/// the date.
+pub fn start_of_day(date: NaiveDate) -> NaiveDateTime {
+    let start = date.and_time(NaiveTime::MIN) + Duration::hours(get().day_starts_at.value.into());
+    Local
+        .from_local_datetime(&start)
+        .earliest()
+        .map(|local| local.naive_utc())
+        .unwrap_or(start)
+}
+
+#[test]
+fn test_config_file_rejects_unknown_settings() {
+    let file: ConfigFile = toml::from_str("print_days = 30\ncolor = \"never\"").unwrap();
+    assert_eq!(file.print_days, Some(30));
+    assert_eq!(
+        parse_color(&file.color.unwrap()).unwrap(),
+        ColorChoice::Never
+    );
+    assert!(toml::from_str::<ConfigFile>("print_dayz = 30").is_err());
+}
@@ -0,0 +1,274 @@
+/// This file contains the encryption of the journal. The details of the entries are encrypted with XChaCha20-Poly1305,
+/// with a key derived from a passphrase with Argon2id, and stored as `enc:v1:<base64 of the nonce and ciphertext>`.
+/// The salt and the parameters of the key derivation are stored in the journal, in the `encryption` table.
+///
+/// Once unlocked, the key is cached in the runtime directory (e.g. `/run/user/1000/jurnalo`, which is only readable by
+/// the user and cleared on logout) for `unlock_minutes`, so the passphrase isn't asked on every command.
+/// `JURNALO_PASSPHRASE` can be set instead, for scripts.
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::errors::Error;
+
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+pub const PASSPHRASE_ENV_VAR: &str = "JURNALO_PASSPHRASE";
+/// The new passphrase of `encrypt` and `rekey`, for scripts.
+pub const NEW_PASSPHRASE_ENV_VAR: &str = "JURNALO_NEW_PASSPHRASE";
+const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+/// Encrypted into the check value of the journal, to tell whether a passphrase is the right one.
+const CHECK_PLAINTEXT: &str = "jurnalo";
+const KEY_CACHE_DIRECTORY: &str = "jurnalo";
+const PASSPHRASE_ATTEMPTS: usize = 3;
+
+/// The key the details are encrypted with.
+#[derive(Clone)]
+pub struct Key([u8; KEY_LENGTH]);
+
+/// How the key is derived from the passphrase. They are stored in the journal, so their costs can be raised later
+/// without breaking the journals encrypted before.
+#[derive(Debug, Clone)]
+pub struct KeyParameters {
+    pub salt: Vec<u8>,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KeyParameters {
+    /// Returns new parameters, with a random salt and the default costs of Argon2id.
+    pub fn generate() -> Self {
+        let mut salt = vec![0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            salt,
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+
+    /// Derives the key from the passphrase. This is slow on purpose, to make guessing the passphrase slow too.
+    pub fn derive_key(&self, passphrase: &str) -> Result<Key, Error> {
+        let invalid = |e: argon2::Error| {
+            Error::Encryption(format!("Invalid key derivation parameters: {}", e))
+        };
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(invalid)?;
+        let mut key = [0; KEY_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut key)
+            .map_err(invalid)?;
+        Ok(Key(key))
+    }
+}
+
+impl Key {
+    /// Encrypts the text, with a new random nonce every time.
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let cipher = XChaCha20Poly1305::new(&self.0.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("Encrypting in memory doesn't fail.");
+        let mut bytes = nonce.to_vec();
+        bytes.extend(ciphertext);
+        format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(bytes))
+    }
+
+    /// Decrypts the text if it is encrypted, or else returns it as it is, e.g. for the entries written before the
+    /// journal was encrypted.
+    pub fn decrypt(&self, text: &str) -> Result<String, Error> {
+        let Some(encoded) = text.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(text.to_owned());
+        };
+        let damaged = || Error::Encryption("Some encrypted text is damaged.".to_owned());
+        let bytes = STANDARD.decode(encoded).map_err(|_| damaged())?;
+        if bytes.len() < NONCE_LENGTH {
+            return Err(damaged());
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+        let plaintext = XChaCha20Poly1305::new(&self.0.into())
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| damaged())?;
+        String::from_utf8(plaintext).map_err(|_| damaged())
+    }
+
+    /// Returns the check value to store with the parameters.
+    pub fn check_value(&self) -> String {
+        self.encrypt(CHECK_PLAINTEXT)
+    }
+
+    /// Whether this is the key the check value was made with.
+    pub fn matches(&self, check_value: &str) -> bool {
+        is_encrypted(check_value)
+            && self
+                .decrypt(check_value)
+                .is_ok_and(|text| text == CHECK_PLAINTEXT)
+    }
+}
+
+pub fn is_encrypted(text: &str) -> bool {
+    text.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Derives the key from `JURNALO_PASSPHRASE`, or else from the passphrase typed by the user (a few attempts are
+/// allowed), and checks it against the check value of the journal.
+pub fn unlock(parameters: &KeyParameters, check_value: &str) -> Result<Key, Error> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+        let key = parameters.derive_key(&passphrase)?;
+        return match key.matches(check_value) {
+            true => Ok(key),
+            false => Err(Error::WrongPassphrase),
+        };
+    }
+    for attempt in 1..=PASSPHRASE_ATTEMPTS {
+        let passphrase = prompt_passphrase("Passphrase of the journal: ")?;
+        let key = parameters.derive_key(&passphrase)?;
+        if key.matches(check_value) {
+            return Ok(key);
+        }
+        if attempt < PASSPHRASE_ATTEMPTS {
+            eprintln!("The passphrase is wrong. Try again.");
+        }
+    }
+    Err(Error::WrongPassphrase)
+}
+
+/// Returns `JURNALO_NEW_PASSPHRASE`, or else asks for a new passphrase, twice to catch typos.
+pub fn read_new_passphrase() -> Result<String, Error> {
+    let passphrase = match std::env::var(NEW_PASSPHRASE_ENV_VAR) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = prompt_passphrase("New passphrase: ")?;
+            if prompt_passphrase("New passphrase again: ")? != passphrase {
+                return Err(Error::InvalidInput(
+                    "The passphrases don't match.".to_owned(),
+                ));
+            }
+            passphrase
+        }
+    };
+    if passphrase.is_empty() {
+        return Err(Error::InvalidInput(
+            "The passphrase can't be empty.".to_owned(),
+        ));
+    }
+    Ok(passphrase)
+}
+
+/// Asks for a passphrase on the terminal, without showing it.
+fn prompt_passphrase(prompt: &str) -> Result<String, Error> {
+    rpassword::prompt_password(prompt).map_err(|e| {
+        Error::InvalidInput(format!(
+            "Couldn't ask for the passphrase ({}). Set {} to unlock the journal without a terminal.",
+            e, PASSPHRASE_ENV_VAR
+        ))
+    })
+}
+
+/// Returns the key cached for the journal by an earlier command, if it hasn't expired.
+pub fn cached_key(journal_id: &str) -> Option<Key> {
+    let content = std::fs::read_to_string(key_cache_dir()?.join(journal_id)).ok()?;
+    let (expires_at, key) = content.trim().split_once(' ')?;
+    if expires_at.parse::<i64>().ok()? < chrono::Utc::now().timestamp() {
+        forget_key(journal_id);
+        return None;
+    }
+    let key: [u8; KEY_LENGTH] = STANDARD.decode(key).ok()?.try_into().ok()?;
+    Some(Key(key))
+}
+
+/// Caches the key for `minutes`, so the next commands don't ask for the passphrase. Nothing is cached if `minutes` is 0
+/// or if there is no runtime directory (which is only readable by the user).
+pub fn cache_key(journal_id: &str, key: &Key, minutes: u32) -> Result<(), Error> {
+    let Some(directory) = key_cache_dir().filter(|_| minutes > 0) else {
+        return Ok(());
+    };
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&directory)?;
+    let expires_at = chrono::Utc::now().timestamp() + i64::from(minutes) * 60;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(directory.join(journal_id))?;
+    std::io::Write::write_all(
+        &mut file,
+        format!("{} {}", expires_at, STANDARD.encode(key.0)).as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// Removes the cached key of the journal, if there is one.
+pub fn forget_key(journal_id: &str) {
+    if let Some(directory) = key_cache_dir() {
+        std::fs::remove_file(directory.join(journal_id)).ok();
+    }
+}
+
+/// Removes all the cached keys. Returns how many there were.
+pub fn forget_all_keys() -> Result<usize, Error> {
+    let Some(directory) = key_cache_dir().filter(|directory| directory.exists()) else {
+        return Ok(0);
+    };
+    let mut count = 0;
+    for entry in std::fs::read_dir(directory)? {
+        std::fs::remove_file(entry?.path())?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Identifies the cached key of a journal, from its path and its salt, so a new passphrase never uses an old key.
+pub fn journal_id(database_path: &str, salt: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(database_path.as_bytes());
+    hasher.update(salt);
+    format!("{:x}", hasher.finalize())[..32].to_owned()
+}
+
+/// The cached keys are only kept in the runtime directory, which isn't shared with other users or kept after logout.
+fn key_cache_dir() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|directory| directory.join(KEY_CACHE_DIRECTORY))
+}
+
+#[test]
+fn test_encrypted_details_round_trip() {
+    let parameters = KeyParameters {
+        salt: vec![7; SALT_LENGTH],
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+    let key = parameters.derive_key("correct horse").unwrap();
+    let encrypted = key.encrypt("Went hiking with @sam");
+    assert!(is_encrypted(&encrypted));
+    assert!(!encrypted.contains("hiking"));
+    assert_ne!(encrypted, key.encrypt("Went hiking with @sam"));
+    assert_eq!(key.decrypt(&encrypted).unwrap(), "Went hiking with @sam");
+    assert_eq!(key.decrypt("written before").unwrap(), "written before");
+
+    let check_value = key.check_value();
+    assert!(key.matches(&check_value));
+    let wrong_key = parameters.derive_key("battery staple").unwrap();
+    assert!(!wrong_key.matches(&check_value));
+    assert!(matches!(
+        wrong_key.decrypt(&encrypted),
+        Err(Error::Encryption(_))
+    ));
+}
@@ -1,106 +1,200 @@
 pub mod toml_utils;
 use crate::backend::establish_connection;
+use crate::backend::migrations;
 use crate::backend::schema;
+use crate::errors::Error;
 use crate::models::insertable as m_ins;
+use crate::modes::goals::PeriodUnit;
+use crate::modes::status::Schedule;
 use diesel::prelude::*;
-use dotenvy::dotenv;
-use std::env;
-use toml_utils::{load_toml, toml_schema};
+use std::path::{Path, PathBuf};
+use toml_utils::{load_toml, parse_toml, toml_schema};
 
-use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
-// TODO: move all the initial setup logic from backend/mod.rs to here.
+/// The categories and quizzes added by `init` when no TOML is given: mood, weather and habits.
+const STARTER_TEMPLATE: &str = include_str!("starter_template.toml");
 
-const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+/// What the new database is populated with.
+pub enum Seed<'a> {
+    /// The bundled starter template.
+    Starter,
+    /// The categories and quizzes of the TOML at this path.
+    Toml(&'a str),
+    /// Nothing: the categories and quizzes are added later.
+    Empty,
+}
+
+/// Creates the database at `database_path` (with its directory), runs the migrations and populates it with the seed,
+/// printing each step. Refuses to replace a database that already has categories or entries, unless `force` is set, in
+/// which case the existing file is moved aside first.
+pub fn setup(database_path: &str, seed: Seed, force: bool) -> Result<(), Error> {
+    // The TOML is loaded first, so a mistake in it doesn't leave an empty database behind.
+    let seed = match seed {
+        Seed::Starter => Some((
+            parse_toml(STARTER_TEMPLATE)?,
+            "the starter template".to_owned(),
+        )),
+        Seed::Toml(toml_path) => Some((load_toml(toml_path)?, toml_path.to_owned())),
+        Seed::Empty => None,
+    };
+    if let Some((ref toml_data, ref source)) = seed {
+        check_setup(toml_data).map_err(|e| Error::InvalidInput(format!("{}: {}", source, e)))?;
+    }
+
+    let path = Path::new(database_path);
+    let mut existed = path.exists();
+    if existed {
+        let is_empty = is_database_empty(&mut establish_connection(Some(database_path))?)?;
+        if !is_empty {
+            if !force {
+                return Err(Error::DatabaseAlreadyExists(database_path.to_owned()));
+            }
+            let moved_to = move_aside(path)?;
+            println!("Moved the existing database to {}.", moved_to.display());
+            existed = false;
+        }
+    } else if let Some(directory) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(directory)?;
+    }
 
-pub fn setup(initial_settings_path: &str, database_path: &str) {
-    // Setup
-    let mut connection = establish_connection(Some(database_path));
+    let mut connection = establish_connection(Some(database_path))?;
+    let migrations_count = migrations::run_pending_migrations(&mut connection)?;
+    if existed {
+        println!(
+            "Using the empty database at {} ({} migration(s) applied).",
+            database_path, migrations_count
+        );
+    } else {
+        println!(
+            "Created the database at {} ({} migration(s) applied).",
+            database_path, migrations_count
+        );
+    }
 
-    // If database doesn't exist, create it based on the schema and populate the database with the starting values from the TOML
-    create_database_if_it_doesnt_exist(&mut connection);
-    populate_db_from_toml(&mut connection, initial_settings_path);
+    let Some((toml_data, source)) = seed else {
+        return Ok(());
+    };
+    let (categories_count, choices_count, quizzes_count) =
+        populate_db_from_toml(&mut connection, &toml_data)?;
+    println!(
+        "Added {} category(ies), {} choice(s) and {} quiz(zes) from {}.",
+        categories_count, choices_count, quizzes_count, source
+    );
+    for quiz in toml_data.quizzes.iter() {
+        println!("Run `jurnalo {}` to answer the quiz.", quiz.command);
+    }
+    Ok(())
 }
 
-fn create_database_if_it_doesnt_exist(connection: &mut SqliteConnection) {
-    if !is_database_empty(connection) {
-        println!("Database is not empty! Proceding...");
-        return;
+/// Checks the goals, which the database would only reject when the setup is inserted with an error that doesn't tell
+/// where the mistake is, and the schedules, which would be ignored. The errors are returned as text, to be prefixed with
+/// the source of the setup.
+fn check_setup(toml_data: &toml_schema::TomlData) -> Result<(), String> {
+    for category in toml_data.categories.iter() {
+        for choice in category.choices.iter().flat_map(|choices| choices.iter()) {
+            let name = format!("the choice `{}` of `{}`", choice.label, category.label);
+            match (choice.goal_times, choice.goal_period.as_deref()) {
+                (None, None) => {}
+                (Some(times), Some(period)) => {
+                    if times < 1 || choice.goal_period_length.is_some_and(|length| length < 1) {
+                        return Err(format!(
+                            "the goal of {} must be at least once per at least 1 period.",
+                            name
+                        ));
+                    }
+                    if PeriodUnit::from_label(period).is_none() {
+                        return Err(format!(
+                            "the goal period of {} must be `day`, `week` or `month`, not `{}`.",
+                            name, period
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "the goal of {} needs both `goal_times` and `goal_period`.",
+                        name
+                    ))
+                }
+            }
+        }
     }
-    println!("Trying to create database from scratch.");
-    create_basic_database(connection).unwrap();
-    println!("Created database.");
-
-    println!("Running the basic config.");
-    dotenv().ok();
-    let toml_path = env::var("TEST_TOML").expect("`TEST_TOML` not set in .env");
-    populate_db_from_toml(connection, &toml_path);
+    for quiz in toml_data.quizzes.iter() {
+        match quiz.schedule.as_deref() {
+            Some(schedule)
+                if !schedule.trim().eq_ignore_ascii_case("none")
+                    && Schedule::parse(schedule).is_none() =>
+            {
+                return Err(format!(
+                    "the schedule of the quiz `{}` must be `daily`, `weekdays`, `weekly <weekday>`, `monthly <day>` \
+                    or `none`, not `{}`.",
+                    quiz.command, schedule
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
-fn is_database_empty(connection: &mut SqliteConnection) -> bool {
-    // TODO: get it to work with the actual database, not just check if the file exists.
-    use crate::backend::schema::categories::dsl::*;
+/// Whether the database has neither categories nor entries, e.g. because it was just created.
+fn is_database_empty(connection: &mut SqliteConnection) -> Result<bool, Error> {
     use diesel::result::DatabaseErrorKind as dErrorKind;
     use diesel::result::Error as dError;
+    use schema::{categories, entries};
 
-    match categories.count().first::<i64>(connection) {
-        Ok(n) => n == 0, // true if no rows in that table
-        Err(dError::DatabaseError(dErrorKind::Unknown, d)) => {
-            // This error is typically "table not found."
-            // TODO: Keep an watch on this, make sure it's actually always "table not found"
-            dbg!("{}", d);
-            true
+    let count = categories::table
+        .count()
+        .get_result::<i64>(connection)
+        .and_then(|n| Ok(n + entries::table.count().get_result::<i64>(connection)?));
+    match count {
+        Ok(n) => Ok(n == 0),
+        // The tables don't exist yet.
+        Err(dError::DatabaseError(dErrorKind::Unknown, info))
+            if info.message().starts_with("no such table") =>
+        {
+            Ok(true)
         }
-        Err(e) => panic!("{}", e),
+        Err(e) => Err(e.into()),
     }
 }
 
-fn create_basic_database(
-    connection: &mut SqliteConnection,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
-    connection
-        .run_pending_migrations(MIGRATIONS)
-        .expect("Failed to run migrations.");
-    Ok(())
+/// Renames the database to `<name>.<timestamp>.old`, so it isn't lost when it is replaced.
+fn move_aside(path: &Path) -> Result<PathBuf, Error> {
+    let destination = super::timestamped_sibling(path, "old");
+    std::fs::rename(path, &destination)?;
+    Ok(destination)
 }
 
 // APIs
 
-fn populate_db_from_toml(connection: &mut SqliteConnection, toml_path: &str) {
+/// Adds the categories, choices and quizzes of the TOML, and returns how many of each were added.
+fn populate_db_from_toml(
+    connection: &mut SqliteConnection,
+    toml_data: &toml_schema::TomlData,
+) -> Result<(usize, usize, usize), Error> {
     use schema::categories::dsl::*;
     use schema::choices::dsl::*;
     use schema::quizzes::dsl::*;
     use schema::quizzes_to_categories::dsl::*;
 
-    let toml_data = match load_toml(toml_path) {
-        Ok(td) => td,
-        Err(_) => {
-            eprintln!("Couldn't load the data to the TOML.");
-            dbg!(toml_path);
-            panic!()
-        }
-    };
-    let objects_to_insert = toml_to_db_query(&toml_data);
-
-    diesel::insert_into(categories)
-        .values(objects_to_insert.categories)
-        .execute(connection)
-        .expect("Failed to write to database.");
-
-    diesel::insert_into(choices)
-        .values(objects_to_insert.alternatives)
-        .execute(connection)
-        .expect("Failed to write to database.");
-
-    diesel::insert_into(quizzes)
-        .values(objects_to_insert.quizzes)
-        .execute(connection)
-        .expect("Failed to write quizzes to database.");
-
-    diesel::insert_into(quizzes_to_categories)
-        .values(objects_to_insert.quiz_to_cat)
-        .execute(connection)
-        .expect("Failed to write quiz_to_cat to database.");
+    let objects_to_insert = toml_to_db_query(toml_data);
+
     // TODO: Handle foreign key errors, as these are user errors. ErrorType: `DatabaseError(ForeignKeyViolation, _)`
+    let counts = connection.transaction(|connection| {
+        let categories_count = diesel::insert_into(categories)
+            .values(objects_to_insert.categories)
+            .execute(connection)?;
+        let choices_count = diesel::insert_into(choices)
+            .values(objects_to_insert.alternatives)
+            .execute(connection)?;
+        let quizzes_count = diesel::insert_into(quizzes)
+            .values(objects_to_insert.quizzes)
+            .execute(connection)?;
+        diesel::insert_into(quizzes_to_categories)
+            .values(objects_to_insert.quiz_to_cat)
+            .execute(connection)?;
+        Ok::<_, diesel::result::Error>((categories_count, choices_count, quizzes_count))
+    })?;
+    Ok(counts)
 }
 
 struct ObjectsToInsertFromSetup {
@@ -141,6 +235,13 @@ fn toml_to_db_query(toml_data: &toml_schema::TomlData) -> ObjectsToInsertFromSet
                         category_label: question.label.clone(),
                         show_in_streaks: choice.show_in_streaks,
                         reminder_timer_in_days: choice.reminder_timer_in_days,
+                        goal_times: choice.goal_times,
+                        goal_period: choice
+                            .goal_period
+                            .as_deref()
+                            .and_then(PeriodUnit::from_label)
+                            .map(|unit| unit.label().to_owned()),
+                        goal_period_length: choice.goal_period_length,
                     })
                 }
             }
@@ -162,6 +263,11 @@ fn toml_to_db_query(toml_data: &toml_schema::TomlData) -> ObjectsToInsertFromSet
         .map(|q| m_ins::NewQuiz {
             label: q.command.clone(),
             command: Some(q.command.clone()),
+            schedule: q
+                .schedule
+                .as_deref()
+                .and_then(Schedule::parse)
+                .map(|schedule| schedule.label()),
         })
         .collect();
 
@@ -185,3 +291,57 @@ fn toml_to_db_query(toml_data: &toml_schema::TomlData) -> ObjectsToInsertFromSet
         quiz_to_cat: result_quiz_to_cat,
     }
 }
+
+#[test]
+fn test_starter_template() {
+    let toml_data = parse_toml(STARTER_TEMPLATE).expect("Couldn't parse the starter template.");
+    let objects = toml_to_db_query(&toml_data);
+    let labels: Vec<&str> = objects
+        .categories
+        .iter()
+        .map(|c| c.label.as_str())
+        .collect();
+    assert_eq!(labels, ["mood", "weather", "habits"]);
+    assert!(objects
+        .quiz_to_cat
+        .iter()
+        .all(|q| labels.contains(&q.category_label.as_str())));
+}
+
+#[test]
+fn test_check_setup_names_the_choice_with_an_invalid_goal() {
+    let setup = |goal: &str| {
+        parse_toml(&format!(
+            "[[categories]]\nlabel = \"habits\"\nprompt = \"Which habits?\"\n\
+            [[categories.choices]]\nlabel = \"gym\"\nshortcut = \"1\"\n{}\n\
+            [[quizzes]]\ncommand = \"daily\"\ncategories = [\"habits\"]\n",
+            goal
+        ))
+        .unwrap()
+    };
+    assert!(check_setup(&setup("goal_times = 3\ngoal_period = \"weeks\"")).is_ok());
+    assert!(
+        check_setup(&setup("goal_times = 3\ngoal_period = \"fortnight\""))
+            .unwrap_err()
+            .contains("the choice `gym` of `habits`")
+    );
+    assert!(check_setup(&setup("goal_times = 0\ngoal_period = \"week\"")).is_err());
+    assert!(check_setup(&setup("goal_times = 3")).is_err());
+}
+
+#[test]
+fn test_check_setup_names_the_quiz_with_an_invalid_schedule() {
+    let setup = |schedule: &str| {
+        parse_toml(&format!(
+            "[[categories]]\nlabel = \"mood\"\nprompt = \"How are you?\"\n\
+            [[quizzes]]\ncommand = \"daily\"\ncategories = [\"mood\"]\nschedule = \"{}\"\n",
+            schedule
+        ))
+        .unwrap()
+    };
+    assert!(check_setup(&setup("weekly Monday")).is_ok());
+    assert!(check_setup(&setup("none")).is_ok());
+    assert!(check_setup(&setup("every other day"))
+        .unwrap_err()
+        .contains("the quiz `daily`"));
+}
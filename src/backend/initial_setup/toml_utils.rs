@@ -1,30 +1,24 @@
 /// This module is responsible for loading the TOML file and parsing it into a struct.
-extern crate dotenvy;
 extern crate toml;
 
 use std::path::Path;
 
+use crate::errors::Error;
 use toml_schema::TomlData;
 
 // TODO: auto-generate shortcuts if none are provided.
 
-pub fn load_toml(path_string: &str) -> Result<TomlData, toml::de::Error> {
-    let mut test_toml_path_string = path_string.to_owned();
-    dotenvy::dotenv().ok();
-    let maybe_test_toml = std::env::var("TEST_TOML");
-
-    if path_string.is_empty() {
-        test_toml_path_string =
-            maybe_test_toml.expect("Could not find `TEST_TOML` in the environment.");
-    }
-    let toml_path = Path::new(test_toml_path_string.as_str());
-
-    let toml_string = std::fs::read_to_string(toml_path).unwrap_or(format!(
-        "Could not read the toml file at path: {:#?}.",
-        toml_path
-    ));
+pub fn load_toml(path_string: &str) -> Result<TomlData, Error> {
+    let toml_path = Path::new(path_string);
+    let toml_string = std::fs::read_to_string(toml_path)?;
     // TODO: Validate TOML (make sure the foreign keys are valid (I think it's only on the quiz section).)
     toml::from_str::<TomlData>(&toml_string)
+        .map_err(|e| Error::InvalidConfig(format!("{}: {}", toml_path.display(), e)))
+}
+
+/// Parses the TOML with the categories and the quizzes, e.g. the content of a setup file.
+pub fn parse_toml(toml_string: &str) -> Result<TomlData, Error> {
+    toml::from_str::<TomlData>(toml_string).map_err(|e| Error::InvalidConfig(e.to_string()))
 }
 
 pub mod toml_schema {
@@ -53,20 +47,25 @@ pub mod toml_schema {
         pub label: String,
         pub show_in_streaks: Option<i32>,
         pub reminder_timer_in_days: Option<i32>,
+        // Goal: `goal_times` per `goal_period_length` `goal_period`s ("day", "week" or "month").
+        pub goal_times: Option<i32>,
+        pub goal_period: Option<String>,
+        pub goal_period_length: Option<i32>,
     }
 
     #[derive(Deserialize)]
     pub struct Quiz {
         pub command: String,
         pub categories: Box<[String]>,
+        pub schedule: Option<String>,
     }
 }
 
 #[test]
 fn test_load_toml() {
-    let toml_data = load_toml("").expect("Couldn't parse the test TOML.");
+    let toml_data = load_toml("mockdb/toml_test.toml").expect("Couldn't parse the test TOML.");
 
-    assert!(toml_data.categories.len() > 0);
+    assert!(!toml_data.categories.is_empty());
     assert_eq!(
         toml_data.categories[2].prompt,
         "What habits did you accomplish today?"
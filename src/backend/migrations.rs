@@ -0,0 +1,98 @@
+/// This file contains the migrations of the database, embedded in the binary, and the check run on startup that brings
+/// the database up to date with them.
+use diesel::migration::MigrationSource;
+use diesel::sqlite::SqliteConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use std::path::Path;
+
+use crate::errors::Error;
+
+pub(crate) const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Runs the pending migrations, and returns how many were applied.
+pub(crate) fn run_pending_migrations(connection: &mut SqliteConnection) -> Result<usize, Error> {
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .map(|versions| versions.len())
+        .map_err(|e| Error::Migration(e.to_string()))
+}
+
+/// Brings the database up to date: if some migrations are pending, makes an automatic backup of the database and
+/// applies them. Fails if the database has migrations this binary doesn't know, i.e. it was created by a newer version
+/// of jurnalo, since the tables wouldn't match what the queries expect.
+pub(crate) fn migrate_if_needed(
+    connection: &mut SqliteConnection,
+    database_path: &str,
+    backups_to_keep: u32,
+) -> Result<(), Error> {
+    let applied: Vec<String> = connection
+        .applied_migrations()
+        .map_err(|e| Error::Migration(e.to_string()))?
+        .iter()
+        .map(|version| version.to_string())
+        .collect();
+    check_applied_versions(&applied)?;
+
+    let pending_count = known_versions()?.len() - applied.len();
+    if pending_count == 0 {
+        return Ok(());
+    }
+
+    // A database without any migration has nothing worth backing up.
+    if !applied.is_empty() {
+        match super::backups::automatic_backup(Path::new(database_path), backups_to_keep, true)? {
+            Some(backup_path) => eprintln!(
+                "Updating the database to the latest version ({} migration(s)). A backup was saved to {}.",
+                pending_count,
+                backup_path.display()
+            ),
+            None => eprintln!(
+                "Updating the database to the latest version ({} migration(s)).",
+                pending_count
+            ),
+        }
+    }
+    run_pending_migrations(connection)?;
+    Ok(())
+}
+
+/// Fails if some of the applied migrations aren't known to this version of jurnalo.
+pub(crate) fn check_applied_versions(applied: &[String]) -> Result<(), Error> {
+    let known = known_versions()?;
+    match applied.iter().find(|version| !known.contains(version)) {
+        Some(unknown) => Err(Error::DatabaseTooNew(unknown.clone())),
+        None => Ok(()),
+    }
+}
+
+/// The versions of the migrations embedded in the binary.
+fn known_versions() -> Result<Vec<String>, Error> {
+    Ok(
+        MigrationSource::<diesel::sqlite::Sqlite>::migrations(&MIGRATIONS)
+            .map_err(|e| Error::Migration(e.to_string()))?
+            .iter()
+            .map(|migration| migration.name().version().to_string())
+            .collect(),
+    )
+}
+
+#[test]
+fn test_migrate_if_needed_refuses_newer_databases() {
+    use diesel::Connection;
+
+    let mut connection = SqliteConnection::establish(":memory:").unwrap();
+    migrate_if_needed(&mut connection, ":memory:", 0).unwrap();
+    assert!(!connection.has_pending_migration(MIGRATIONS).unwrap());
+
+    diesel::RunQueryDsl::execute(
+        diesel::sql_query(
+            "INSERT INTO __diesel_schema_migrations (version) VALUES ('99991231000000')",
+        ),
+        &mut connection,
+    )
+    .unwrap();
+    assert!(matches!(
+        migrate_if_needed(&mut connection, ":memory:", 0),
+        Err(Error::DatabaseTooNew(version)) if version == "99991231000000"
+    ));
+}
@@ -1,55 +1,94 @@
-// Use crates
-extern crate dotenvy;
-
 use diesel::prelude::*;
 use diesel::sqlite::SqliteConnection;
-use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use crate::errors::Error;
 
 pub mod api;
+pub mod attachments;
+pub mod backups;
+pub mod config;
+pub mod encryption;
 pub mod schema;
 // mod toml_utils;
 mod initial_setup;
+mod migrations;
+pub mod profiles;
 
 // pub use initial_setup::setup;
 
 // const STANDARD_TOML_PATH: &str = "mockdb/toml_test.toml";
 const PRAGMAS: [&str; 1] = ["PRAGMA foreign_keys = ON"];
 
+/// Makes sure the database from the settings is only checked for pending migrations once per run.
+static MIGRATION_CHECK: Once = Once::new();
+
 /// Establishes a connection to the database, and returns the connection.
-/// If the database_path is None, it will try to get the path from the settings or from the .env file.
-pub fn establish_connection(database_path: Option<&str>) -> SqliteConnection {
-    let database_path: String = match database_path {
-        Some(path) => path.to_string(),
-        None => get_database_path(),
+/// If the database_path is None, it will use the one from the settings (see `config`), which must already exist.
+/// The first time, that database is brought up to date with the migrations of this version of jurnalo.
+pub fn establish_connection(database_path: Option<&str>) -> Result<SqliteConnection, Error> {
+    let (database_path, is_from_settings) = match database_path {
+        Some(path) => (path.to_string(), false),
+        None => (get_database_path(), true),
     };
 
-    let mut connection = SqliteConnection::establish(&database_path)
-        .unwrap_or_else(|_| panic!("Error connecting to {}", database_path));
+    // Otherwise SQLite would create an empty database, and the queries would fail with missing tables.
+    if is_from_settings && !Path::new(&database_path).exists() {
+        return Err(Error::DatabaseNotFound(database_path));
+    }
+
+    let mut connection = SqliteConnection::establish(&database_path)?;
 
     for pragma in PRAGMAS {
-        diesel::sql_query(pragma)
-            .execute(&mut connection)
-            .unwrap_or_else(|_| panic!("Could not execute pragma `{}`", pragma));
+        diesel::sql_query(pragma).execute(&mut connection)?;
     }
-    connection
-}
 
-fn get_database_path() -> String {
-    // TODO: Search in config file first.
+    if is_from_settings {
+        let mut migration_result = Ok(());
+        MIGRATION_CHECK.call_once(|| {
+            migration_result = migrations::migrate_if_needed(
+                &mut connection,
+                &database_path,
+                config::get().backups_to_keep.value,
+            );
+        });
+        migration_result?;
+    }
+    Ok(connection)
+}
 
-    // Search in .env
-    dotenvy::dotenv().ok();
-    env::var("DATABASE_URL").expect("`DATABASE_URL` not set in .env")
+/// Returns `<file name>.<timestamp>.<extension>`, next to the file, e.g. for backups.
+pub(crate) fn timestamped_sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(format!(
+        ".{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ));
+    path.with_file_name(file_name)
 }
 
-pub fn setup(initial_settings_path: &Option<String>, database_path: &Option<String>) {
-    // TODO: check if database already exists
+/// Returns the database of the selected profile, or else the one from `DATABASE_URL`, the config file, or the default
+/// one in the data directory.
+pub(crate) fn get_database_path() -> String {
+    config::get().database.value.clone()
+}
 
-    // Now it doesn't exist, so we need to create it.
-    if let (Some(settings_path), Some(database_path)) = (initial_settings_path, database_path) {
-        initial_setup::setup(settings_path, database_path);
-        return;
-    }
+/// Creates and migrates the database at `database_path` (or the one from the settings), and populates it with the
+/// categories and quizzes of the TOML at `initial_settings_path`, or of the starter template unless `empty` is set.
+pub fn setup(
+    initial_settings_path: &Option<String>,
+    database_path: &Option<String>,
+    empty: bool,
+    force: bool,
+) -> Result<(), Error> {
+    let database_path = database_path.clone().unwrap_or_else(get_database_path);
+    let seed = match initial_settings_path {
+        Some(path) => initial_setup::Seed::Toml(path),
+        None if empty => initial_setup::Seed::Empty,
+        None => initial_setup::Seed::Starter,
+    };
 
-    todo!("We don't support creating the database without a settings file yet. Please pass both the settings file and the database path.");
+    initial_setup::setup(&database_path, seed, force)
 }
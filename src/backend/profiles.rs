@@ -0,0 +1,98 @@
+/// This file contains the profiles: named journals, each with its own database and setup TOML.
+/// They are kept in `profiles.toml`, in the `jurnalo` directory of the user's config directory, e.g.:
+/// ```toml
+/// active = "personal"
+///
+/// [profiles.personal]
+/// database = "/home/me/.local/share/jurnalo/personal.sqlite"
+/// setup = "/home/me/journal/personal.toml"
+/// ```
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use super::config::config_dir;
+use crate::errors::Error;
+
+const PROFILES_FILE_NAME: &str = "profiles.toml";
+/// The environment variable that selects the profile, unless `--profile` is passed.
+pub const PROFILE_ENV_VAR: &str = "JURNALO_PROFILE";
+
+/// The profile selected with `--profile`, which takes precedence over everything else.
+static SELECTED_PROFILE: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProfilesFile {
+    /// The profile used when none is selected with `--profile` or `JURNALO_PROFILE`.
+    pub active: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub database: String,
+    /// The TOML used by `init` to set up the database.
+    pub setup: Option<String>,
+}
+
+/// Selects the profile for the rest of the run, as with `--profile`. Only the first call has an effect.
+pub fn select_profile(name: &str) {
+    SELECTED_PROFILE.set(name.to_owned()).ok();
+}
+
+pub fn profiles_path() -> PathBuf {
+    config_dir().join(PROFILES_FILE_NAME)
+}
+
+/// Loads the profiles, or an empty list if there is no profiles file yet.
+pub fn load_profiles() -> Result<ProfilesFile, Error> {
+    let path = profiles_path();
+    if !path.exists() {
+        return Ok(ProfilesFile::default());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    toml::from_str(&content).map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))
+}
+
+pub fn save_profiles(profiles: &ProfilesFile) -> Result<(), Error> {
+    let path = profiles_path();
+    std::fs::create_dir_all(path.parent().expect("The path has a directory."))?;
+    let content = toml::to_string_pretty(profiles)
+        .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Returns the name of the profile in use and what selected it: `--profile`, then `JURNALO_PROFILE`, then the active
+/// one. Like the other settings, the environment takes precedence over the config files, so the active profile isn't
+/// used when `DATABASE_URL` is set.
+pub fn selected_profile_name(profiles: &ProfilesFile) -> Option<(String, &'static str)> {
+    if let Some(name) = SELECTED_PROFILE.get() {
+        return Some((name.clone(), "--profile"));
+    }
+    dotenvy::dotenv().ok();
+    if let Some(name) = std::env::var(PROFILE_ENV_VAR)
+        .ok()
+        .filter(|name| !name.is_empty())
+    {
+        return Some((name, PROFILE_ENV_VAR));
+    }
+    if std::env::var(super::config::DATABASE_ENV_VAR).is_ok() {
+        return None;
+    }
+    profiles.active.clone().map(|name| (name, "active profile"))
+}
+
+/// Returns the profile in use with its name and what selected it, or `None` if no profile is selected.
+pub fn current_profile() -> Result<Option<(String, Profile, &'static str)>, Error> {
+    let profiles = load_profiles()?;
+    let Some((name, source)) = selected_profile_name(&profiles) else {
+        return Ok(None);
+    };
+    match profiles.profiles.get(&name) {
+        Some(profile) => Ok(Some((name, profile.clone(), source))),
+        None => Err(Error::ProfileNotFound(name)),
+    }
+}
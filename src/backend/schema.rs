@@ -1,5 +1,16 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    attachments (id) {
+        id -> Integer,
+        entry_id -> Integer,
+        hash -> Text,
+        file_name -> Text,
+        extension -> Nullable<Text>,
+        timestamp -> Timestamp,
+    }
+}
+
 diesel::table! {
     categories (id) {
         id -> Integer,
@@ -29,6 +40,20 @@ diesel::table! {
         category_label -> Text,
         show_in_streaks -> Integer,
         reminder_timer_in_days -> Nullable<Integer>,
+        goal_times -> Nullable<Integer>,
+        goal_period -> Nullable<Text>,
+        goal_period_length -> Integer,
+    }
+}
+
+diesel::table! {
+    encryption (id) {
+        id -> Integer,
+        salt -> Text,
+        memory_kib -> Integer,
+        iterations -> Integer,
+        parallelism -> Integer,
+        check_value -> Text,
     }
 }
 
@@ -39,6 +64,7 @@ diesel::table! {
         category -> Nullable<Integer>,
         value -> Nullable<Integer>,
         details -> Nullable<Text>,
+        uuid -> Nullable<Text>,
     }
 }
 
@@ -47,6 +73,55 @@ diesel::table! {
         id -> Integer,
         label -> Text,
         command -> Nullable<Text>,
+        schedule -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    entries_to_people (id) {
+        id -> Integer,
+        entry_id -> Integer,
+        person_id -> Integer,
+    }
+}
+
+diesel::table! {
+    entries_to_places (id) {
+        id -> Integer,
+        entry_id -> Integer,
+        place_id -> Integer,
+    }
+}
+
+diesel::table! {
+    entries_to_tags (id) {
+        id -> Integer,
+        entry_id -> Integer,
+        tag_id -> Integer,
+    }
+}
+
+diesel::table! {
+    people (id) {
+        id -> Integer,
+        label -> Text,
+        reminder_timer_in_days -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    places (id) {
+        id -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    quiz_runs (id) {
+        id -> Integer,
+        quiz_label -> Text,
+        for_date -> Date,
+        timestamp -> Timestamp,
     }
 }
 
@@ -59,15 +134,55 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    sent_reminders (id) {
+        id -> Integer,
+        reminder_key -> Text,
+        sent_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    synced_entries (uuid) {
+        uuid -> Text,
+        hash -> Text,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::joinable!(attachments -> entries (entry_id));
 diesel::joinable!(categories -> category_types (category_type));
 diesel::joinable!(entries -> categories (category));
 diesel::joinable!(entries -> choices (value));
+diesel::joinable!(entries_to_people -> entries (entry_id));
+diesel::joinable!(entries_to_people -> people (person_id));
+diesel::joinable!(entries_to_places -> entries (entry_id));
+diesel::joinable!(entries_to_places -> places (place_id));
+diesel::joinable!(entries_to_tags -> entries (entry_id));
+diesel::joinable!(entries_to_tags -> tags (tag_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    attachments,
     categories,
     category_types,
     choices,
+    encryption,
     entries,
+    entries_to_people,
+    entries_to_places,
+    entries_to_tags,
+    people,
+    places,
+    quiz_runs,
     quizzes,
     quizzes_to_categories,
+    sent_reminders,
+    synced_entries,
+    tags,
 );
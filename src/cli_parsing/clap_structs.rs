@@ -1,30 +1,134 @@
-use clap::{Parser, Subcommand};
+use clap::{Args as ClapArgs, Parser, Subcommand};
+
+use crate::backend::api::EntryFilter;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about=None, name = "jurnalo")]
-#[command(arg_required_else_help = true)]
 pub struct Args {
-    /// The quiz to run.
+    /// The quiz to run. Defaults to `default_quiz` in the config.
     pub quiz: Option<String>,
     /// If the quiz is quicknote, the note to add.
     pub note: Option<String>,
 
+    /// The profile to use for this command, instead of the active one. Defaults to `JURNALO_PROFILE`.
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Shows the underlying errors (e.g. from the database) when a command fails.
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
     #[command(subcommand)]
     pub subcommand: Option<SubCommand>,
 }
 
+/// The filters for the entries, shared by the commands that list or count them.
+/// Repeating a flag matches any of its values. The different flags must all match, or any of them with `--any`.
+#[derive(ClapArgs, Debug)]
+pub struct FilterArgs {
+    /// Only entries of this category. Can be repeated.
+    #[arg(long, value_name = "CATEGORY")]
+    pub category: Vec<String>,
+
+    /// Only entries with this choice. Can be repeated.
+    #[arg(long, value_name = "CHOICE")]
+    pub choice: Vec<String>,
+
+    /// Only entries of the categories in this quiz. Can be repeated.
+    #[arg(long, value_name = "QUIZ")]
+    pub quiz: Vec<String>,
+
+    /// Only entries with this tag. Can be repeated.
+    #[arg(long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Only entries with details.
+    #[arg(long)]
+    pub has_details: bool,
+
+    /// Only entries without a category, i.e. quick notes.
+    #[arg(long)]
+    pub notes_only: bool,
+
+    /// Leaves out the entries of this category, whatever the other filters. Can be repeated.
+    #[arg(long, value_name = "CATEGORY")]
+    pub exclude_category: Vec<String>,
+
+    /// Keeps the entries that match any of the filters, instead of all of them.
+    #[arg(long)]
+    pub any: bool,
+}
+
+impl FilterArgs {
+    pub fn to_entry_filter(&self) -> Result<EntryFilter, crate::errors::Error> {
+        Ok(EntryFilter {
+            categories: self.category.clone(),
+            choices: self.choice.clone(),
+            quizzes: self.quiz.clone(),
+            tags: crate::modes::tags::parse_tags(&self.tag)?,
+            has_details: self.has_details,
+            notes_only: self.notes_only,
+            exclude_categories: self.exclude_category.clone(),
+            any: self.any,
+        })
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum SubCommand {
+    /// Adds a quick note, without a category. The #hashtags in it become tags.
+    /// Without a note or a file, opens the editor to write the note.
+    Log {
+        note: Vec<String>,
+
+        /// Attaches a file to the note, e.g. a photo. Can be repeated.
+        #[arg(short, long, value_name = "FILE")]
+        attach: Vec<String>,
+    },
+    /// Lists the people mentioned with @name in the entries, how often and when they were last mentioned.
+    People {
+        #[command(subcommand)]
+        subcommand: Option<PeopleSubcommands>,
+
+        /// Lists the places mentioned with +name instead.
+        #[arg(long)]
+        places: bool,
+
+        /// Extracts the mentions from the details of every entry again, e.g. for the entries added before mentions existed.
+        #[arg(long)]
+        rescan: bool,
+    },
+    /// Lists the tags and how many entries have each.
+    Tags {
+        /// Only count the entries in this period: `week`, `month`, `year` or a number of days.
+        #[arg(short, long, value_name = "PERIOD")]
+        period: Option<String>,
+
+        /// Extracts the #hashtags from the details of every entry again, e.g. for the entries added before tags existed.
+        #[arg(long)]
+        rescan: bool,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
     /// Prints the recent entries to the terminal.
     Print {
-        /// The number of days to print.
-        #[arg(short, long, value_name = "DAYS", default_value_t = 7 as u32)]
-        days: u32,
+        /// The number of days to print. Defaults to `print_days` in the config, or 7.
+        #[arg(short, long, value_name = "DAYS")]
+        days: Option<u32>,
 
         /// Output file: if provided, the output will be written to this file instead of stdout.
         /// If the file already exists, it will be overwritten.
         #[arg(short, long, value_name = "FILE")]
         output: Option<String>,
+
+        /// The format: `markdown` or `html` (which embeds the attached images).
+        /// Defaults to `html` if the output file ends in `.html`, then to `print_format` in the config, then to `markdown`.
+        #[arg(short, long, value_name = "FORMAT")]
+        format: Option<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
     },
 
     /// Used for seeing, adding or editing categories of questions.
@@ -46,14 +150,215 @@ pub enum SubCommand {
         #[command(subcommand)]
         subcommand: EntriesSubcommands,
     },
+    /// Searches the details of the entries and the labels of their category and choice.
+    Search {
+        /// What to search for. Supports `"a phrase"` (quoted for the shell too), `prefix*`, `this OR that` and `NOT this`.
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Only entries from this date on (YYYY-MM-DD).
+        #[arg(short, long, value_name = "DATE")]
+        from: Option<String>,
+
+        /// Only entries up to this date, included (YYYY-MM-DD).
+        #[arg(short, long, value_name = "DATE")]
+        to: Option<String>,
+
+        /// Only entries of this category.
+        #[arg(short, long, value_name = "CATEGORY")]
+        category: Option<String>,
+
+        /// Only entries with this tag.
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+
+        /// The maximum number of matches to show.
+        #[arg(short, long, value_name = "N", default_value_t = 20)]
+        limit: u32,
+    },
+    /// Shows how often each choice was picked in a period, compared with the period before it.
+    Stats {
+        /// The period: `week`, `month`, `year` or a number of days. Defaults to 30 days.
+        #[arg(short, long, value_name = "PERIOD")]
+        period: Option<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Shows how an outcome category (e.g. mood) differs on the days each habit was logged, compared with the days it wasn't.
+    Insights {
+        /// The category whose choices are the outcome, e.g. mood.
+        outcome: String,
+
+        /// Only consider the choices of this category as habits. Defaults to the choices of every other category.
+        #[arg(short = 'H', long, value_name = "CATEGORY")]
+        habits: Option<String>,
+
+        /// Relate the outcome to the habits logged this many days before (e.g. 1 for "gym yesterday -> mood today").
+        #[arg(short, long, value_name = "DAYS", default_value_t = 0)]
+        lag: u32,
+
+        /// Leave out the habits with less than this many days with or without them.
+        #[arg(short, long, value_name = "DAYS", default_value_t = 5)]
+        min_samples: usize,
+
+        /// Only consider this period: `week`, `month`, `year` or a number of days. Defaults to all the entries.
+        #[arg(short, long, value_name = "PERIOD")]
+        period: Option<String>,
+    },
+    /// Draws a month grid or a "year in pixels" of a category, showing the choice picked on each day.
+    Calendar {
+        category: String,
+
+        /// The month to draw, written as YYYY-MM. Defaults to the current month.
+        #[arg(short, long, value_name = "MONTH", conflicts_with = "year")]
+        month: Option<String>,
+
+        /// Draws the whole year, with a row for each month.
+        #[arg(short, long, value_name = "YEAR")]
+        year: Option<i32>,
+    },
+    /// Draws a chart of a category in the terminal: a heatmap of a choice, a line of a rating scale, or monthly bars.
+    Chart {
+        category: String,
+
+        /// Draws a heatmap of the days this choice was picked.
+        #[arg(short, long, value_name = "CHOICE")]
+        choice: Option<String>,
+
+        /// The kind of chart: `heatmap`, `line` or `bars`. Defaults to a heatmap if a choice is given,
+        /// a line for rating scale categories, and bars otherwise.
+        #[arg(short, long, value_name = "KIND")]
+        kind: Option<String>,
+
+        /// The period to draw: `week`, `month`, `year` or a number of days.
+        #[arg(short, long, value_name = "PERIOD")]
+        period: Option<String>,
+
+        /// Averages the line by week instead of by day.
+        #[arg(short, long)]
+        weekly: bool,
+    },
+    /// Shows the progress towards the goals of the choices.
+    Goals,
+    /// Lists the choices and categories whose reminder timer has elapsed.
+    Due {
+        /// Prints one tab-separated line per item: kind, category, choice, days overdue and date last done.
+        #[arg(short, long)]
+        porcelain: bool,
+
+        /// Only prints the number of due items.
+        #[arg(short, long, conflicts_with = "porcelain")]
+        count: bool,
+    },
+    /// Shows the scheduled quizzes that are pending for today or were missed recently, and offers to answer them.
+    Status {
+        /// How many days to look back for missed quizzes.
+        #[arg(short, long, value_name = "DAYS", default_value_t = 7)]
+        days: u32,
+
+        /// Prints a single line (or nothing, if everything is done), e.g. for a shell prompt.
+        #[arg(short, long)]
+        short: bool,
+
+        /// Don't offer to run or back-fill the quizzes.
+        #[arg(short, long)]
+        no_prompt: bool,
+    },
+    /// Sends a notification for each due item. Meant to be run periodically, e.g. from cron or a systemd timer.
+    Remind {
+        /// The command used to send the notifications, e.g. "notify-send {title} {message}".
+        /// Defaults to `notify_command` in the config, or printing to stdout if that isn't set either.
+        #[arg(short, long, value_name = "COMMAND")]
+        command: Option<String>,
+
+        /// The text of the notifications, e.g. "{label} is {days_overdue} days overdue".
+        /// Defaults to `notify_template` in the config, or "{label}: {status}".
+        #[arg(short, long, value_name = "TEMPLATE")]
+        template: Option<String>,
+
+        /// Don't send the same reminder again until this many hours have passed.
+        #[arg(short, long, value_name = "HOURS", default_value_t = 24)]
+        every: u32,
+
+        /// Send all the reminders, even if they were sent recently.
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Creates the database, with the categories and quizzes of the starter template (mood, weather and habits).
+    /// Defaults to the database and the setup TOML of the profile in use.
     Init {
-        /// The path to the directory where the database will be stored.
+        /// The database to create. Defaults to the one from the config, e.g. `~/.local/share/jurnalo/jurnalo.sqlite`.
         #[arg(short, long, value_name = "PATH")]
         path: Option<String>,
 
-        /// The path to the toml file containing the configuration.
+        /// The TOML with the categories and quizzes to start with, instead of the starter template.
         #[arg(short, long, value_name = "PATH")]
         config: Option<String>,
+
+        /// Starts without any category or quiz, even if the profile in use has a setup TOML.
+        #[arg(long, conflicts_with = "config")]
+        empty: bool,
+
+        /// Replaces a database that already has entries. The old one is renamed, not deleted.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Saves a copy of the journal, by default in the `backups` directory next to the database.
+    /// The attachments aren't included.
+    Backup {
+        /// The file to save the backup to, or a directory to save it in.
+        #[arg(long, value_name = "PATH")]
+        to: Option<String>,
+    },
+    /// Replaces the journal with a backup. The current journal is backed up first.
+    Restore {
+        /// The backup to restore.
+        file: String,
+    },
+    /// Writes the whole journal (its categories, choices, quizzes and entries) as JSON, which `import json` reads back.
+    Export {
+        /// Output file: if provided, the JSON will be written to this file instead of stdout.
+        /// If the file already exists, it will be overwritten.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+    /// Imports the entries of other journals. The entries already in the journal are skipped.
+    Import {
+        #[command(subcommand)]
+        subcommand: ImportSubcommands,
+    },
+    /// Syncs the journal with another one, e.g. the journal of another computer in a synced folder, so that both have
+    /// the same entries. The entries edited in both journals since the last sync are reported, and left as they are.
+    Sync {
+        /// The database of the other journal.
+        path: String,
+
+        /// Keeps the version of `here` (this journal) or `there` (the other one) of the entries edited in both.
+        #[arg(long, value_name = "SIDE")]
+        prefer: Option<String>,
+
+        /// Shows what would be copied and updated, without saving anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Encrypts the details of the entries with a passphrase. The new entries are encrypted too.
+    Encrypt,
+    /// Decrypts the details of the entries, so the journal isn't encrypted anymore.
+    Decrypt,
+    /// Changes the passphrase of an encrypted journal.
+    Rekey,
+    /// Forgets the passphrase typed recently, so the next command asks for it again.
+    Lock,
+    /// Shows the settings.
+    Config {
+        #[command(subcommand)]
+        subcommand: ConfigSubcommands,
+    },
+    /// Manages the profiles: separate journals, each with its own database.
+    Profile {
+        #[command(subcommand)]
+        subcommand: ProfileSubcommands,
     },
 }
 
@@ -126,6 +431,12 @@ pub enum QuizSubcommands {
     ListCategories {
         quiz: String,
     },
+    /// Sets when the quiz should be answered, checked by `jurnalo status`.
+    Schedule {
+        quiz: String,
+        /// One of `daily`, `weekdays`, `weekly <weekday>`, `monthly <day>`, or `none` to remove the schedule.
+        schedule: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -156,21 +467,145 @@ pub enum ChoiceSubcommands {
         label: String,
         new_name: String,
     },
+    /// Sets a frequency goal for the choice, e.g. 3 times per week, or once every 14 days.
+    SetGoal {
+        category: String,
+        label: String,
+        /// How many times the choice should be done in each period.
+        times: i32,
+        /// The unit of the period: day, week or month.
+        period: String,
+
+        /// How many units long each period is (e.g. `1 day --length 14` means "every 14 days").
+        #[arg(short, long, value_name = "LENGTH", default_value_t = 1)]
+        length: i32,
+    },
+    /// Removes the frequency goal of the choice.
+    ClearGoal {
+        category: String,
+        label: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigSubcommands {
+    /// Prints the effective value of each setting and where it comes from.
+    Show,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileSubcommands {
+    /// Lists the profiles. The one in use is marked with `*`.
+    List,
+    /// Creates a profile. The first one created becomes the active one.
+    Create {
+        name: String,
+
+        /// The database of the profile. Defaults to `<name>.sqlite` in the data directory, e.g. `~/.local/share/jurnalo`.
+        #[arg(short, long, value_name = "PATH")]
+        database: Option<String>,
+
+        /// The TOML used by `init` to set up the database of the profile.
+        #[arg(short, long, value_name = "PATH")]
+        setup: Option<String>,
+    },
+    /// Makes the profile the active one.
+    Use { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PeopleSubcommands {
+    /// Reminds you of a person (in `due` and `remind`) after some days without mentioning them.
+    SetReminder {
+        /// The person, with or without the @.
+        person: String,
+        days: u32,
+    },
+    /// Removes the reminder of a person.
+    ClearReminder {
+        /// The person, with or without the @.
+        person: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 pub enum EntriesSubcommands {
     /// Prints the recent entries to the terminal.
     Print {
-        /// The number of days to print.
-        #[arg(short, long, value_name = "DAYS", default_value_t = 7 as u32)]
-        days: u32,
+        /// The number of days to print. Defaults to `print_days` in the config, or 7.
+        #[arg(short, long, value_name = "DAYS")]
+        days: Option<u32>,
 
         /// Output file: if provided, the output will be written to this file instead of stdout.
         /// If the file already exists, it will be overwritten.
         #[arg(short, long, value_name = "FILE")]
         output: Option<String>,
+
+        /// The format: `markdown` or `html` (which embeds the attached images).
+        /// Defaults to `html` if the output file ends in `.html`, then to `print_format` in the config, then to `markdown`.
+        #[arg(short, long, value_name = "FORMAT")]
+        format: Option<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
     },
     /// Pushes the latest entry to yesterday.
     PushLatestToYesterday,
+    /// Adds tags to an entry.
+    Tag {
+        /// The id of the entry (as shown by `jurnalo search`), or `last` for the latest entry.
+        entry: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Removes tags from an entry.
+    Untag {
+        /// The id of the entry (as shown by `jurnalo search`), or `last` for the latest entry.
+        entry: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportSubcommands {
+    /// Imports a Markdown or text journal, or a directory of them, e.g. the output of `jurnalo print`.
+    /// The headings with a date (YYYY-MM-DD) and a time (HH:MM) give the time of the paragraphs below them.
+    Markdown {
+        /// The file, or the directory with the `.md` and `.txt` files.
+        path: String,
+
+        /// Shows the entries that would be imported, without saving them.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Reads the times as local times, like in a journal written by hand, instead of UTC like `print`.
+        #[arg(long)]
+        local_time: bool,
+    },
+    /// Imports the JSON written by `jurnalo export`, into an empty journal to restore it, or into another one.
+    /// The categories, choices and quizzes the journal doesn't have yet are added, matched by label.
+    Json {
+        /// The JSON file.
+        path: String,
+
+        /// Shows what would be added, without saving it.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Imports the CSV export of a mood or habit tracker, e.g. its moods, activities and notes.
+    /// The mapping file tells which columns hold the date, the time and the notes, and which category each other column
+    /// is about.
+    Csv {
+        /// The CSV file.
+        path: String,
+
+        /// The TOML file that maps the columns to categories and their values to choices.
+        #[arg(short, long)]
+        mapping: String,
+
+        /// Shows the entries that would be imported and the choices that would be created, without saving them.
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
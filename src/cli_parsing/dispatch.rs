@@ -1,42 +1,185 @@
 use super::clap_structs::{
-    Args, CategorySubcommands, ChoiceSubcommands, EntriesSubcommands, QuizSubcommands, SubCommand,
+    Args, CategorySubcommands, ChoiceSubcommands, ConfigSubcommands, EntriesSubcommands,
+    ImportSubcommands, PeopleSubcommands, ProfileSubcommands, QuizSubcommands, SubCommand,
 };
 
+use std::path::Path;
+
+use crate::backend::api::Api;
+use crate::backend::{backups, config};
+use crate::errors::Error;
 use crate::modes;
 
 /// Dispatches the arguments to the appropriate functions.
-pub fn dispatch(args: &Args) {
-    if args.quiz.is_none() && args.note.is_none() && args.subcommand.is_none() {
-        println!("No arguments provided");
+pub fn dispatch(args: &Args) -> Result<(), Error> {
+    if let Some(ref profile) = args.profile {
+        crate::backend::profiles::select_profile(profile);
     }
 
-    // From now on, we can assume that at least one of the arguments is Some.
-    // Since quiz and note are positional arguments, we can assume note is None if quiz is None.
-    // Therefore, at least one of quiz or subcommand is Some.
+    // These commands don't use the journal, so they work before it is created.
+    match args.subcommand {
+        Some(SubCommand::Init {
+            ref path,
+            ref config,
+            empty,
+            force,
+        }) => return init(path, config, empty, force),
+        Some(SubCommand::Backup { ref to }) => return modes::backup::backup(to),
+        Some(SubCommand::Restore { ref file }) => return modes::backup::restore(file),
+        Some(SubCommand::Lock) => return modes::encryption::lock(),
+        Some(SubCommand::Config { ref subcommand }) => {
+            return match subcommand {
+                ConfigSubcommands::Show => modes::config::print_config(),
+            }
+        }
+        Some(SubCommand::Profile { ref subcommand }) => {
+            return dispatch_profile_subcommands(subcommand)
+        }
+        _ => {}
+    }
 
-    if let Some(ref quiz_name) = args.quiz {
-        modes::run_quiz::quiz_full(quiz_name).unwrap();
-        return;
+    let config = config::init()?;
+    let no_arguments = args.quiz.is_none() && args.note.is_none() && args.subcommand.is_none();
+    // Without arguments, the default quiz is run.
+    let quiz = match args.quiz {
+        Some(ref quiz_name) => Some(quiz_name),
+        None if no_arguments => match config.default_quiz.value {
+            Some(ref quiz_name) => Some(quiz_name),
+            None => {
+                use clap::CommandFactory;
+                Args::command().print_help()?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    // A single connection is shared by everything the command does.
+    let api = &mut Api::connect()?;
+    if let Some(ref subcommand) = args.subcommand {
+        if changes_existing_data(subcommand) {
+            if let Some(backup_path) = backups::automatic_backup(
+                Path::new(&config.database.value),
+                config.backups_to_keep.value,
+                false,
+            )? {
+                eprintln!("Saved an automatic backup to {}.", backup_path.display());
+            }
+        }
+    }
+
+    if let Some(quiz_name) = quiz {
+        return modes::run_quiz::quiz_full(api, quiz_name, None);
     }
 
     // Now we can assume that quiz and note are None, and subcommand is Some.
 
     match args.subcommand {
         Some(ref subcommand) => match subcommand {
-            SubCommand::Print { days, output } => {
-                modes::print::print(*days, output);
-            }
-            SubCommand::Category { subcommand } => {
-                dispatch_category_subcommands(subcommand);
+            SubCommand::Log { note, attach } => modes::quick_note::parse_note(api, note, attach),
+            SubCommand::People {
+                subcommand,
+                places,
+                rescan,
+            } => match subcommand {
+                Some(PeopleSubcommands::SetReminder { person, days }) => {
+                    modes::people::set_reminder_for_person(api, person, *days)
+                }
+                Some(PeopleSubcommands::ClearReminder { person }) => {
+                    modes::people::clear_reminder_for_person(api, person)
+                }
+                None => modes::people::print_people(api, *places, *rescan),
+            },
+            SubCommand::Tags {
+                period,
+                rescan,
+                filter,
+            } => modes::tags::print_tags(api, period, *rescan, &filter.to_entry_filter()?),
+            SubCommand::Print {
+                days,
+                output,
+                format,
+                filter,
+            } => modes::print::print(api, *days, output, format, &filter.to_entry_filter()?),
+            SubCommand::Category { subcommand } => dispatch_category_subcommands(api, subcommand),
+            SubCommand::Quiz { subcommand } => dispatch_quiz_subcommands(api, subcommand),
+            SubCommand::Choice { subcommand } => dispatch_choice_subcommands(api, subcommand),
+            SubCommand::Entries { subcommand } => dispatch_entries_subcommands(api, subcommand),
+            SubCommand::Search {
+                query,
+                from,
+                to,
+                category,
+                tag,
+                limit,
+            } => modes::search::print_search(api, query, from, to, category, tag, *limit),
+            SubCommand::Stats { period, filter } => {
+                modes::stats::print_stats(api, period, &filter.to_entry_filter()?)
             }
-            SubCommand::Quiz { subcommand } => {
-                dispatch_quiz_subcommands(subcommand);
+            SubCommand::Insights {
+                outcome,
+                habits,
+                lag,
+                min_samples,
+                period,
+            } => modes::insights::print_insights(api, outcome, habits, *lag, *min_samples, period),
+            SubCommand::Calendar {
+                category,
+                month,
+                year,
+            } => modes::calendar::print_calendar(api, category, month, *year),
+            SubCommand::Chart {
+                category,
+                choice,
+                kind,
+                period,
+                weekly,
+            } => modes::chart::print_chart(api, category, choice, kind, period, *weekly),
+            SubCommand::Goals => modes::goals::print_goals_report(api),
+            SubCommand::Due { porcelain, count } => modes::due::print_due(api, *porcelain, *count),
+            SubCommand::Status {
+                days,
+                short,
+                no_prompt,
+            } => modes::status::print_status(api, *days, *short, !no_prompt),
+            SubCommand::Remind {
+                command,
+                template,
+                every,
+                force,
+            } => modes::remind::remind(api, command, template, *every, *force),
+            SubCommand::Export { output } => modes::export::export(api, output),
+            SubCommand::Import { subcommand } => match subcommand {
+                ImportSubcommands::Json { path, dry_run } => {
+                    modes::import::json::import_json(api, path, *dry_run)
+                }
+                ImportSubcommands::Markdown {
+                    path,
+                    dry_run,
+                    local_time,
+                } => modes::import::markdown::import_markdown(api, path, *dry_run, *local_time),
+                ImportSubcommands::Csv {
+                    path,
+                    mapping,
+                    dry_run,
+                } => modes::import::csv::import_csv(api, path, mapping, *dry_run),
+            },
+            SubCommand::Sync {
+                path,
+                prefer,
+                dry_run,
+            } => modes::sync::sync(api, path, prefer, *dry_run),
+            SubCommand::Encrypt => modes::encryption::encrypt(api),
+            SubCommand::Decrypt => modes::encryption::decrypt(api),
+            SubCommand::Rekey => modes::encryption::rekey(api),
+            SubCommand::Init { .. }
+            | SubCommand::Backup { .. }
+            | SubCommand::Restore { .. }
+            | SubCommand::Lock
+            | SubCommand::Config { .. }
+            | SubCommand::Profile { .. } => {
+                unreachable!("The commands that don't use the journal are dispatched above.")
             }
-            SubCommand::Choice { subcommand } => dispatch_choice_subcommands(subcommand),
-            SubCommand::Entries { subcommand } => {
-                dispatch_entries_subcommands(subcommand);
-            }
-            SubCommand::Init { path, config } => crate::backend::setup(config, path),
         },
         None => {
             unreachable!("If we got here, it means that quiz is None, note is None, and subcommand is None, which goes against our assumptions.");
@@ -44,100 +187,171 @@ pub fn dispatch(args: &Args) {
     }
 }
 
+/// Whether the command changes or removes existing data, so the journal gets its daily automatic backup before it runs.
+/// Adding data isn't included, as it can be undone without a backup, except for the imports, which add many entries.
+fn changes_existing_data(subcommand: &SubCommand) -> bool {
+    match subcommand {
+        SubCommand::Category { subcommand } => matches!(
+            subcommand,
+            CategorySubcommands::Disable { .. } | CategorySubcommands::Rename { .. }
+        ),
+        SubCommand::Choice { subcommand } => matches!(
+            subcommand,
+            ChoiceSubcommands::Disable { .. } | ChoiceSubcommands::Rename { .. }
+        ),
+        SubCommand::Quiz { subcommand } => matches!(
+            subcommand,
+            QuizSubcommands::Rename { .. } | QuizSubcommands::UnlinkCategory { .. }
+        ),
+        SubCommand::Entries { subcommand } => matches!(
+            subcommand,
+            EntriesSubcommands::PushLatestToYesterday | EntriesSubcommands::Untag { .. }
+        ),
+        SubCommand::Import { subcommand } => match subcommand {
+            ImportSubcommands::Json { dry_run, .. }
+            | ImportSubcommands::Markdown { dry_run, .. }
+            | ImportSubcommands::Csv { dry_run, .. } => !dry_run,
+        },
+        SubCommand::Sync { dry_run, .. } => !dry_run,
+        // Not `Encrypt`, whose backup would be a new copy of the details in plain text.
+        SubCommand::Decrypt | SubCommand::Rekey => true,
+        _ => false,
+    }
+}
+
 /// Dispatches the category subcommands to the appropriate functions.
-fn dispatch_category_subcommands(subcommand: &CategorySubcommands) {
+fn dispatch_category_subcommands(
+    api: &mut Api,
+    subcommand: &CategorySubcommands,
+) -> Result<(), Error> {
     match subcommand {
         CategorySubcommands::Create { category, prompt } => {
-            modes::alter::new_category(category, prompt);
-        }
-        CategorySubcommands::Disable { category } => {
-            modes::alter::disable_category(category);
+            modes::alter::new_category(api, category, prompt)
         }
+        CategorySubcommands::Disable { category } => modes::alter::disable_category(api, category),
         CategorySubcommands::Rename { category, new_name } => {
-            modes::alter::rename_category(category, new_name);
-        }
-        CategorySubcommands::List => {
-            modes::alter::list_all_categories();
+            modes::alter::rename_category(api, category, new_name)
         }
+        CategorySubcommands::List => modes::alter::list_all_categories(api),
         CategorySubcommands::AddChoice {
             category,
             choice_label,
             choice_shortcut,
-        } => {
-            modes::alter::new_choice(choice_label, choice_shortcut, category);
-        }
+        } => modes::alter::new_choice(api, choice_label, choice_shortcut, category),
         CategorySubcommands::ListChoices { category } => {
-            modes::alter::list_all_choices_in_category(category);
+            modes::alter::list_all_choices_in_category(api, category)
         }
     }
 }
 
 /// Dispatches the quiz subcommands to the appropriate functions.
-fn dispatch_quiz_subcommands(subcommand: &QuizSubcommands) {
+fn dispatch_quiz_subcommands(api: &mut Api, subcommand: &QuizSubcommands) -> Result<(), Error> {
     match subcommand {
-        QuizSubcommands::Create { quiz } => {
-            modes::alter::new_quiz(quiz);
-        }
+        QuizSubcommands::Create { quiz } => modes::alter::new_quiz(api, quiz),
         QuizSubcommands::LinkCategory { quiz, category } => {
-            modes::alter::link_category_to_quiz(category, quiz);
+            modes::alter::link_category_to_quiz(api, category, quiz)
         }
         QuizSubcommands::UnlinkCategory { quiz, category } => {
-            modes::alter::unlink_category_from_quiz(category, quiz);
+            modes::alter::unlink_category_from_quiz(api, category, quiz)
         }
         QuizSubcommands::Rename { quiz, new_name } => {
-            modes::alter::rename_quiz(quiz, new_name);
-        }
-        QuizSubcommands::List => {
-            modes::alter::list_all_quizzes();
+            modes::alter::rename_quiz(api, quiz, new_name)
         }
+        QuizSubcommands::List => modes::alter::list_all_quizzes(api),
         QuizSubcommands::ListCategories { quiz } => {
-            modes::alter::list_all_categories_in_quiz(quiz);
+            modes::alter::list_all_categories_in_quiz(api, quiz)
+        }
+        QuizSubcommands::Schedule { quiz, schedule } => {
+            modes::alter::set_schedule_for_quiz(api, quiz, schedule)
         }
     }
 }
 
-fn dispatch_choice_subcommands(subcommand: &ChoiceSubcommands) {
+fn dispatch_choice_subcommands(api: &mut Api, subcommand: &ChoiceSubcommands) -> Result<(), Error> {
     match subcommand {
         ChoiceSubcommands::Add {
             label,
             shortcut,
             category,
-        } => {
-            modes::alter::new_choice(label, shortcut, category);
-        }
+        } => modes::alter::new_choice(api, label, shortcut, category),
         ChoiceSubcommands::Disable { category, label } => {
-            modes::alter::disable_choice(category, label);
+            modes::alter::disable_choice(api, category, label)
         }
         ChoiceSubcommands::Rename {
             category,
             label,
             new_name,
-        } => {
-            modes::alter::rename_choice(category, label, new_name);
-        }
+        } => modes::alter::rename_choice(api, category, label, new_name),
         ChoiceSubcommands::List { category } => {
-            modes::alter::list_all_choices_in_category(category);
+            modes::alter::list_all_choices_in_category(api, category)
         }
         ChoiceSubcommands::ChangeTimer {
             category,
             label,
             timer,
-        } => {
-            modes::alter::change_timer_for_choice(category, label, *timer);
-        }
+        } => modes::alter::change_timer_for_choice(api, category, label, *timer),
         ChoiceSubcommands::ToggleStreaks { category, label } => {
-            modes::alter::toggle_show_in_streaks_for_choice(category, label);
+            modes::alter::toggle_show_in_streaks_for_choice(api, category, label)
+        }
+        ChoiceSubcommands::SetGoal {
+            category,
+            label,
+            times,
+            period,
+            length,
+        } => modes::alter::set_goal_for_choice(api, category, label, *times, period, *length),
+        ChoiceSubcommands::ClearGoal { category, label } => {
+            modes::alter::clear_goal_for_choice(api, category, label)
         }
     }
 }
 
-fn dispatch_entries_subcommands(subcommand: &EntriesSubcommands) {
+fn dispatch_entries_subcommands(
+    api: &mut Api,
+    subcommand: &EntriesSubcommands,
+) -> Result<(), Error> {
     match subcommand {
-        EntriesSubcommands::Print { days, output } => {
-            modes::print::print(*days, output);
-        }
+        EntriesSubcommands::Print {
+            days,
+            output,
+            format,
+            filter,
+        } => modes::print::print(api, *days, output, format, &filter.to_entry_filter()?),
         EntriesSubcommands::PushLatestToYesterday => {
-            modes::alter::move_last_entry_to_yesterday();
+            modes::alter::move_last_entry_to_yesterday(api)
+        }
+        EntriesSubcommands::Tag { entry, tags } => modes::tags::tag_entry(api, entry, tags),
+        EntriesSubcommands::Untag { entry, tags } => modes::tags::untag_entry(api, entry, tags),
+    }
+}
+
+/// Sets up the database, using the setup TOML of the profile in use if none is given, unless `empty` is set.
+fn init(
+    path: &Option<String>,
+    config: &Option<String>,
+    empty: bool,
+    force: bool,
+) -> Result<(), Error> {
+    config::init()?;
+    let config = match config {
+        Some(config) => Some(config.clone()),
+        None if empty => None,
+        None => {
+            crate::backend::profiles::current_profile()?.and_then(|(_, profile, _)| profile.setup)
         }
+    };
+    crate::backend::setup(&config, path, empty, force)
+}
+
+/// Dispatches the profile subcommands to the appropriate functions.
+fn dispatch_profile_subcommands(subcommand: &ProfileSubcommands) -> Result<(), Error> {
+    match subcommand {
+        ProfileSubcommands::List => modes::profile::list_profiles(),
+        ProfileSubcommands::Create {
+            name,
+            database,
+            setup,
+        } => modes::profile::create_profile(name, database, setup),
+        ProfileSubcommands::Use { name } => modes::profile::use_profile(name),
     }
 }
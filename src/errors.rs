@@ -1,37 +1,128 @@
 use diesel::result::Error as DieselError;
-// use thiserror::Error;
-// todo: implement trait thiserror::Error
+use thiserror::Error;
 
-#[derive(Debug)]
+/// The errors of jurnalo. Their messages are shown to the user, so they say what went wrong in plain words; the
+/// underlying errors (e.g. from Diesel) are only shown with `--verbose`.
+#[derive(Debug, Error)]
 pub enum Error {
-    IO(std::io::Error),
-    CLIParsing(ParsingCommandError),
-    DatabaseError(DieselError),
+    #[error("Couldn't read or write a file: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("A query to the database failed.")]
+    Database(#[from] DieselError),
+    #[error("Couldn't open the database.")]
+    Connection(#[from] diesel::ConnectionError),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("The category `{0}` doesn't exist. See `jurnalo category list`.")]
+    CategoryNotFound(String),
+    #[error(
+        "The category `{category}` has no choice `{choice}`. See `jurnalo choice list {category}`."
+    )]
+    ChoiceNotFound { category: String, choice: String },
+    #[error("The quiz `{0}` doesn't exist. See `jurnalo quiz list`.")]
+    QuizNotFound(String),
+    #[error("The entry {0} doesn't exist.")]
+    EntryNotFound(i32),
+    #[error("@{0} was never mentioned. See `jurnalo people`.")]
+    PersonNotFound(String),
+    #[error("The category `{0}` already exists.")]
     CategoryAlreadyExists(String),
+    #[error("The choice `{0}` already exists in this category.")]
     ChoiceAlreadyExists(String),
+    #[error("The quiz `{0}` already exists.")]
     QuizAlreadyExists(String),
+    #[error("The category `{category}` is already in the quiz `{quiz}`.")]
+    CategoryAlreadyInQuiz { category: String, quiz: String },
+    #[error("The profile `{0}` doesn't exist. See `jurnalo profile list`.")]
+    ProfileNotFound(String),
+    #[error("The profile `{0}` already exists.")]
+    ProfileAlreadyExists(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("There is no journal at {0}. Run `jurnalo init` to create it.")]
+    DatabaseNotFound(String),
+    #[error(
+        "There is already a journal at {0}. Use `--force` to move it aside and create a new one."
+    )]
+    DatabaseAlreadyExists(String),
+    #[error("The passphrase is wrong.")]
+    WrongPassphrase,
+    #[error("{0}")]
+    Encryption(String),
+    #[error("Couldn't copy the database.")]
+    Backup(#[from] rusqlite::Error),
+    #[error("Couldn't update the database to the latest version: {0}")]
+    Migration(String),
+    #[error("The database was updated by a newer version of jurnalo (migration {0}). Please update jurnalo to use it.")]
+    DatabaseTooNew(String),
 }
-#[derive(Debug)]
-pub enum ParsingCommandError {
-    QuizNotFound(String),
-}
-
-// Implementations:
 
-impl From<std::io::Error> for Error {
-    fn from(value: std::io::Error) -> Self {
-        Self::IO(value)
+impl Error {
+    /// The exit code of the process when the command fails with this error:
+    /// - 2: invalid input (like the usage errors reported by clap)
+    /// - 3: something that doesn't exist
+    /// - 4: something that already exists
+    /// - 5: invalid configuration
+    /// - 6: a database that can't be opened, copied or updated
+    /// - 7: a file that can't be read or written
+    /// - 1: anything else, e.g. a failed query
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::InvalidInput(_) | Self::WrongPassphrase => 2,
+            Self::CategoryNotFound(_)
+            | Self::ChoiceNotFound { .. }
+            | Self::QuizNotFound(_)
+            | Self::EntryNotFound(_)
+            | Self::PersonNotFound(_)
+            | Self::ProfileNotFound(_)
+            | Self::DatabaseNotFound(_) => 3,
+            Self::CategoryAlreadyExists(_)
+            | Self::ChoiceAlreadyExists(_)
+            | Self::QuizAlreadyExists(_)
+            | Self::CategoryAlreadyInQuiz { .. }
+            | Self::ProfileAlreadyExists(_)
+            | Self::DatabaseAlreadyExists(_) => 4,
+            Self::InvalidConfig(_) => 5,
+            Self::Connection(_)
+            | Self::Backup(_)
+            | Self::Migration(_)
+            | Self::DatabaseTooNew(_) => 6,
+            Self::IO(_) => 7,
+            Self::Database(_) | Self::Encryption(_) => 1,
+        }
     }
-}
 
-impl From<ParsingCommandError> for Error {
-    fn from(value: ParsingCommandError) -> Self {
-        Self::CLIParsing(value)
+    /// Prints the error to stderr. With `verbose`, the errors that caused it are printed too.
+    pub fn report(&self, verbose: bool) {
+        eprintln!("Error: {}", self);
+        if verbose {
+            let mut source = std::error::Error::source(self);
+            while let Some(error) = source {
+                eprintln!("  Caused by: {}", error);
+                source = error.source();
+            }
+        } else if matches!(
+            self,
+            Self::Database(_) | Self::Connection(_) | Self::Backup(_)
+        ) {
+            eprintln!("Run with `--verbose` for details.");
+        }
     }
 }
 
-impl From<DieselError> for Error {
-    fn from(value: DieselError) -> Self {
-        Self::DatabaseError(value)
-    }
+#[test]
+fn test_errors_explain_themselves_without_the_details() {
+    let error = Error::ChoiceNotFound {
+        category: "mood".to_owned(),
+        choice: "great".to_owned(),
+    };
+    assert_eq!(
+        error.to_string(),
+        "The category `mood` has no choice `great`. See `jurnalo choice list mood`."
+    );
+    assert_eq!(error.exit_code(), 3);
+
+    let error = Error::from(DieselError::NotFound);
+    assert_eq!(error.to_string(), "A query to the database failed.");
+    assert!(std::error::Error::source(&error).is_some());
 }
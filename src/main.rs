@@ -5,13 +5,18 @@ mod models;
 mod modes;
 
 use clap::Parser; // Needs to be in scope for the derive macro (::parse()) to work here.
+use std::process::ExitCode;
 
 // const MOCK_LOG_PATH: &str = "mockdb/logs.txt";
 
-fn main() {
+fn main() -> ExitCode {
     let args = cli_parsing::Args::parse();
 
-    dbg!(&args);
-
-    cli_parsing::dispatch(&args);
+    match cli_parsing::dispatch(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            e.report(args.verbose);
+            ExitCode::from(e.exit_code())
+        }
+    }
 }
@@ -10,6 +10,7 @@ pub mod queryable_or_selectable {
         pub id: i32,
         pub label: String,
         pub command: Option<String>,
+        pub schedule: Option<String>,
     }
 
     #[derive(Queryable, Selectable, Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
@@ -51,8 +52,55 @@ pub mod queryable_or_selectable {
         pub id: i32,
         pub timestamp: NaiveDateTime,
         pub category: Option<i32>,
-        pub value: Option<i32>,
         pub details: Option<String>,
+        /// Set for every entry, see the migration that added it.
+        pub uuid: Option<String>,
+    }
+
+    #[derive(Queryable, Selectable, Debug, Clone)]
+    #[diesel(table_name = crate::backend::schema::attachments)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct Attachment {
+        pub entry_id: i32,
+        pub hash: String,
+        pub file_name: String,
+        pub extension: Option<String>,
+    }
+
+    #[derive(Queryable, Selectable, Debug, Clone)]
+    #[diesel(table_name = crate::backend::schema::people)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct Person {
+        pub id: i32,
+        pub label: String,
+        pub reminder_timer_in_days: Option<i32>,
+    }
+
+    #[derive(Queryable, Selectable, Debug, Clone)]
+    #[diesel(table_name = crate::backend::schema::encryption)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct EncryptionSettings {
+        pub salt: String,
+        pub memory_kib: i32,
+        pub iterations: i32,
+        pub parallelism: i32,
+        pub check_value: String,
+    }
+
+    /// A match of the full-text search, from the `entries_fts` table (which isn't in the schema, as it's a virtual table).
+    #[derive(QueryableByName, Debug, Clone)]
+    pub struct SearchResult {
+        #[diesel(sql_type = diesel::sql_types::Integer)]
+        pub id: i32,
+        #[diesel(sql_type = diesel::sql_types::Timestamp)]
+        pub timestamp: NaiveDateTime,
+        #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+        pub category_label: Option<String>,
+        #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+        pub choice_label: Option<String>,
+        /// The part of the entry that matched, with the matches between the highlight markers.
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        pub snippet: String,
     }
 
     #[derive(Queryable, Selectable, Debug, Clone)]
@@ -66,11 +114,15 @@ pub mod queryable_or_selectable {
         pub category_label: String,
         pub show_in_streaks: i32,
         pub reminder_timer_in_days: Option<i32>,
+        pub goal_times: Option<i32>,
+        pub goal_period: Option<String>,
+        pub goal_period_length: i32,
     }
 }
 
 pub mod insertable {
     /// This module contains structs that are used for inserting into the database.
+    use chrono::{NaiveDate, NaiveDateTime};
     use diesel::prelude::*;
 
     #[derive(Insertable, Default)]
@@ -79,6 +131,7 @@ pub mod insertable {
     pub struct NewQuiz {
         pub label: String,
         pub command: Option<String>,
+        pub schedule: Option<String>,
     }
 
     #[derive(Insertable)]
@@ -126,9 +179,12 @@ pub mod insertable {
     #[diesel(table_name = crate::backend::schema::entries)]
     #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
     pub struct NewEntry {
+        pub timestamp: Option<NaiveDateTime>,
         pub category: Option<i32>,
         pub value: Option<i32>,
         pub details: Option<String>,
+        /// Generated on insertion if `None`.
+        pub uuid: Option<String>,
     }
 
     #[derive(Insertable, Default)]
@@ -140,5 +196,97 @@ pub mod insertable {
         pub category_label: String,
         pub show_in_streaks: Option<i32>,
         pub reminder_timer_in_days: Option<i32>,
+        pub goal_times: Option<i32>,
+        pub goal_period: Option<String>,
+        pub goal_period_length: Option<i32>,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::quiz_runs)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewQuizRun {
+        pub quiz_label: String,
+        pub for_date: NaiveDate,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::sent_reminders)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewSentReminder {
+        pub reminder_key: String,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::tags)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewTag {
+        pub label: String,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::attachments)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewAttachment {
+        pub entry_id: i32,
+        pub hash: String,
+        pub file_name: String,
+        pub extension: Option<String>,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::people)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewPerson {
+        pub label: String,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::places)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewPlace {
+        pub label: String,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::entries_to_people)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewEntryToPerson {
+        pub entry_id: i32,
+        pub person_id: i32,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::entries_to_places)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewEntryToPlace {
+        pub entry_id: i32,
+        pub place_id: i32,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::entries_to_tags)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewEntryToTag {
+        pub entry_id: i32,
+        pub tag_id: i32,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::synced_entries)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewSyncedEntry {
+        pub uuid: String,
+        pub hash: String,
+    }
+
+    #[derive(Insertable)]
+    #[diesel(table_name = crate::backend::schema::encryption)]
+    #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+    pub struct NewEncryptionSettings {
+        pub salt: String,
+        pub memory_kib: i32,
+        pub iterations: i32,
+        pub parallelism: i32,
+        pub check_value: String,
     }
 }
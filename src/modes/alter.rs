@@ -1,152 +1,278 @@
 use itertools::Itertools;
 
 /// This module contains functions for altering the database.
-use crate::backend::api;
+use crate::backend::api::Api;
+use crate::errors::Error;
 
-pub fn new_category(label: &str, prompt: &str) {
+pub fn new_category(api: &mut Api, label: &str, prompt: &str) -> Result<(), Error> {
     if label.is_empty() || prompt.is_empty() {
-        panic!("Invalid Input: You must provide a label and a prompt.");
+        return Err(Error::InvalidInput(
+            "You must provide a label and a prompt.".to_owned(),
+        ));
     }
-    api::post_category(label, prompt).unwrap();
+    api.post_category(label, prompt)?;
     println!("Success! Added category {}.", label);
+    Ok(())
 }
 
-pub fn new_choice(label: &str, shortcut: &str, category: &str) {
+pub fn new_choice(api: &mut Api, label: &str, shortcut: &str, category: &str) -> Result<(), Error> {
     if label.is_empty() || shortcut.is_empty() || category.is_empty() {
-        panic!("Invalid Input: You must provide a label, a shortcut, and a category.");
+        return Err(Error::InvalidInput(
+            "You must provide a label, a shortcut, and a category.".to_owned(),
+        ));
     }
-    api::post_choice(label, shortcut, category).unwrap();
+    api.post_choice(label, shortcut, category)?;
     println!("Success! Added choice {}.", label);
+    Ok(())
 }
 
-pub fn new_quiz(label: &str) {
+pub fn new_quiz(api: &mut Api, label: &str) -> Result<(), Error> {
     if label.is_empty() {
-        panic!("Invalid Input: You must provide a label for the quiz.");
+        return Err(Error::InvalidInput(
+            "You must provide a label for the quiz.".to_owned(),
+        ));
     }
-    api::post_quiz(label).unwrap();
+    api.post_quiz(label)?;
     println!("Success! Added quiz {}.", label);
+    Ok(())
 }
 
-pub fn link_category_to_quiz(category: &str, quiz: &str) {
+pub fn link_category_to_quiz(api: &mut Api, category: &str, quiz: &str) -> Result<(), Error> {
     if category.is_empty() || quiz.is_empty() {
-        panic!("Invalid Input: You must provide a category and a quiz.");
+        return Err(Error::InvalidInput(
+            "You must provide a category and a quiz.".to_owned(),
+        ));
     }
-    api::patch::link_category_to_quiz(category, quiz).unwrap();
+    api.link_category_to_quiz(category, quiz)?;
     println!("Success! Added category {} to quiz {}.", category, quiz);
+    Ok(())
 }
 
-pub fn unlink_category_from_quiz(category: &str, quiz: &str) {
+pub fn unlink_category_from_quiz(api: &mut Api, category: &str, quiz: &str) -> Result<(), Error> {
     if category.is_empty() || quiz.is_empty() {
-        panic!("Invalid Input: You must provide a category and a quiz.");
+        return Err(Error::InvalidInput(
+            "You must provide a category and a quiz.".to_owned(),
+        ));
     }
-    api::patch::unlink_category_from_quiz(category, quiz).unwrap();
+    api.unlink_category_from_quiz(category, quiz)?;
     println!("Success! Added category {} to quiz {}.", category, quiz);
+    Ok(())
 }
 
-pub fn list_all_categories() {
-    let categories = api::get_all_categories().unwrap();
+pub fn list_all_categories(api: &mut Api) -> Result<(), Error> {
+    let categories = api.get_all_categories()?;
     println!("Categories:");
     for category in categories {
         println!("{}: {}", category.label, category.prompt);
     }
+    Ok(())
 }
 
-pub fn list_all_categories_in_quiz(quiz: &str) {
+pub fn list_all_categories_in_quiz(api: &mut Api, quiz: &str) -> Result<(), Error> {
     if quiz.is_empty() {
-        panic!("Invalid Input: You must provide a quiz.");
+        return Err(Error::InvalidInput("You must provide a quiz.".to_owned()));
     }
-    let categories = api::get_categories_in_quiz(quiz).unwrap();
+    let categories = api.get_categories_in_quiz(quiz)?;
     println!("Categories in quiz {}:", quiz);
     for category in categories {
         println!("{}: {}", category.label, category.prompt);
     }
+    Ok(())
 }
 
-pub fn list_all_choices_in_category(category_label: &str) {
+pub fn list_all_choices_in_category(api: &mut Api, category_label: &str) -> Result<(), Error> {
     if category_label.is_empty() {
-        panic!("Invalid Input: You must provide a category.");
+        return Err(Error::InvalidInput(
+            "You must provide a category.".to_owned(),
+        ));
     }
-    let choices = api::get_choices_in_category(category_label).unwrap();
+    let choices = api.get_choices_in_category(category_label)?;
     println!("Choices in category {}:", category_label);
     for choice in choices {
         println!("  {}: {}", choice.label, choice.shortcut);
     }
+    Ok(())
 }
 
-pub fn disable_choice(category: &str, choice: &str) {
+pub fn disable_choice(api: &mut Api, category: &str, choice: &str) -> Result<(), Error> {
     if choice.is_empty() || category.is_empty() {
-        panic!("Invalid Input: You must provide a choice and a category.");
+        return Err(Error::InvalidInput(
+            "You must provide a choice and a category.".to_owned(),
+        ));
     }
-    api::patch::disable_choice(category, choice).unwrap();
+    api.disable_choice(category, choice)?;
     println!("Success! Disabled choice {}.", choice);
+    Ok(())
 }
 
-pub fn disable_category(category: &str) {
+pub fn disable_category(api: &mut Api, category: &str) -> Result<(), Error> {
     if category.is_empty() {
-        panic!("Invalid Input: You must provide a category.");
+        return Err(Error::InvalidInput(
+            "You must provide a category.".to_owned(),
+        ));
     }
-    api::patch::disable_category(category).unwrap();
+    api.disable_category(category)?;
     println!("Success! Disabled category {}.", category);
+    Ok(())
 }
 
-pub fn toggle_show_in_streaks_for_choice(category: &str, choice: &str) {
+pub fn toggle_show_in_streaks_for_choice(
+    api: &mut Api,
+    category: &str,
+    choice: &str,
+) -> Result<(), Error> {
     if choice.is_empty() {
-        panic!("Invalid Input: You must provide a choice.")
+        return Err(Error::InvalidInput("You must provide a choice.".to_owned()));
     }
-    api::patch::toggle_show_in_streaks_for_choice(category, choice).unwrap();
+    api.toggle_show_in_streaks_for_choice(category, choice)?;
     println!("Success! Toggled show_in_streaks for choice {}.", choice);
+    Ok(())
 }
 
-pub fn change_timer_for_choice(category: &str, choice: &str, new_timer: i32) {
+pub fn change_timer_for_choice(
+    api: &mut Api,
+    category: &str,
+    choice: &str,
+    new_timer: i32,
+) -> Result<(), Error> {
     if choice.is_empty() || category.is_empty() {
-        panic!("Invalid Input: You must provide a choice and a category.")
+        return Err(Error::InvalidInput(
+            "You must provide a choice and a category.".to_owned(),
+        ));
     }
     let timer_arg = if new_timer == -1 {
         None
     } else if new_timer >= 1 {
         Some(new_timer)
     } else {
-        panic!("Invalid Input: Timer must be -1 or greater than 1.")
+        return Err(Error::InvalidInput(
+            "Timer must be -1 or greater than 1.".to_owned(),
+        ));
     };
 
-    api::patch::change_timer_for_choice(choice, timer_arg).unwrap();
+    api.change_timer_for_choice(category, choice, timer_arg)?;
     println!(
         "Success! Changed timer for choice {} to {} days.",
         choice, new_timer
     );
+    Ok(())
 }
 
-pub fn move_last_entry_to_yesterday() {
-    api::patch::move_last_entry_to_yesterday().unwrap();
+pub fn set_goal_for_choice(
+    api: &mut Api,
+    category: &str,
+    choice: &str,
+    times: i32,
+    period: &str,
+    length: i32,
+) -> Result<(), Error> {
+    if choice.is_empty() || category.is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide a choice and a category.".to_owned(),
+        ));
+    }
+    if times < 1 || length < 1 {
+        return Err(Error::InvalidInput(
+            "The goal times and the period length must be at least 1.".to_owned(),
+        ));
+    }
+    let Some(unit) = super::goals::PeriodUnit::from_label(period) else {
+        return Err(Error::InvalidInput(
+            "The goal period must be one of `day`, `week` or `month`.".to_owned(),
+        ));
+    };
+
+    api.set_goal_for_choice(category, choice, Some(times), Some(unit.label()), length)?;
+    let goal = super::goals::Goal {
+        times: times as u32,
+        unit,
+        length: length as u32,
+    };
+    println!(
+        "Success! Set goal for choice {} to {}.",
+        choice,
+        goal.describe()
+    );
+    Ok(())
+}
+
+pub fn clear_goal_for_choice(api: &mut Api, category: &str, choice: &str) -> Result<(), Error> {
+    if choice.is_empty() || category.is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide a choice and a category.".to_owned(),
+        ));
+    }
+    api.set_goal_for_choice(category, choice, None, None, 1)?;
+    println!("Success! Removed the goal for choice {}.", choice);
+    Ok(())
+}
+
+pub fn set_schedule_for_quiz(api: &mut Api, quiz: &str, schedule: &str) -> Result<(), Error> {
+    if quiz.is_empty() || schedule.is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide a quiz and a schedule.".to_owned(),
+        ));
+    }
+    if schedule.trim().eq_ignore_ascii_case("none") {
+        api.set_schedule_for_quiz(quiz, None)?;
+        println!("Success! Removed the schedule of quiz {}.", quiz);
+        return Ok(());
+    }
+    let Some(parsed) = super::status::Schedule::parse(schedule) else {
+        return Err(Error::InvalidInput("The schedule must be `daily`, `weekdays`, `weekly <weekday>`, `monthly <day>` or `none`.".to_owned()));
+    };
+
+    api.set_schedule_for_quiz(quiz, Some(&parsed.label()))?;
+    println!("Success! Scheduled quiz {} as {}.", quiz, parsed.label());
+    Ok(())
+}
+
+pub fn move_last_entry_to_yesterday(api: &mut Api) -> Result<(), Error> {
+    api.move_last_entry_to_yesterday()?;
     println!("Success! Moved last entry to yesterday.");
+    Ok(())
 }
 
-pub fn rename_category(category: &str, new_name: &str) {
+pub fn rename_category(api: &mut Api, category: &str, new_name: &str) -> Result<(), Error> {
     if category.is_empty() || new_name.is_empty() {
-        panic!("Invalid Input: You must provide a category and a new name.");
+        return Err(Error::InvalidInput(
+            "You must provide a category and a new name.".to_owned(),
+        ));
     }
-    api::patch::rename_category(category, new_name).unwrap();
+    api.rename_category(category, new_name)?;
     println!("Success! Renamed category {} to {}.", category, new_name);
+    Ok(())
 }
 
-pub fn rename_choice(category: &str, choice: &str, new_name: &str) {
+pub fn rename_choice(
+    api: &mut Api,
+    category: &str,
+    choice: &str,
+    new_name: &str,
+) -> Result<(), Error> {
     if choice.is_empty() || category.is_empty() || new_name.is_empty() {
-        panic!("Invalid Input: You must provide a choice, a category, and a new name.");
+        return Err(Error::InvalidInput(
+            "You must provide a choice, a category, and a new name.".to_owned(),
+        ));
     }
-    api::patch::rename_choice(category, choice, new_name).unwrap();
+    api.rename_choice(category, choice, new_name)?;
     println!("Success! Renamed choice {} to {}.", choice, new_name);
+    Ok(())
 }
 
-pub fn rename_quiz(quiz: &str, new_name: &str) {
+pub fn rename_quiz(api: &mut Api, quiz: &str, new_name: &str) -> Result<(), Error> {
     if quiz.is_empty() || new_name.is_empty() {
-        panic!("Invalid Input: You must provide a quiz and a new name.");
+        return Err(Error::InvalidInput(
+            "You must provide a quiz and a new name.".to_owned(),
+        ));
     }
-    api::patch::rename_quiz(quiz, new_name).unwrap();
+    api.rename_quiz(quiz, new_name)?;
     println!("Success! Renamed quiz {} to {}.", quiz, new_name);
+    Ok(())
 }
 
-pub fn list_all_quizzes() {
-    let quizzes = api::get_all_quizzes().unwrap();
+pub fn list_all_quizzes(api: &mut Api) -> Result<(), Error> {
+    let quizzes = api.get_all_quizzes()?;
 
     for (quiz, group) in &quizzes.into_iter().group_by(|(quiz, _)| quiz.label.clone()) {
         println!("{}", quiz);
@@ -154,4 +280,5 @@ pub fn list_all_quizzes() {
             println!("  {}: {}", category.label, category.prompt);
         }
     }
+    Ok(())
 }
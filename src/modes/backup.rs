@@ -0,0 +1,34 @@
+/// This module contains the logic for the `backup` and `restore` modes, which copy the database to a backup and back.
+use std::path::Path;
+
+use crate::backend::{backups, config};
+use crate::errors::Error;
+
+/// Backs the database up to `to` (a file or a directory), or else to the backups directory next to the database.
+pub fn backup(to: &Option<String>) -> Result<(), Error> {
+    let config = config::init()?;
+    let backup_path = backups::backup(
+        Path::new(&config.database.value),
+        to.as_deref().map(Path::new),
+    )?;
+    println!(
+        "Saved a backup of the journal to {}.",
+        backup_path.display()
+    );
+    Ok(())
+}
+
+/// Replaces the database with the backup, once it is checked. The replaced database is backed up first.
+pub fn restore(file: &str) -> Result<(), Error> {
+    let config = config::init()?;
+    let database_path = Path::new(&config.database.value);
+    let saved = backups::restore(database_path, Path::new(file), config.backups_to_keep.value)?;
+    if let Some(saved) = saved {
+        println!(
+            "Saved a backup of the replaced journal to {}.",
+            saved.display()
+        );
+    }
+    println!("Restored {} to {}.", file, database_path.display());
+    Ok(())
+}
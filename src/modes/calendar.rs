@@ -0,0 +1,237 @@
+/// This module contains the logic for the `calendar` mode, which draws a month grid or a "year in pixels" of a category,
+/// showing the choice picked on each day and marking the days with notes.
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::chart::CHOICE_SYMBOLS;
+use crate::backend::api::{self, Api};
+use crate::backend::config::{self, local_date};
+use crate::errors::Error;
+
+/// The ANSI 256-colour code of each choice, used when printing to a terminal.
+const CHOICE_COLORS: [u8; 10] = [34, 226, 208, 196, 33, 129, 51, 201, 244, 94];
+const NO_ENTRY_SYMBOL: char = '·';
+const NOTE_MARK: char = '*';
+
+/// How each day of the calendar is drawn.
+struct Painter {
+    /// The index of the choice picked on each day, in the order of the choices.
+    choice_by_day: BTreeMap<NaiveDate, usize>,
+    days_with_notes: BTreeSet<NaiveDate>,
+    color: bool,
+}
+
+impl Painter {
+    fn symbol(&self, date: NaiveDate) -> String {
+        let Some(&index) = self.choice_by_day.get(&date) else {
+            return NO_ENTRY_SYMBOL.to_string();
+        };
+        let symbol = CHOICE_SYMBOLS[index % CHOICE_SYMBOLS.len()];
+        if self.color {
+            format!(
+                "\x1b[38;5;{}m{}\x1b[0m",
+                CHOICE_COLORS[index % CHOICE_COLORS.len()],
+                symbol
+            )
+        } else {
+            symbol.to_string()
+        }
+    }
+
+    fn note_mark(&self, date: NaiveDate) -> char {
+        if self.days_with_notes.contains(&date) {
+            NOTE_MARK
+        } else {
+            ' '
+        }
+    }
+}
+
+/// Draws the calendar of the category for the month (`YYYY-MM`) or the year, defaulting to the current month.
+/// If several entries of the category were logged on the same day, the latest one is shown.
+pub fn print_calendar(
+    api: &mut Api,
+    category_label: &str,
+    month: &Option<String>,
+    year: Option<i32>,
+) -> Result<(), Error> {
+    if category_label.is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide a category.".to_owned(),
+        ));
+    }
+    api.get_category_by_label(category_label)?;
+    let mut choices = api.get_choices_in_category(category_label)?;
+    if choices.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "The category {} has no choices.",
+            category_label
+        )));
+    }
+    choices.sort_by(|a, b| a.shortcut.cmp(&b.shortcut));
+
+    let today = config::today();
+    let (first_day, last_day) = match (month, year) {
+        (Some(month), _) => {
+            let first_day = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+                .map_err(|_| {
+                    Error::InvalidInput(
+                        "The month must be written as YYYY-MM, e.g. 2026-09.".to_owned(),
+                    )
+                })?;
+            (first_day, last_day_of_month(first_day))
+        }
+        (None, Some(year)) => (
+            NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| {
+                Error::InvalidInput(format!("The year {} is out of range.", year))
+            })?,
+            NaiveDate::from_ymd_opt(year, 12, 31).expect("Every year has a December 31st."),
+        ),
+        (None, None) => {
+            let first_day = today.with_day(1).expect("Every month has a first day.");
+            (first_day, last_day_of_month(first_day))
+        }
+    };
+
+    // The database is in UTC, so one more day on each side covers every timezone.
+    let entries = api.get_entries_between_dates(
+        (first_day - Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+        (last_day + Duration::days(2)).and_hms_opt(0, 0, 0).unwrap(),
+    )?;
+
+    let mut painter = Painter {
+        choice_by_day: BTreeMap::new(),
+        days_with_notes: BTreeSet::new(),
+        color: config::use_color(),
+    };
+    for api::EntryWithLabelsTuple(entry, entry_category, entry_choice) in entries {
+        let date = local_date(entry.timestamp);
+        if date < first_day || date > last_day {
+            continue;
+        }
+        if entry.details.as_deref().is_some_and(|d| !d.is_empty()) {
+            painter.days_with_notes.insert(date);
+        }
+        if entry_category.as_deref() != Some(category_label) {
+            continue;
+        }
+        // The entries are sorted by timestamp, so the latest choice of each day wins.
+        if let Some(index) =
+            entry_choice.and_then(|label| choices.iter().position(|c| c.label == label))
+        {
+            painter.choice_by_day.insert(date, index);
+        }
+    }
+
+    let grid = if month.is_none() && year.is_some() {
+        render_year(&painter, first_day.year())
+    } else {
+        render_month(&painter, first_day)
+    };
+    println!("{}", category_label);
+    println!("{}", grid);
+
+    let legend: Vec<String> = choices
+        .iter()
+        .enumerate()
+        .map(|(i, choice)| {
+            let days = painter.choice_by_day.values().filter(|c| **c == i).count();
+            let symbol = painter
+                .choice_by_day
+                .iter()
+                .find(|(_, c)| **c == i)
+                .map(|(date, _)| painter.symbol(*date))
+                .unwrap_or(CHOICE_SYMBOLS[i % CHOICE_SYMBOLS.len()].to_string());
+            format!("{} {} ({})", symbol, choice.label, days)
+        })
+        .chain([
+            format!("{} no entry", NO_ENTRY_SYMBOL),
+            format!("{} has notes", NOTE_MARK),
+        ])
+        .collect();
+    println!("{}", legend.join("  "));
+    Ok(())
+}
+
+fn last_day_of_month(first_day: NaiveDate) -> NaiveDate {
+    let (year, month) = match first_day.month() {
+        12 => (first_day.year() + 1, 1),
+        m => (first_day.year(), m + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).expect("The first of the next month exists.")
+        - Duration::days(1)
+}
+
+/// Renders a month grid with a column for each day of the week, starting on Monday.
+fn render_month(painter: &Painter, first_day: NaiveDate) -> String {
+    let last_day = last_day_of_month(first_day);
+    let mut rows = vec![
+        first_day.format("%B %Y").to_string(),
+        " Mon  Tue  Wed  Thu  Fri  Sat  Sun".to_owned(),
+    ];
+
+    let mut row = "     ".repeat(first_day.weekday().num_days_from_monday() as usize);
+    let mut date = first_day;
+    while date <= last_day {
+        row += &format!(
+            " {:>2}{}{}",
+            date.day(),
+            painter.symbol(date),
+            painter.note_mark(date)
+        );
+        if date.weekday() == chrono::Weekday::Sun {
+            rows.push(std::mem::take(&mut row).trim_end().to_owned());
+        }
+        date += Duration::days(1);
+    }
+    if !row.is_empty() {
+        rows.push(row.trim_end().to_owned());
+    }
+    rows.join("\n")
+}
+
+/// Renders a "year in pixels": a row for each month and a column for each day of the month.
+fn render_year(painter: &Painter, year: i32) -> String {
+    let mut header = String::from("    ");
+    for day in 1..=31 {
+        header += &if day == 1 || day % 5 == 0 {
+            format!("{:<2}", day)
+        } else {
+            "  ".to_owned()
+        };
+    }
+    let mut rows = vec![year.to_string(), header.trim_end().to_owned()];
+
+    for month in 1..=12 {
+        let first_day = NaiveDate::from_ymd_opt(year, month, 1).expect("Valid month.");
+        let mut row = first_day.format("%b ").to_string();
+        let mut date = first_day;
+        while date <= last_day_of_month(first_day) {
+            row += &painter.symbol(date);
+            row.push(painter.note_mark(date));
+            date += Duration::days(1);
+        }
+        rows.push(row.trim_end().to_owned());
+    }
+    rows.join("\n")
+}
+
+#[test]
+fn test_render_month() {
+    let day = |d| NaiveDate::from_ymd_opt(2026, 9, d).unwrap();
+    let painter = Painter {
+        choice_by_day: [(day(1), 0), (day(2), 1)].into_iter().collect(),
+        days_with_notes: [day(2)].into_iter().collect(),
+        color: false,
+    };
+
+    let rendered = render_month(&painter, day(1));
+    let rows: Vec<&str> = rendered.lines().collect();
+    assert_eq!(rows[0], "September 2026");
+    // 2026-09-01 is a Tuesday.
+    assert_eq!(rows[2], "       1█   2▓*  3·   4·   5·   6·");
+    assert_eq!(rows[6], " 28·  29·  30·");
+    assert_eq!(rows.len(), 7);
+}
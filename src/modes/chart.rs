@@ -0,0 +1,347 @@
+/// This module contains the logic for the `chart` mode, which draws the trends of a category in the terminal,
+/// using only plain Unicode characters so it also works over SSH.
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+use crate::backend::api::{self, Api};
+use crate::backend::config;
+use crate::errors::Error;
+use crate::models::queryable_or_selectable as m_qos;
+
+const HEATMAP_SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The symbol of each choice, in the order of the choices: its fill in the stacked bars, and its day in the calendar.
+pub(super) const CHOICE_SYMBOLS: [char; 10] = ['█', '▓', '▒', '░', '#', '=', '+', ':', '-', '.'];
+const BAR_WIDTH: usize = 50;
+const RATING_SCALE_CATEGORY_TYPE: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    /// A GitHub-style calendar of how often a choice was picked each day.
+    Heatmap,
+    /// A sparkline of the average score of a category by day or week.
+    Line,
+    /// Stacked bars of how the choices of a category were distributed each month.
+    Bars,
+}
+
+impl ChartKind {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_lowercase().as_str() {
+            "heatmap" => Some(Self::Heatmap),
+            "line" | "sparkline" => Some(Self::Line),
+            "bars" => Some(Self::Bars),
+            _ => None,
+        }
+    }
+}
+
+/// Draws a chart of the category. The kind defaults to a heatmap if a choice is given, a line for rating scale
+/// categories, and bars otherwise. The period is `week`, `month`, `year` or a number of days, and defaults to
+/// half a year for heatmaps, a month for lines and a year for bars.
+pub fn print_chart(
+    api: &mut Api,
+    category_label: &str,
+    choice_label: &Option<String>,
+    kind: &Option<String>,
+    period: &Option<String>,
+    weekly: bool,
+) -> Result<(), Error> {
+    if category_label.is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide a category.".to_owned(),
+        ));
+    }
+    let category = api.get_category_by_label(category_label)?;
+    let mut choices = api.get_choices_in_category(category_label)?;
+    choices.sort_by(|a, b| a.shortcut.cmp(&b.shortcut));
+
+    let kind = match kind {
+        Some(k) => ChartKind::from_label(k).ok_or_else(|| {
+            Error::InvalidInput("The kind of chart must be `heatmap`, `line` or `bars`.".to_owned())
+        })?,
+        None if choice_label.is_some() => ChartKind::Heatmap,
+        None if category.category_type == RATING_SCALE_CATEGORY_TYPE => ChartKind::Line,
+        None => ChartKind::Bars,
+    };
+    if kind == ChartKind::Heatmap && choice_label.is_none() {
+        return Err(Error::InvalidInput(
+            "You must provide a choice for a heatmap.".to_owned(),
+        ));
+    }
+    if let Some(choice) = choice_label {
+        if !choices.iter().any(|c| &c.label == choice) {
+            return Err(Error::ChoiceNotFound {
+                category: category_label.to_owned(),
+                choice: choice.clone(),
+            });
+        }
+    }
+
+    let days: u32 = match period {
+        Some(p) => super::stats::parse_period(p).ok_or_else(|| {
+            Error::InvalidInput(
+                "The period must be `week`, `month`, `year` or a number of days.".to_owned(),
+            )
+        })?,
+        None => match kind {
+            ChartKind::Heatmap => 182,
+            ChartKind::Line => 30,
+            ChartKind::Bars => 365,
+        },
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    let today = config::today();
+    let first_day = today - Duration::days(days as i64 - 1);
+
+    // Only the entries of the category with a choice, as (local date, choice label).
+    let entries: Vec<(NaiveDate, String)> = api
+        .get_entries_between_dates(now - Duration::days(i64::from(days) + 1), now)?
+        .into_iter()
+        .filter_map(|api::EntryWithLabelsTuple(entry, cat, choice)| {
+            if cat.as_deref() != Some(category_label) {
+                return None;
+            }
+            let date = config::local_date(entry.timestamp);
+            (date >= first_day).then_some((date, choice?))
+        })
+        .collect();
+
+    let chart = match kind {
+        ChartKind::Heatmap => {
+            let choice_label = choice_label.as_ref().expect("Checked above.");
+            let mut counts: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+            for (date, _) in entries.iter().filter(|(_, c)| c == choice_label) {
+                *counts.entry(*date).or_default() += 1;
+            }
+            format!(
+                "{} -> {}, from {} to {}:\n{}",
+                category_label,
+                choice_label,
+                first_day,
+                today,
+                render_heatmap(&counts, first_day, today)
+            )
+        }
+        ChartKind::Line => render_line(&entries, &choices, first_day, today, weekly),
+        ChartKind::Bars => render_bars(&entries, &choices),
+    };
+    println!("{}", chart);
+    Ok(())
+}
+
+/// Renders a GitHub-style heatmap, with a row for each day of the week and a column for each week.
+fn render_heatmap(
+    counts: &BTreeMap<NaiveDate, u32>,
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> String {
+    let first_monday =
+        first_day - Duration::days(first_day.weekday().num_days_from_monday().into());
+    let weeks = (last_day - first_monday).num_days() / 7 + 1;
+    let mondays: Vec<NaiveDate> = (0..weeks)
+        .map(|week| first_monday + Duration::weeks(week))
+        .collect();
+
+    // Month labels above the first week of each month, unless they would overlap the previous label.
+    let mut header: Vec<char> = vec![' '; 4 + 2 * mondays.len() + 3];
+    let mut last_month = None;
+    let mut next_free_position = 0;
+    for (i, monday) in mondays.iter().enumerate() {
+        if last_month == Some(monday.month()) {
+            continue;
+        }
+        last_month = Some(monday.month());
+        let position = 4 + 2 * i;
+        if position >= next_free_position {
+            for (j, c) in monday.format("%b").to_string().chars().enumerate() {
+                header[position + j] = c;
+            }
+            next_free_position = position + 4;
+        }
+    }
+
+    let mut rows = vec![header.iter().collect::<String>().trim_end().to_owned()];
+    for weekday in 0..7 {
+        let mut row = (first_monday + Duration::days(weekday))
+            .format("%a ")
+            .to_string();
+        for monday in mondays.iter() {
+            let date = *monday + Duration::days(weekday);
+            if date < first_day || date > last_day {
+                row += "  ";
+                continue;
+            }
+            let count = counts.get(&date).copied().unwrap_or(0) as usize;
+            row.push(HEATMAP_SHADES[count.min(HEATMAP_SHADES.len() - 1)]);
+            row.push(' ');
+        }
+        rows.push(row.trim_end().to_owned());
+    }
+    rows.push(format!(
+        "    {} none  {} 1  {} 2  {} 3  {} 4+",
+        HEATMAP_SHADES[0],
+        HEATMAP_SHADES[1],
+        HEATMAP_SHADES[2],
+        HEATMAP_SHADES[3],
+        HEATMAP_SHADES[4]
+    ));
+    rows.join("\n")
+}
+
+/// Returns the score of each choice, for averaging: the shortcuts if they are all numbers, otherwise their position.
+fn choice_scores(choices: &[m_qos::Choice]) -> BTreeMap<String, f64> {
+    let numeric: Option<Vec<f64>> = choices
+        .iter()
+        .map(|c| c.shortcut.trim().parse::<f64>().ok())
+        .collect();
+    match numeric {
+        Some(scores) => choices
+            .iter()
+            .zip(scores)
+            .map(|(c, score)| (c.label.clone(), score))
+            .collect(),
+        None => choices
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.label.clone(), (i + 1) as f64))
+            .collect(),
+    }
+}
+
+/// Renders a sparkline of the average score of the choices by day (or by week), with the scale below it.
+fn render_line(
+    entries: &[(NaiveDate, String)],
+    choices: &[m_qos::Choice],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+    weekly: bool,
+) -> String {
+    let scores = choice_scores(choices);
+    let bucket_length = if weekly { 7 } else { 1 };
+    let buckets = (last_day - first_day).num_days() / bucket_length + 1;
+
+    let mut sums: Vec<(f64, u32)> = vec![(0.0, 0); buckets as usize];
+    for (date, choice_label) in entries {
+        if let Some(score) = scores.get(choice_label) {
+            let bucket = ((*date - first_day).num_days() / bucket_length) as usize;
+            sums[bucket].0 += score;
+            sums[bucket].1 += 1;
+        }
+    }
+    let averages: Vec<Option<f64>> = sums
+        .iter()
+        .map(|(sum, n)| (*n > 0).then(|| sum / *n as f64))
+        .collect();
+
+    let min = scores.values().copied().fold(f64::INFINITY, f64::min);
+    let max = scores.values().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let mut chart = format!(
+        "{} to {}, by {}:\n{}\n",
+        first_day,
+        last_day,
+        if weekly { "week" } else { "day" },
+        sparkline(&averages, min, max)
+    );
+    let mut scale: Vec<(&String, &f64)> = scores.iter().collect();
+    scale.sort_by(|a, b| a.1.total_cmp(b.1));
+    chart += &scale
+        .iter()
+        .map(|(label, score)| {
+            format!(
+                "{} {} ({})",
+                sparkline(&[Some(**score)], min, max),
+                label,
+                score
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+    chart
+}
+
+/// Maps each value to a block of a height proportional to where it falls between `min` and `max`. Missing values are spaces.
+fn sparkline(values: &[Option<f64>], min: f64, max: f64) -> String {
+    let top = (SPARKLINE_LEVELS.len() - 1) as f64;
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if max <= min => SPARKLINE_LEVELS[0],
+            Some(v) => {
+                let level = ((v - min) / (max - min) * top).round().clamp(0.0, top);
+                SPARKLINE_LEVELS[level as usize]
+            }
+        })
+        .collect()
+}
+
+/// Renders a bar for each month, split into the fraction of the entries of each choice, with a legend below.
+fn render_bars(entries: &[(NaiveDate, String)], choices: &[m_qos::Choice]) -> String {
+    let mut by_month: BTreeMap<(i32, u32), BTreeMap<&str, usize>> = BTreeMap::new();
+    for (date, choice_label) in entries {
+        *by_month
+            .entry((date.year(), date.month()))
+            .or_default()
+            .entry(choice_label.as_str())
+            .or_default() += 1;
+    }
+
+    let fill_of = |i: usize| CHOICE_SYMBOLS[i % CHOICE_SYMBOLS.len()];
+    let mut rows = Vec::new();
+    for ((year, month), counts) in by_month.iter() {
+        let total: usize = counts.values().sum();
+        let mut bar = String::new();
+        let mut cumulative = 0;
+        for (i, choice) in choices.iter().enumerate() {
+            let count = counts.get(choice.label.as_str()).copied().unwrap_or(0);
+            // Rounding the cumulative widths keeps the total width constant.
+            let start = cumulative * BAR_WIDTH / total;
+            cumulative += count;
+            let end = cumulative * BAR_WIDTH / total;
+            bar.extend(std::iter::repeat_n(fill_of(i), end - start));
+        }
+        rows.push(format!(
+            "{}-{:02} {:<BAR_WIDTH$} {}",
+            year, month, bar, total
+        ));
+    }
+    if rows.is_empty() {
+        return "No entries in this period.".to_owned();
+    }
+
+    rows.push(
+        choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| format!("{} {}", fill_of(i), choice.label))
+            .collect::<Vec<_>>()
+            .join("  "),
+    );
+    rows.join("\n")
+}
+
+#[test]
+fn test_sparkline() {
+    assert_eq!(
+        sparkline(&[Some(1.0), None, Some(3.0), Some(2.0)], 1.0, 3.0),
+        "▁ █▅"
+    );
+    assert_eq!(sparkline(&[Some(2.0)], 2.0, 2.0), "▁");
+}
+
+#[test]
+fn test_render_heatmap() {
+    let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+    // From Wednesday the 14th to Tuesday the 20th.
+    let counts: BTreeMap<NaiveDate, u32> = [(date(14), 1), (date(19), 5)].into_iter().collect();
+    let heatmap = render_heatmap(&counts, date(14), date(20));
+    let rows: Vec<&str> = heatmap.lines().collect();
+    assert_eq!(rows[0], "    Oct");
+    assert_eq!(rows[1], "Mon   █");
+    assert_eq!(rows[2], "Tue   ·");
+    assert_eq!(rows[3], "Wed ░");
+    assert_eq!(rows[7], "Sun ·");
+}
@@ -0,0 +1,65 @@
+/// This module contains the logic for the `config` mode, which shows the settings.
+use crate::backend::config::{self, Setting};
+use crate::errors::Error;
+
+/// Prints the effective value of each setting and where it comes from.
+pub fn print_config() -> Result<(), Error> {
+    let config = config::init()?;
+    let path = config::config_path();
+    println!(
+        "Config file: {}{}\n",
+        path.display(),
+        if path.exists() { "" } else { " (not found)" }
+    );
+
+    let rows = [
+        ("database", describe(&config.database, |v| v.clone())),
+        (
+            "default_quiz",
+            describe(&config.default_quiz, |v| optional(v.as_deref())),
+        ),
+        (
+            "day_starts_at",
+            describe(&config.day_starts_at, |v| format!("{}:00", v)),
+        ),
+        ("print_days", describe(&config.print_days, u32::to_string)),
+        (
+            "print_format",
+            describe(&config.print_format, |v| optional(v.as_deref())),
+        ),
+        (
+            "color",
+            describe(&config.color, |v| format!("{:?}", v).to_lowercase()),
+        ),
+        ("editor", describe(&config.editor, |v| v.clone())),
+        (
+            "notify_command",
+            describe(&config.notify_command, |v| optional(v.as_deref())),
+        ),
+        (
+            "notify_template",
+            describe(&config.notify_template, |v| v.clone()),
+        ),
+        (
+            "backups_to_keep",
+            describe(&config.backups_to_keep, u32::to_string),
+        ),
+        (
+            "unlock_minutes",
+            describe(&config.unlock_minutes, u32::to_string),
+        ),
+    ];
+    let padding_length = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0) + 3;
+    for (name, value) in rows {
+        println!("{:<padding_length$}{}", name, value);
+    }
+    Ok(())
+}
+
+fn describe<T>(setting: &Setting<T>, show: impl Fn(&T) -> String) -> String {
+    format!("{}  ({})", show(&setting.value), setting.source)
+}
+
+fn optional(value: Option<&str>) -> String {
+    value.unwrap_or("(not set)").to_owned()
+}
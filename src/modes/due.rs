@@ -0,0 +1,206 @@
+/// This module contains the logic for the `due` mode, which lists the choices, categories and people whose reminder
+/// timer has elapsed.
+use chrono::NaiveDateTime;
+
+use crate::backend::api::Api;
+use crate::backend::config;
+use crate::errors::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueKind {
+    Choice,
+    Category,
+    Person,
+}
+
+impl DueKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Choice => "choice",
+            Self::Category => "category",
+            Self::Person => "person",
+        }
+    }
+}
+
+/// The category label of the people in due items, as they don't belong to a category.
+pub const PEOPLE_CATEGORY_LABEL: &str = "people";
+
+/// A choice, category or person whose reminder timer has elapsed.
+#[derive(Debug, Clone)]
+pub struct DueItem {
+    pub kind: DueKind,
+    pub id: i32,
+    pub category_label: String,
+    /// The label of the choice or the person, or `None` if the item is a category.
+    pub choice_label: Option<String>,
+    pub timer_in_days: i32,
+    pub last_done: Option<NaiveDateTime>,
+}
+
+impl DueItem {
+    /// The name of the item as shown to the user, e.g. "habits -> Gym", "habits" or "@Monica".
+    pub fn display_label(&self) -> String {
+        match (self.kind, &self.choice_label) {
+            (DueKind::Person, Some(person)) => format!("@{}", person),
+            (_, Some(choice)) => format!("{} -> {}", self.category_label, choice),
+            (_, None) => self.category_label.clone(),
+        }
+    }
+
+    /// A key that identifies the item across runs, e.g. "choice:3".
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.kind.label(), self.id)
+    }
+
+    /// Describes how overdue the item is, e.g. "2 days overdue (last done 2026-09-01)" or "never done".
+    /// For people, e.g. "not mentioned in 34 days (last seen 2026-09-15)" or "never mentioned".
+    pub fn describe_status(&self, now: NaiveDateTime) -> String {
+        match (self.kind, self.last_done) {
+            (DueKind::Person, Some(ts)) => format!(
+                "not mentioned in {} days (last seen {})",
+                (now - ts).num_days(),
+                config::local_date(ts)
+            ),
+            (DueKind::Person, None) => "never mentioned".to_owned(),
+            (_, Some(ts)) => format!(
+                "{} (last done {})",
+                describe_days_overdue((now - ts).num_days() - self.timer_in_days as i64),
+                config::local_date(ts)
+            ),
+            (_, None) => "never done".to_owned(),
+        }
+    }
+
+    /// How many days past the reminder timer the item is, or `None` if it was never done.
+    pub fn days_overdue(&self, now: NaiveDateTime) -> Option<i64> {
+        self.last_done
+            .map(|ts| (now - ts).num_days() - self.timer_in_days as i64)
+    }
+}
+
+/// Returns whether something with the given reminder timer, last done at `last_done`, is due at `now`.
+pub fn is_due(timer_in_days: i32, last_done: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+    match last_done {
+        Some(ts) => (now - ts).num_days() >= timer_in_days.into(),
+        None => true, // default behavior, if no entry is found
+    }
+}
+
+/// Returns all the choices, categories and people that are due at `now`, the most overdue first.
+pub fn get_due_items(api: &mut Api, now: NaiveDateTime) -> Result<Vec<DueItem>, Error> {
+    let mut items: Vec<DueItem> = Vec::new();
+
+    for (category, last_done) in api.get_latest_timestamps_for_categories_with_timers()? {
+        let timer = category
+            .reminder_timer_in_days
+            .expect("The query only returns categories with timers.");
+        if is_due(timer, last_done, now) {
+            items.push(DueItem {
+                kind: DueKind::Category,
+                id: category.id,
+                category_label: category.label,
+                choice_label: None,
+                timer_in_days: timer,
+                last_done,
+            });
+        }
+    }
+
+    for (choice, last_done) in api.get_latest_timestamps_for_choices_with_timers()? {
+        let timer = choice
+            .reminder_timer_in_days
+            .expect("The query only returns choices with timers.");
+        if is_due(timer, last_done, now) {
+            items.push(DueItem {
+                kind: DueKind::Choice,
+                id: choice.id,
+                category_label: choice.category_label,
+                choice_label: Some(choice.label),
+                timer_in_days: timer,
+                last_done,
+            });
+        }
+    }
+
+    for (person, last_done) in api.get_latest_timestamps_for_people_with_timers()? {
+        let timer = person
+            .reminder_timer_in_days
+            .expect("The query only returns people with timers.");
+        if is_due(timer, last_done, now) {
+            items.push(DueItem {
+                kind: DueKind::Person,
+                id: person.id,
+                category_label: PEOPLE_CATEGORY_LABEL.to_owned(),
+                choice_label: Some(person.label),
+                timer_in_days: timer,
+                last_done,
+            });
+        }
+    }
+
+    // Never done first, then the most overdue.
+    items.sort_by_key(|item| item.days_overdue(now).map(std::cmp::Reverse));
+    Ok(items)
+}
+
+/// Prints the items that are due.
+/// With `porcelain`, prints one tab-separated line per item (kind, category, choice, days overdue, date last done),
+/// with empty fields for missing values. With `count`, only prints the number of due items.
+pub fn print_due(api: &mut Api, porcelain: bool, count: bool) -> Result<(), Error> {
+    let now = chrono::Utc::now().naive_utc();
+    let items = get_due_items(api, now)?;
+
+    if count {
+        println!("{}", items.len());
+        return Ok(());
+    }
+
+    if porcelain {
+        for item in items.iter() {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                item.kind.label(),
+                item.category_label,
+                item.choice_label.as_deref().unwrap_or(""),
+                item.days_overdue(now)
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                item.last_done
+                    .map(|ts| config::local_date(ts).to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        return Ok(());
+    }
+
+    if items.is_empty() {
+        println!("Nothing is due.");
+        return Ok(());
+    }
+
+    let padding_length = items
+        .iter()
+        .map(|item| item.display_label().len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+
+    println!("Due:");
+    for item in items.iter() {
+        println!(
+            "  {:<padding_length$}{}",
+            format!("{}:", item.display_label()),
+            item.describe_status(now)
+        );
+    }
+    Ok(())
+}
+
+fn describe_days_overdue(days: i64) -> String {
+    match days {
+        0 => "due today".to_owned(),
+        1 => "1 day overdue".to_owned(),
+        n => format!("{} days overdue", n),
+    }
+}
@@ -0,0 +1,66 @@
+/// This module contains the logic for the `encrypt`, `decrypt`, `rekey` and `lock` modes, which manage the encryption of
+/// the details of the entries.
+use crate::backend::api::Api;
+use crate::backend::{backups, config, encryption};
+use crate::errors::Error;
+
+/// Encrypts the journal with a new passphrase.
+pub fn encrypt(api: &mut Api) -> Result<(), Error> {
+    if api.is_encrypted()? {
+        return Err(Error::InvalidInput(
+            "The journal is already encrypted. Use `jurnalo rekey` to change the passphrase."
+                .to_owned(),
+        ));
+    }
+    println!(
+        "The passphrase can't be recovered: if you forget it, the details of the entries are lost."
+    );
+    let passphrase = encryption::read_new_passphrase()?;
+    let count = api.encrypt_journal(&passphrase)?;
+    println!("Encrypted the details of {} entries.", count);
+    println!(
+        "The labels of the categories, choices and quizzes, the tags, the mentions and the attachments stay \
+        unencrypted, and `search` only finds words in them."
+    );
+    println!(
+        "The backups made before now aren't encrypted: once you've checked the journal, delete them from {}.",
+        backups::backups_dir(std::path::Path::new(&config::get().database.value)).display()
+    );
+    Ok(())
+}
+
+/// Decrypts the journal, after asking for its passphrase.
+pub fn decrypt(api: &mut Api) -> Result<(), Error> {
+    let count = api.decrypt_journal()?;
+    println!(
+        "Decrypted the details of {} entries. The journal isn't encrypted anymore.",
+        count
+    );
+    Ok(())
+}
+
+/// Changes the passphrase of the journal.
+pub fn rekey(api: &mut Api) -> Result<(), Error> {
+    api.unlock()?;
+    if !api.is_encrypted()? {
+        return Err(Error::InvalidInput(
+            "The journal isn't encrypted. See `jurnalo encrypt`.".to_owned(),
+        ));
+    }
+    let passphrase = encryption::read_new_passphrase()?;
+    let count = api.rekey_journal(&passphrase)?;
+    println!(
+        "Encrypted the details of {} entries with the new passphrase.",
+        count
+    );
+    Ok(())
+}
+
+/// Forgets the keys cached by the previous commands, so the next one asks for the passphrase again.
+pub fn lock() -> Result<(), Error> {
+    match encryption::forget_all_keys()? {
+        0 => println!("No journal was unlocked."),
+        _ => println!("Locked the journals. The passphrase will be asked again."),
+    }
+    Ok(())
+}
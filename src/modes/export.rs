@@ -0,0 +1,26 @@
+/// This module contains the logic for the export mode, which writes the whole journal as a JSON document that
+/// `import json` reads back, see `api::export`.
+use crate::backend::api::Api;
+use crate::errors::Error;
+
+/// Writes the document to the file at `output`, or to stdout if it is `None`.
+pub fn export(api: &mut Api, output: &Option<String>) -> Result<(), Error> {
+    let document = api.export_document()?;
+    let json =
+        serde_json::to_string_pretty(&document).expect("The document can always be serialized.");
+    match output {
+        Some(path) => {
+            std::fs::write(path, json + "\n")?;
+            println!(
+                "Exported {} entries and the setup of the journal to {}.",
+                document.entries.len(),
+                path
+            );
+            if api.is_encrypted()? {
+                eprintln!("Note: the file isn't encrypted, unlike the journal.");
+            }
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
@@ -0,0 +1,298 @@
+/// This module contains the logic for frequency goals (e.g. "3 times per week") and the goals report.
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use itertools::Itertools;
+use std::collections::BTreeSet;
+
+use crate::backend::api::Api;
+use crate::backend::config;
+use crate::errors::Error;
+use crate::models::queryable_or_selectable as m_qos;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodUnit {
+    Day,
+    Week,
+    Month,
+}
+
+impl PeriodUnit {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_lowercase().as_str() {
+            "day" | "days" => Some(Self::Day),
+            "week" | "weeks" => Some(Self::Week),
+            "month" | "months" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    /// The label used for this unit in the database and in the TOML.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalStatus {
+    Met,
+    OnTrack,
+    Behind,
+}
+
+/// A frequency goal: the choice should be done on `times` different days per `length` `unit`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Goal {
+    pub times: u32,
+    pub unit: PeriodUnit,
+    pub length: u32,
+}
+
+impl Goal {
+    /// Returns the goal of the choice, or `None` if it doesn't have one.
+    pub fn from_choice(choice: &m_qos::Choice) -> Option<Self> {
+        let times = choice.goal_times?;
+        let unit = PeriodUnit::from_label(choice.goal_period.as_deref()?)?;
+        Some(Self {
+            times: times.max(1) as u32,
+            unit,
+            length: choice.goal_period_length.max(1) as u32,
+        })
+    }
+
+    /// Returns the bounds `[start, end)` of the goal period that is `periods_back` periods before the one containing `today`.
+    /// Weeks start on Monday and months on the 1st. Day periods are rolling windows, so the current one always ends today.
+    pub fn period_bounds(&self, today: NaiveDate, periods_back: u32) -> (NaiveDate, NaiveDate) {
+        let length = self.length as i64;
+        let back = periods_back as i64;
+        match self.unit {
+            PeriodUnit::Day => {
+                let end = today + Duration::days(1) - Duration::days(length * back);
+                (end - Duration::days(length), end)
+            }
+            PeriodUnit::Week => {
+                let this_monday =
+                    today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                let end = this_monday + Duration::weeks(1) - Duration::weeks(length * back);
+                (end - Duration::weeks(length), end)
+            }
+            PeriodUnit::Month => {
+                let first_of_month = today.with_day(1).expect("Every month has a first day.");
+                let end = first_of_month + Months::new(1) - Months::new(self.length * periods_back);
+                (end - Months::new(self.length), end)
+            }
+        }
+    }
+
+    /// Counts the days in the period `periods_back` periods ago in which the choice was done.
+    pub fn count_in_period(
+        &self,
+        days_done: &BTreeSet<NaiveDate>,
+        today: NaiveDate,
+        periods_back: u32,
+    ) -> u32 {
+        let (start, end) = self.period_bounds(today, periods_back);
+        days_done.range(start..end).count() as u32
+    }
+
+    /// Returns whether the current period is on track, given that the choice was done on `done` days so far.
+    /// Calendar periods are on track as long as they keep up with the fraction of the period that has elapsed.
+    pub fn status(&self, done: u32, today: NaiveDate) -> GoalStatus {
+        if done >= self.times {
+            return GoalStatus::Met;
+        }
+        let expected = match self.unit {
+            // Rolling windows are always "complete", so anything short of the goal is behind.
+            PeriodUnit::Day => self.times as f64,
+            PeriodUnit::Week | PeriodUnit::Month => {
+                let (start, end) = self.period_bounds(today, 0);
+                let elapsed = (today - start).num_days() + 1;
+                let total = (end - start).num_days();
+                self.times as f64 * elapsed as f64 / total as f64
+            }
+        };
+        if done as f64 >= expected.floor() {
+            GoalStatus::OnTrack
+        } else {
+            GoalStatus::Behind
+        }
+    }
+
+    /// Returns the number of consecutive goal periods in which the goal was met.
+    /// The current period only adds to the streak once it is met, but doesn't break it while still in progress.
+    pub fn streak(&self, days_done: &BTreeSet<NaiveDate>, today: NaiveDate) -> u32 {
+        let Some(earliest) = days_done.first() else {
+            return 0;
+        };
+
+        let mut streak = 0;
+        if self.count_in_period(days_done, today, 0) >= self.times {
+            streak += 1;
+        }
+        for periods_back in 1.. {
+            let (_, end) = self.period_bounds(today, periods_back);
+            if end <= *earliest || self.count_in_period(days_done, today, periods_back) < self.times
+            {
+                break;
+            }
+            streak += 1;
+        }
+        streak
+    }
+
+    /// Describes the goal, e.g. "3x per week" or "1x per 14 days".
+    pub fn describe(&self) -> String {
+        if self.length == 1 {
+            format!("{}x per {}", self.times, self.unit.label())
+        } else {
+            format!("{}x per {} {}s", self.times, self.length, self.unit.label())
+        }
+    }
+
+    /// Describes an amount of goal periods, e.g. "4 weeks" or "4 periods".
+    fn describe_periods(&self, n: u32) -> String {
+        let unit = if self.length == 1 {
+            self.unit.label()
+        } else {
+            "period"
+        };
+        if n == 1 {
+            format!("{} {}", n, unit)
+        } else {
+            format!("{} {}s", n, unit)
+        }
+    }
+}
+
+/// Prints the progress towards every goal.
+pub fn print_goals_report(api: &mut Api) -> Result<(), Error> {
+    match format_goals_report(api, config::today())? {
+        Some(report) => println!("{}", report),
+        None => println!("No goals set. Use `jurnalo choice set-goal` to add one."),
+    }
+    Ok(())
+}
+
+/// Returns a table with the progress of each choice towards its goal in the current period (the one containing the
+/// local date `today`), and the streak of periods in which the goal was met. Returns `None` if there are no goals.
+pub fn format_goals_report(api: &mut Api, today: NaiveDate) -> Result<Option<String>, Error> {
+    let response = api.get_timestamps_for_choices_with_goals()?;
+
+    let mut rows: Vec<(String, String)> = Vec::new();
+
+    for (_, group) in &response.into_iter().group_by(|(choice, _)| choice.id) {
+        let group: Vec<(m_qos::Choice, Option<chrono::NaiveDateTime>)> = group.collect();
+        let choice = &group[0].0;
+        let Some(goal) = Goal::from_choice(choice) else {
+            continue;
+        };
+
+        let days_done: BTreeSet<NaiveDate> = group
+            .iter()
+            .filter_map(|(_, timestamp)| timestamp.map(config::local_date))
+            .collect();
+
+        let done = goal.count_in_period(&days_done, today, 0);
+        let status = match goal.status(done, today) {
+            GoalStatus::Met => "done",
+            GoalStatus::OnTrack => "on track",
+            GoalStatus::Behind => "behind",
+        };
+        let this_period = if goal.length == 1 {
+            format!("this {}", goal.unit.label())
+        } else {
+            "this period".to_owned()
+        };
+
+        rows.push((
+            format!("{} -> {}", choice.category_label, choice.label),
+            format!(
+                "{}/{} {} ({}) [{}], streak: {}",
+                done,
+                goal.times,
+                this_period,
+                status,
+                goal.describe(),
+                goal.describe_periods(goal.streak(&days_done, today))
+            ),
+        ));
+    }
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let padding_length = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0) + 2;
+
+    let mut table = String::from("Goals:\n");
+    for (label, progress) in rows {
+        table += &format!("  {:<padding_length$}{}\n", format!("{}:", label), progress);
+    }
+
+    Ok(Some(table))
+}
+
+#[test]
+fn test_goal_period_bounds() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap(); // a Thursday
+    let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+
+    let weekly = Goal {
+        times: 3,
+        unit: PeriodUnit::Week,
+        length: 1,
+    };
+    assert_eq!(weekly.period_bounds(today, 0), (date(10, 12), date(10, 19)));
+    assert_eq!(weekly.period_bounds(today, 1), (date(10, 5), date(10, 12)));
+
+    let every_two_weeks = Goal {
+        times: 1,
+        unit: PeriodUnit::Day,
+        length: 14,
+    };
+    assert_eq!(
+        every_two_weeks.period_bounds(today, 0),
+        (date(10, 2), date(10, 16))
+    );
+
+    let monthly = Goal {
+        times: 2,
+        unit: PeriodUnit::Month,
+        length: 1,
+    };
+    assert_eq!(monthly.period_bounds(today, 0), (date(10, 1), date(11, 1)));
+    assert_eq!(monthly.period_bounds(today, 2), (date(8, 1), date(9, 1)));
+}
+
+#[test]
+fn test_goal_status_and_streak() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap(); // a Thursday
+    let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+
+    let weekly = Goal {
+        times: 3,
+        unit: PeriodUnit::Week,
+        length: 1,
+    };
+    // 4 of 7 days elapsed: 3 * 4/7 = 1.7, so at least 1 is needed to be on track.
+    assert_eq!(weekly.status(0, today), GoalStatus::Behind);
+    assert_eq!(weekly.status(1, today), GoalStatus::OnTrack);
+    assert_eq!(weekly.status(3, today), GoalStatus::Met);
+
+    let days_done: BTreeSet<NaiveDate> = [
+        date(9, 29),
+        date(9, 30),
+        date(10, 1),
+        date(10, 6),
+        date(10, 7),
+        date(10, 9),
+        date(10, 13),
+    ]
+    .into_iter()
+    .collect();
+    // The current week is still in progress, the two previous ones were met.
+    assert_eq!(weekly.count_in_period(&days_done, today, 0), 1);
+    assert_eq!(weekly.streak(&days_done, today), 2);
+}
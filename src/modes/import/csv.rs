@@ -0,0 +1,342 @@
+/// This module contains the logic for `import csv`, which reads the CSV exports of mood and habit trackers.
+///
+/// The columns are read as described by a mapping file, e.g. for a tracker that exports the date, the time, the mood,
+/// the activities and a note of each day:
+/// ```toml
+/// date_column = "full_date"
+/// date_format = "%Y-%m-%d"     # optional, YYYY-MM-DD and YYYY/MM/DD are found without it
+/// time_column = "time"         # optional, the entries without a time are set at noon
+/// note_columns = ["note_title", "note"]
+///
+/// [[columns]]
+/// column = "mood"
+/// category = "mood"
+/// values = { rad = "great", meh = "okay", awful = "" }  # "" skips the value
+///
+/// [[columns]]
+/// column = "activities"
+/// category = "activities"
+/// separator = " | "
+/// create_missing = true
+/// ```
+/// Each value of a mapped column becomes an entry of the choice with the same label (ignoring the case), or with the
+/// label given in `values`. The values without a choice are skipped and reported, unless `create_missing` is set, in
+/// which case their choices are created. The notes become entries without a category.
+use serde_derive::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use super::{ImportedEntry, Labels};
+use crate::backend::api::Api;
+use crate::errors::Error;
+use chrono::{NaiveDate, NaiveTime};
+
+/// How the columns of the CSV file are read.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Mapping {
+    date_column: String,
+    /// As in `chrono`, e.g. `%d/%m/%Y`.
+    date_format: Option<String>,
+    time_column: Option<String>,
+    /// As in `chrono`, e.g. `%I:%M %p`. Without it, times like `09:30` and `9:30 pm` are read.
+    time_format: Option<String>,
+    /// Whether the times are in UTC. Otherwise, they are local times, like in most apps.
+    #[serde(default)]
+    utc: bool,
+    delimiter: Option<char>,
+    #[serde(default)]
+    note_columns: Vec<String>,
+    #[serde(default)]
+    columns: Vec<ColumnMapping>,
+}
+
+/// A column whose values are choices of a category.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnMapping {
+    column: String,
+    category: String,
+    /// Splits a cell into several values, e.g. a list of activities.
+    separator: Option<String>,
+    /// Creates the choices the values don't have yet, instead of skipping them.
+    #[serde(default)]
+    create_missing: bool,
+    /// The choices of the values whose label differs, ignoring the case. An empty choice skips the value.
+    #[serde(default)]
+    values: HashMap<String, String>,
+}
+
+/// The entries read from the CSV file.
+#[derive(Debug, Default)]
+struct ReadRows {
+    entries: Vec<ImportedEntry>,
+    /// The choices to create, by category and label.
+    new_choices: Vec<(String, String)>,
+    /// How many times each value without a choice was found, by column and value.
+    unmapped: BTreeMap<(String, String), usize>,
+}
+
+/// Imports the entries of the CSV file, read as described by the mapping file.
+pub fn import_csv(api: &mut Api, path: &str, mapping: &str, dry_run: bool) -> Result<(), Error> {
+    let mapping = load_mapping(Path::new(mapping))?;
+    api.unlock()?;
+    let labels = Labels::load(api)?;
+    if let Some(column) = mapping
+        .columns
+        .iter()
+        .find(|column| !labels.has_category(&column.category))
+    {
+        return Err(Error::CategoryNotFound(column.category.clone()));
+    }
+
+    let file = std::fs::File::open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            Error::InvalidInput(format!("There is nothing at {}.", path))
+        }
+        _ => Error::IO(e),
+    })?;
+    let rows = read_rows(file, &mapping, &labels)
+        .map_err(|e| Error::InvalidInput(format!("{}: {}", path, e)))?;
+
+    if !rows.unmapped.is_empty() {
+        eprintln!(
+            "Skipped these values, as their categories have no such choice. Map them to a choice in `values`, or set \
+            `create_missing` to create their choices:"
+        );
+        for ((column, value), count) in rows.unmapped.iter() {
+            eprintln!("  {}: `{}` ({} time(s))", column, value, count);
+        }
+    }
+    let summary = super::save_entries(api, rows.entries, &rows.new_choices, dry_run)?;
+    if summary.imported > 0 {
+        let verb = if dry_run { "Would create" } else { "Created" };
+        for (category, choice) in rows.new_choices.iter() {
+            println!("{} the choice `{}` of `{}`.", verb, choice, category);
+        }
+    }
+    summary.print(dry_run);
+    Ok(())
+}
+
+fn load_mapping(path: &Path) -> Result<Mapping, Error> {
+    let content = std::fs::read_to_string(path)?;
+    let mapping: Mapping = toml::from_str(&content)
+        .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+    if mapping
+        .delimiter
+        .is_some_and(|delimiter| !delimiter.is_ascii())
+    {
+        return Err(Error::InvalidConfig(format!(
+            "{}: the delimiter must be an ASCII character.",
+            path.display()
+        )));
+    }
+    Ok(mapping)
+}
+
+/// Reads the rows of the CSV file into entries. The errors are returned as text, to be prefixed with the file.
+fn read_rows(
+    reader: impl std::io::Read,
+    mapping: &Mapping,
+    labels: &Labels,
+) -> Result<ReadRows, String> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter.unwrap_or(',') as u8)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(reader);
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let index = |column: &str| {
+        headers
+            .iter()
+            .position(|header| header == column)
+            .ok_or_else(|| {
+                format!(
+                    "there is no column `{}`. The columns are: {}.",
+                    column,
+                    headers.iter().collect::<Vec<_>>().join(", ")
+                )
+            })
+    };
+    let date_index = index(&mapping.date_column)?;
+    let time_index = mapping.time_column.as_deref().map(index).transpose()?;
+    let note_indexes = mapping
+        .note_columns
+        .iter()
+        .map(|column| index(column))
+        .collect::<Result<Vec<_>, _>>()?;
+    let column_indexes = mapping
+        .columns
+        .iter()
+        .map(|column| index(&column.column))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut rows = ReadRows::default();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let line = record.position().map_or(0, |position| position.line());
+        let cell = |index: usize| record.get(index).unwrap_or("");
+
+        let date_cell = cell(date_index);
+        let date = match mapping.date_format {
+            Some(ref format) => NaiveDate::parse_from_str(date_cell, format).ok(),
+            None => super::markdown::find_date(date_cell),
+        }
+        .ok_or_else(|| format!("line {}: `{}` isn't a date.", line, date_cell))?;
+        // Without a time column, the time can be in the date column, e.g. `2024-03-01 09:30`.
+        let time_cell = time_index.map_or(date_cell, cell);
+        let time = match (mapping.time_format.as_deref(), time_index) {
+            (_, Some(_)) if time_cell.is_empty() => None,
+            (Some(format), Some(_)) => Some(
+                NaiveTime::parse_from_str(time_cell, format)
+                    .map_err(|_| format!("line {}: `{}` isn't a time.", line, time_cell))?,
+            ),
+            (None, Some(_)) => Some(
+                super::markdown::find_time(time_cell)
+                    .ok_or_else(|| format!("line {}: `{}` isn't a time.", line, time_cell))?,
+            ),
+            (_, None) => super::markdown::find_time(time_cell),
+        };
+        let timestamp = super::to_utc(
+            date.and_time(time.unwrap_or_else(super::noon)),
+            !mapping.utc,
+        );
+        let entry =
+            |category: Option<&str>, choice: Option<&str>, details: Option<String>| ImportedEntry {
+                timestamp,
+                category: category.map(str::to_owned),
+                choice: choice.map(str::to_owned),
+                details,
+                tags: None,
+                attachments: Vec::new(),
+                uuid: None,
+            };
+
+        // A value repeated in a row, e.g. in a list of activities, is only imported once.
+        let mut row_choices: Vec<(&str, String)> = Vec::new();
+        for (column, &index) in mapping.columns.iter().zip(column_indexes.iter()) {
+            let values: Vec<&str> = match column.separator {
+                Some(ref separator) => cell(index).split(separator.as_str()).collect(),
+                None => vec![cell(index)],
+            };
+            for value in values.into_iter().map(str::trim).filter(|v| !v.is_empty()) {
+                let Some(choice) = choice_of(column, value, labels, &mut rows) else {
+                    continue;
+                };
+                if !row_choices.contains(&(column.category.as_str(), choice.clone())) {
+                    rows.entries
+                        .push(entry(Some(&column.category), Some(&choice), None));
+                    row_choices.push((&column.category, choice));
+                }
+            }
+        }
+
+        let note = note_indexes
+            .iter()
+            .map(|&index| cell(index))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !note.is_empty() {
+            rows.entries.push(entry(None, None, Some(note)));
+        }
+    }
+    Ok(rows)
+}
+
+/// Returns the choice of the value in the column, noting it as a new choice or as unmapped if it doesn't exist.
+fn choice_of(
+    column: &ColumnMapping,
+    value: &str,
+    labels: &Labels,
+    rows: &mut ReadRows,
+) -> Option<String> {
+    let mapped = column
+        .values
+        .iter()
+        .find(|(from, _)| from.to_lowercase() == value.to_lowercase())
+        .map_or(value, |(_, to)| to.as_str());
+    if mapped.is_empty() {
+        return None;
+    }
+    if let Some(choice) = labels.find_choice(&column.category, mapped) {
+        return Some(choice.to_owned());
+    }
+    if !column.create_missing {
+        *rows
+            .unmapped
+            .entry((column.column.clone(), value.to_owned()))
+            .or_default() += 1;
+        return None;
+    }
+    let new_choice = rows.new_choices.iter().find(|(category, choice)| {
+        *category == column.category && choice.to_lowercase() == mapped.to_lowercase()
+    });
+    match new_choice {
+        Some((_, choice)) => Some(choice.clone()),
+        None => {
+            rows.new_choices
+                .push((column.category.clone(), mapped.to_owned()));
+            Some(mapped.to_owned())
+        }
+    }
+}
+
+#[test]
+fn test_read_rows_maps_values_to_choices() {
+    let mapping: Mapping = toml::from_str(
+        r#"
+        date_column = "full_date"
+        time_column = "time"
+        utc = true
+        note_columns = ["note"]
+
+        [[columns]]
+        column = "mood"
+        category = "mood"
+        values = { rad = "great", awful = "" }
+
+        [[columns]]
+        column = "activities"
+        category = "activities"
+        separator = " | "
+        create_missing = true
+        "#,
+    )
+    .unwrap();
+    let labels = Labels {
+        categories: HashMap::from([("mood".to_owned(), 1), ("activities".to_owned(), 2)]),
+        choices: HashMap::from([
+            (("mood".to_owned(), "great".to_owned()), 1),
+            (("mood".to_owned(), "Okay".to_owned()), 2),
+            (("activities".to_owned(), "reading".to_owned()), 3),
+        ]),
+    };
+    let csv = "full_date,time,mood,activities,note\n\
+        2024-03-01,9:30 pm,rad,reading | Hiking | hiking,Long walk.\n\
+        2024-03-02,,okay,,\n\
+        2024-03-03,08:00,meh,,\n\
+        2024-03-04,08:00,awful,,\n";
+    let rows = read_rows(csv.as_bytes(), &mapping, &labels).unwrap();
+
+    let entries: Vec<String> = rows.entries.iter().map(super::preview).collect();
+    assert_eq!(
+        entries,
+        [
+            "2024-03-01 21:30:00  mood -> great",
+            "2024-03-01 21:30:00  activities -> reading",
+            "2024-03-01 21:30:00  activities -> Hiking",
+            "2024-03-01 21:30:00  Long walk.",
+            "2024-03-02 12:00:00  mood -> Okay",
+        ]
+    );
+    assert_eq!(
+        rows.new_choices,
+        [("activities".to_owned(), "Hiking".to_owned())]
+    );
+    assert_eq!(
+        rows.unmapped,
+        BTreeMap::from([(("mood".to_owned(), "meh".to_owned()), 1)])
+    );
+}
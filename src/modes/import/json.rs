@@ -0,0 +1,192 @@
+/// This module contains the logic for `import json`, which reads back the documents written by `export`.
+///
+/// The setup of the document is merged into the journal by label, so the document can be imported into an empty
+/// journal, to restore it, or into another one. The entries are recognized by their UUID, and those of the older versions
+/// by their content. The attachments are looked up by their hash, in the attachments directory of the journal and in an
+/// `attachments` directory next to the document.
+use std::path::{Path, PathBuf};
+
+use super::{ImportSummary, ImportedEntry, ImportedFile};
+use crate::backend::api::export::{
+    Document, ExportedAttachment, ExportedEntry, SetupSummary, FORMAT, VERSION,
+};
+use crate::backend::api::Api;
+use crate::backend::attachments;
+use crate::errors::Error;
+
+/// Imports the setup and the entries of the document, all at once.
+pub fn import_json(api: &mut Api, path: &str, dry_run: bool) -> Result<(), Error> {
+    let content = std::fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            Error::InvalidInput(format!("There is nothing at {}.", path))
+        }
+        _ => Error::IO(e),
+    })?;
+    let document =
+        parse_document(&content).map_err(|e| Error::InvalidInput(format!("{}: {}", path, e)))?;
+    api.unlock()?;
+
+    let attachment_dirs = [
+        attachments::attachments_dir_of(Path::new(api.database_path())),
+        Path::new(path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join("attachments"),
+    ];
+    // The ids of the older versions aren't UUIDs.
+    let keep_uuids = document.version >= 2;
+    let entries: Vec<ImportedEntry> = document
+        .entries
+        .iter()
+        .map(|entry| to_entry(entry, &attachment_dirs, keep_uuids))
+        .collect();
+    let import = |api: &mut Api| -> Result<(SetupSummary, ImportSummary), Error> {
+        let setup = api.merge_setup(&document.setup)?;
+        let summary = super::save_entries(api, entries, &[], dry_run)?;
+        Ok((setup, summary))
+    };
+    // The entries can only be checked against the new categories and choices once they are added, so a dry run adds
+    // them too, and then undoes everything.
+    let (setup, summary) = match dry_run {
+        true => api.rolled_back(import)?,
+        false => api.transaction(import)?,
+    };
+
+    print_setup_summary(&setup, if dry_run { "Would add" } else { "Added" });
+    summary.print(dry_run);
+    Ok(())
+}
+
+/// Prints what was added to the setup of the journal, if anything.
+pub(crate) fn print_setup_summary(setup: &SetupSummary, verb: &str) {
+    let added = [
+        (setup.categories, "category(ies)"),
+        (setup.choices, "choice(s)"),
+        (setup.quizzes, "quiz(zes)"),
+        (setup.links, "category(ies) to quizzes"),
+        (setup.people, "person(s)"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, what)| format!("{} {}", count, what))
+    .collect::<Vec<_>>();
+    if !added.is_empty() {
+        println!("{} {}.", verb, added.join(", "));
+    }
+}
+
+/// Reads the document, checking first that it is an export of jurnalo that this version can read.
+fn parse_document(content: &str) -> Result<Document, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    if value.get("format").and_then(|format| format.as_str()) != Some(FORMAT) {
+        return Err("this isn't a JSON export of jurnalo.".to_owned());
+    }
+    match value.get("version").and_then(|version| version.as_u64()) {
+        Some(version) if version <= u64::from(VERSION) => {}
+        Some(version) => {
+            return Err(format!(
+                "this export was made by a newer version of jurnalo (version {}). Please update jurnalo to import it.",
+                version
+            ))
+        }
+        None => return Err("the export has no version.".to_owned()),
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Reads the exported entry as an entry to import, keeping its UUID if `keep_uuid` is set. Each attachment is looked up
+/// in the attachment directories, in order.
+pub(crate) fn to_entry(
+    entry: &ExportedEntry,
+    attachment_dirs: &[PathBuf],
+    keep_uuid: bool,
+) -> ImportedEntry {
+    let attachments = entry
+        .attachments
+        .iter()
+        .filter(|attachment| {
+            let is_valid = is_valid_attachment(attachment);
+            if !is_valid {
+                eprintln!(
+                    "Skipped the attachment {} of the entry {}, as its hash or extension is invalid.",
+                    attachment.file_name, entry.id
+                );
+            }
+            is_valid
+        })
+        .map(|attachment| {
+            let paths: Vec<PathBuf> = attachment_dirs
+                .iter()
+                .map(|directory| {
+                    attachments::stored_path_in(
+                        directory,
+                        &attachment.hash,
+                        attachment.extension.as_deref(),
+                    )
+                })
+                .collect();
+            ImportedFile {
+                // If it is nowhere, the last path is reported as missing.
+                path: paths
+                    .iter()
+                    .find(|path| path.exists())
+                    .or(paths.last())
+                    .cloned()
+                    .unwrap_or_default(),
+                file_name: attachment.file_name.clone(),
+            }
+        })
+        .collect();
+    ImportedEntry {
+        timestamp: entry.timestamp,
+        category: entry.category.clone(),
+        choice: entry.choice.clone(),
+        details: entry.details.clone(),
+        tags: Some(entry.tags.clone()),
+        attachments,
+        uuid: keep_uuid.then(|| entry.id.clone()),
+    }
+}
+
+/// Whether the hash and the extension name a stored file: as they are joined into a path, they can't point anywhere
+/// else.
+fn is_valid_attachment(attachment: &ExportedAttachment) -> bool {
+    attachment.hash.len() == 64
+        && attachment.hash.chars().all(|c| c.is_ascii_hexdigit())
+        && attachment
+            .extension
+            .as_ref()
+            .is_none_or(|extension| extension.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+#[test]
+fn test_parse_document_checks_the_format_and_version() {
+    let document = r#"{"format": "jurnalo", "version": 2, "exported_at": "2024-03-01T09:30:00",
+        "categories": [{"label": "mood", "prompt": "How are you?", "category_type": 1, "extra_info": null,
+            "show_in_streaks": true, "reminder_timer_in_days": null, "choices": [{"label": "great", "shortcut": "1",
+            "show_in_streaks": true, "reminder_timer_in_days": null, "goal_times": null, "goal_period": null,
+            "goal_period_length": 1}]}],
+        "quizzes": [{"label": "daily", "command": "daily", "schedule": null, "categories": ["mood"]}],
+        "entries": [{"id": "6f1c2a9e-5b7d-4e8a-9c3f-1d2e3f4a5b6c", "timestamp": "2024-03-01T09:30:00.250", "category": "mood",
+            "choice": "great", "details": "slept well #rested"}]}"#;
+    let parsed = parse_document(document).unwrap();
+    assert_eq!(parsed.setup.categories[0].choices[0].label, "great");
+    assert!(!parsed.setup.categories[0].disabled);
+    assert!(parsed.setup.people.is_empty());
+    assert_eq!(
+        parsed.entries[0]
+            .timestamp
+            .format("%H:%M:%S%.3f")
+            .to_string(),
+        "09:30:00.250"
+    );
+
+    let newer = document.replace(r#""version": 2"#, r#""version": 3"#);
+    assert!(parse_document(&newer)
+        .unwrap_err()
+        .contains("newer version"));
+    let other = document.replace(r#""format": "jurnalo""#, r#""format": "other""#);
+    assert!(parse_document(&other)
+        .unwrap_err()
+        .contains("isn't a JSON export"));
+}
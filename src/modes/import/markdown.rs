@@ -0,0 +1,317 @@
+/// This module contains the logic for `import markdown`, which reads the entries of Markdown or text journals.
+///
+/// The headings give the dates and times of the text below them: a heading with a date (`2024-03-01` or `2024/03/01`)
+/// starts a new day, and a heading with a time (`09:30` or `09:30:00`) sets the time of the entries below it, e.g. the
+/// `## 2024-03-01` and `### 09:30:00` of `jurnalo print`. A file named after a date (`2024-03-01.md`) starts with that
+/// day. The entries without a time are set at noon.
+///
+/// Below a heading, each paragraph is an entry, and so is each item of a list and each line ending with a line break
+/// (two spaces), like in `print`. The lines `<category> -> <choice> : <details>` and `<category> : <details>` of
+/// existing categories and choices become their entries again, and the links to existing files are attached.
+use chrono::{NaiveDate, NaiveTime};
+use std::path::Path;
+
+use super::{ImportedEntry, ImportedFile, Labels};
+use crate::backend::api::Api;
+use crate::errors::Error;
+
+const EXTENSIONS: [&str; 3] = ["md", "markdown", "txt"];
+const LIST_MARKERS: [&str; 3] = ["- ", "* ", "+ "];
+
+/// Imports the entries of the Markdown file, or of the Markdown and text files in the directory.
+/// With `local_time`, the times are read as local times, like in a journal written by hand. Otherwise, they are read
+/// as UTC, like the output of `print`.
+pub fn import_markdown(
+    api: &mut Api,
+    path: &str,
+    dry_run: bool,
+    local_time: bool,
+) -> Result<(), Error> {
+    api.unlock()?;
+    let labels = Labels::load(api)?;
+    let mut entries = Vec::new();
+    for file in super::files_to_import(Path::new(path), &EXTENSIONS)? {
+        let text = std::fs::read_to_string(&file)?;
+        let directory = file.parent().unwrap_or(Path::new(""));
+        let parsed = parse_markdown(&text, date_from_file_name(&file), directory);
+        if parsed.undated_lines > 0 {
+            eprintln!(
+                "Skipped {} line(s) of {} before the first date.",
+                parsed.undated_lines,
+                file.display()
+            );
+        }
+        entries.extend(
+            parsed
+                .blocks
+                .into_iter()
+                .map(|block| to_entry(block, &labels, local_time)),
+        );
+    }
+    super::save_entries(api, entries, &[], dry_run)?.print(dry_run);
+    Ok(())
+}
+
+/// A paragraph, list item or line of the journal, with the date and time of the headings above it.
+#[derive(Debug, PartialEq, Eq)]
+struct Block {
+    date: NaiveDate,
+    time: Option<NaiveTime>,
+    text: String,
+    /// The files linked in the block.
+    links: Vec<ImportedFile>,
+}
+
+#[derive(Debug, Default)]
+struct ParsedMarkdown {
+    blocks: Vec<Block>,
+    /// The lines with text before the first date, which can't be imported.
+    undated_lines: usize,
+}
+
+/// The links are relative to `directory`, the one of the file.
+fn parse_markdown(text: &str, file_date: Option<NaiveDate>, directory: &Path) -> ParsedMarkdown {
+    let mut parsed = ParsedMarkdown::default();
+    let mut date = file_date;
+    let mut time = None;
+    let mut lines: Vec<String> = Vec::new();
+    let mut links: Vec<ImportedFile> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            let heading = heading.trim_start_matches('#').trim();
+            parsed.end_block(date, time, &mut lines, &mut links);
+            let (heading_date, heading_time) = (find_date(heading), find_time(heading));
+            if heading_date.is_some() {
+                date = heading_date;
+                time = None;
+            }
+            if heading_time.is_some() {
+                time = heading_time;
+            }
+            // The other headings are kept, as the first line of the text below them.
+            if heading_date.is_none() && heading_time.is_none() && !heading.is_empty() {
+                lines.push(heading.to_owned());
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            parsed.end_block(date, time, &mut lines, &mut links);
+            continue;
+        }
+        if let Some(link) = file_link(trimmed, directory) {
+            // The files listed after an entry, like in `print`, are attached to it.
+            let previous = parsed
+                .blocks
+                .last_mut()
+                .filter(|block| Some(block.date) == date && block.time == time);
+            match previous {
+                Some(block) if lines.is_empty() && links.is_empty() => block.links.push(link),
+                _ => links.push(link),
+            }
+            continue;
+        }
+        if let Some(item) = LIST_MARKERS
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            parsed.end_block(date, time, &mut lines, &mut links);
+            lines.push(item.trim().to_owned());
+            continue;
+        }
+        lines.push(trimmed.to_owned());
+        if line.ends_with("  ") {
+            parsed.end_block(date, time, &mut lines, &mut links);
+        }
+    }
+    parsed.end_block(date, time, &mut lines, &mut links);
+    parsed
+}
+
+impl ParsedMarkdown {
+    /// Adds the lines read so far as a block, or counts them as undated if there was no date yet.
+    fn end_block(
+        &mut self,
+        date: Option<NaiveDate>,
+        time: Option<NaiveTime>,
+        lines: &mut Vec<String>,
+        links: &mut Vec<ImportedFile>,
+    ) {
+        if lines.is_empty() && links.is_empty() {
+            return;
+        }
+        match date {
+            Some(date) => self.blocks.push(Block {
+                date,
+                time,
+                text: lines.join("\n"),
+                links: std::mem::take(links),
+            }),
+            None => self.undated_lines += lines.len() + links.len(),
+        }
+        lines.clear();
+        links.clear();
+    }
+}
+
+/// Turns the block into an entry of its category and choice, if it starts with existing ones, or else into a note.
+fn to_entry(block: Block, labels: &Labels, local_time: bool) -> ImportedEntry {
+    let time = block.time.unwrap_or_else(super::noon);
+    let timestamp = super::to_utc(block.date.and_time(time), local_time);
+
+    let (category, choice, details) = split_labels(&block.text, labels);
+    let details = (!details.is_empty()).then(|| details.to_owned());
+    ImportedEntry {
+        timestamp,
+        category: category.map(str::to_owned),
+        choice: choice.map(str::to_owned),
+        details,
+        tags: None,
+        attachments: block.links,
+        uuid: None,
+    }
+}
+
+/// Splits `<category> -> <choice> : <details>` and `<category> : <details>`, if the category and the choice exist.
+fn split_labels<'a>(text: &'a str, labels: &Labels) -> (Option<&'a str>, Option<&'a str>, &'a str) {
+    if let Some((category, rest)) = text.split_once(" -> ") {
+        let (choice, details) = rest.split_once(" : ").unwrap_or((rest, ""));
+        if labels.has_choice(category, choice) {
+            return (Some(category), Some(choice), details);
+        }
+    }
+    if let Some((category, details)) = text.split_once(" : ") {
+        if labels.has_category(category) {
+            return (Some(category), None, details);
+        }
+    }
+    (None, None, text)
+}
+
+/// Returns the file of a line that is only a link to an existing file, e.g. `![photo.jpg](<photos/photo.jpg>)`.
+/// The links to websites and to missing files stay in the text.
+fn file_link(line: &str, directory: &Path) -> Option<ImportedFile> {
+    let rest = line.strip_prefix('!').unwrap_or(line).strip_prefix('[')?;
+    let (name, target) = rest.split_once("](")?;
+    let target = target.strip_suffix(')')?;
+    let target = target
+        .strip_prefix('<')
+        .and_then(|target| target.strip_suffix('>'))
+        .unwrap_or(target);
+    let path = directory.join(target);
+    if !path.is_file() {
+        return None;
+    }
+    let file_name = match name.trim() {
+        "" => path.file_name()?.to_string_lossy().into_owned(),
+        name => name.to_owned(),
+    };
+    Some(ImportedFile { path, file_name })
+}
+
+fn date_from_file_name(path: &Path) -> Option<NaiveDate> {
+    find_date(&path.file_stem()?.to_string_lossy())
+}
+
+/// Returns the first date written as `YYYY-MM-DD` or `YYYY/MM/DD` in the text.
+pub(super) fn find_date(text: &str) -> Option<NaiveDate> {
+    text.char_indices().find_map(|(start, _)| {
+        let candidate = text.get(start..start + 10)?;
+        NaiveDate::parse_from_str(candidate, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(candidate, "%Y/%m/%d"))
+            .ok()
+    })
+}
+
+/// Returns the first time written as `HH:MM:SS` or `HH:MM` in the text, optionally followed by `am` or `pm`.
+pub(super) fn find_time(text: &str) -> Option<NaiveTime> {
+    let bytes = text.as_bytes();
+    let number = |start: usize, end: usize| -> Option<u32> {
+        let digits = text.get(start..end)?;
+        match digits.bytes().all(|b| b.is_ascii_digit()) {
+            true => digits.parse().ok(),
+            false => None,
+        }
+    };
+    let is_digit_at = |index: usize| bytes.get(index).is_some_and(u8::is_ascii_digit);
+
+    text.match_indices(':').find_map(|(colon, _)| {
+        let hour_start = match colon {
+            2.. if is_digit_at(colon - 2) && is_digit_at(colon - 1) => colon - 2,
+            1.. if is_digit_at(colon - 1) => colon - 1,
+            _ => return None,
+        };
+        // Not in the middle of a number, e.g. a ratio like `123:45`.
+        if hour_start > 0 && is_digit_at(hour_start - 1) {
+            return None;
+        }
+        let mut hour = number(hour_start, colon)?;
+        let minute = number(colon + 1, colon + 3)?;
+        let (second, end) = match bytes.get(colon + 3) {
+            Some(b':') => (number(colon + 4, colon + 6)?, colon + 6),
+            _ => (0, colon + 3),
+        };
+        if is_digit_at(end) {
+            return None;
+        }
+        let suffix = text[end..].trim_start().to_lowercase();
+        if suffix.starts_with("pm") && hour < 12 {
+            hour += 12;
+        } else if suffix.starts_with("am") && hour == 12 {
+            hour = 0;
+        }
+        NaiveTime::from_hms_opt(hour, minute, second)
+    })
+}
+
+#[test]
+fn test_parse_markdown_reads_the_output_of_print() {
+    let printed = "## 2024-03-01\n### 09:30:00\nmood -> great : slept well  \ncoffee with @sam  \n\
+        ![photo.jpg](</nonexistent/photo.jpg>)  \n\n\n## 2024-03-02\n### 21:00:05\nA long day.\nStill writing.\n\n\
+        - first item\n- second item";
+    let parsed = parse_markdown(printed, None, Path::new("/nonexistent"));
+    let date = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+    let texts: Vec<(NaiveDate, Option<NaiveTime>, &str)> = parsed
+        .blocks
+        .iter()
+        .map(|block| (block.date, block.time, block.text.as_str()))
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            (
+                date(1),
+                NaiveTime::from_hms_opt(9, 30, 0),
+                "mood -> great : slept well"
+            ),
+            (
+                date(1),
+                NaiveTime::from_hms_opt(9, 30, 0),
+                "coffee with @sam"
+            ),
+            // The link is kept as text, as the file doesn't exist.
+            (
+                date(1),
+                NaiveTime::from_hms_opt(9, 30, 0),
+                "![photo.jpg](</nonexistent/photo.jpg>)"
+            ),
+            (
+                date(2),
+                NaiveTime::from_hms_opt(21, 0, 5),
+                "A long day.\nStill writing."
+            ),
+            (date(2), NaiveTime::from_hms_opt(21, 0, 5), "first item"),
+            (date(2), NaiveTime::from_hms_opt(21, 0, 5), "second item"),
+        ]
+    );
+
+    let parsed = parse_markdown(
+        "# My journal\nIntro.\n\n# Monday 2024/03/04, 7:05 pm\nWoke up late.",
+        None,
+        Path::new("/nonexistent"),
+    );
+    assert_eq!(parsed.undated_lines, 2);
+    assert_eq!(parsed.blocks.len(), 1);
+    assert_eq!(parsed.blocks[0].time, NaiveTime::from_hms_opt(19, 5, 0));
+}
@@ -0,0 +1,330 @@
+/// This module contains the logic for the `import` modes, which add the entries of other journals to this one.
+/// Each format reads its entries into `ImportedEntry`s, which are then saved (or only previewed) the same way: the entries
+/// already in the journal, with the same UUID or else with the same time, labels and details, are skipped, so importing
+/// twice is harmless.
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::backend::api::{self, Api};
+use crate::backend::attachments;
+use crate::errors::Error;
+
+pub mod csv;
+pub mod json;
+pub mod markdown;
+
+/// An entry read from another journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedEntry {
+    /// In UTC, like the timestamps in the database.
+    pub timestamp: NaiveDateTime,
+    pub category: Option<String>,
+    pub choice: Option<String>,
+    pub details: Option<String>,
+    /// The tags of the entry, if they are known, e.g. in an export of jurnalo. Otherwise, the #hashtags of the details
+    /// become its tags.
+    pub tags: Option<Vec<String>>,
+    pub attachments: Vec<ImportedFile>,
+    /// The UUID of the entry, if it comes from another journal of jurnalo, so it is recognized by it when it is
+    /// imported or synced again, even if it was edited since.
+    pub uuid: Option<String>,
+}
+
+/// A file to attach to an imported entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedFile {
+    pub path: PathBuf,
+    /// The name shown in the exports, which can differ from the name of the file, e.g. for a stored attachment.
+    pub file_name: String,
+}
+
+/// The categories and choices of the journal, by label.
+pub struct Labels {
+    categories: HashMap<String, i32>,
+    choices: HashMap<(String, String), i32>,
+}
+
+impl Labels {
+    pub fn load(api: &mut Api) -> Result<Self, Error> {
+        let categories = api
+            .get_all_categories()?
+            .into_iter()
+            .map(|category| (category.label, category.id))
+            .collect();
+        let choices = api
+            .get_all_choices()?
+            .into_iter()
+            .map(|choice| ((choice.category_label, choice.label), choice.id))
+            .collect();
+        Ok(Self {
+            categories,
+            choices,
+        })
+    }
+
+    pub fn has_category(&self, category: &str) -> bool {
+        self.categories.contains_key(category)
+    }
+
+    pub fn has_choice(&self, category: &str, choice: &str) -> bool {
+        self.choices
+            .contains_key(&(category.to_owned(), choice.to_owned()))
+    }
+
+    /// Returns the label of the choice of the category, ignoring the case, e.g. `Great` for `great`.
+    pub fn find_choice(&self, category: &str, choice: &str) -> Option<&str> {
+        self.choices
+            .keys()
+            .filter(|(choice_category, _)| choice_category == category)
+            .map(|(_, label)| label.as_str())
+            .find(|label| label.to_lowercase() == choice.to_lowercase())
+    }
+
+    /// Returns the ids of the category and the choice of the entry.
+    fn ids(&self, entry: &ImportedEntry) -> Result<(Option<i32>, Option<i32>), Error> {
+        let Some(ref category) = entry.category else {
+            return Ok((None, None));
+        };
+        let category_id = *self
+            .categories
+            .get(category)
+            .ok_or_else(|| Error::CategoryNotFound(category.clone()))?;
+        let choice_id = match entry.choice {
+            Some(ref choice) => Some(
+                *self
+                    .choices
+                    .get(&(category.clone(), choice.clone()))
+                    .ok_or_else(|| Error::ChoiceNotFound {
+                        category: category.clone(),
+                        choice: choice.clone(),
+                    })?,
+            ),
+            None => None,
+        };
+        Ok((Some(category_id), choice_id))
+    }
+}
+
+/// How many entries were imported, and how many were skipped because they were already in the journal.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+}
+
+impl ImportSummary {
+    pub fn print(&self, dry_run: bool) {
+        let verb = if dry_run { "Would import" } else { "Imported" };
+        println!("{} {} entries.", verb, self.imported);
+        if self.duplicates > 0 {
+            println!(
+                "Skipped {} entries already in the journal.",
+                self.duplicates
+            );
+        }
+        if dry_run {
+            println!("Nothing was saved: run again without `--dry-run` to import them.");
+        }
+    }
+}
+
+/// Saves the entries that aren't in the journal yet, all at once. With `dry_run`, they are only printed.
+/// The categories and choices of the entries must exist, except the `new_choices` (category and label), which are
+/// created first, in the same transaction.
+pub fn save_entries(
+    api: &mut Api,
+    entries: Vec<ImportedEntry>,
+    new_choices: &[(String, String)],
+    dry_run: bool,
+) -> Result<ImportSummary, Error> {
+    let mut summary = ImportSummary::default();
+    let (Some(first), Some(last)) = (
+        entries.iter().map(|e| e.timestamp).min(),
+        entries.iter().map(|e| e.timestamp).max(),
+    ) else {
+        return Ok(summary);
+    };
+
+    let labels = Labels::load(api)?;
+    if let Some((category, _)) = new_choices
+        .iter()
+        .find(|(category, _)| !labels.has_category(category))
+    {
+        return Err(Error::CategoryNotFound(category.clone()));
+    }
+    for entry in entries.iter() {
+        let is_new = match (&entry.category, &entry.choice) {
+            (Some(category), Some(choice)) => {
+                new_choices.contains(&(category.clone(), choice.clone()))
+            }
+            _ => false,
+        };
+        if !is_new {
+            labels.ids(entry)?;
+        }
+    }
+
+    // The entries are compared by label, as the new choices have no id yet, and to the second, the precision of
+    // `print`.
+    let mut seen: HashSet<(NaiveDateTime, Option<String>, Option<String>, String)> = api
+        .get_entries_between_dates(first, last + chrono::Duration::seconds(1))?
+        .into_iter()
+        .map(|api::EntryWithLabelsTuple(entry, category, choice)| {
+            (
+                to_the_second(entry.timestamp),
+                category,
+                choice,
+                entry.details.unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    let uuids: Vec<String> = entries.iter().filter_map(|e| e.uuid.clone()).collect();
+    let mut seen_uuids = api.existing_entry_uuids(&uuids)?;
+
+    let mut new_entries = Vec::new();
+    for entry in entries {
+        let is_new = match entry.uuid {
+            // The same entry, even if it was edited in one of the journals since.
+            Some(ref uuid) => seen_uuids.insert(uuid.clone()),
+            None => seen.insert((
+                to_the_second(entry.timestamp),
+                entry.category.clone(),
+                entry.choice.clone(),
+                entry.details.clone().unwrap_or_default(),
+            )),
+        };
+        if !is_new {
+            summary.duplicates += 1;
+            continue;
+        }
+        if dry_run {
+            println!("{}", preview(&entry));
+        }
+        new_entries.push(entry);
+    }
+    summary.imported = new_entries.len();
+    if dry_run || new_entries.is_empty() {
+        return Ok(summary);
+    }
+
+    let attachments_dir = attachments::attachments_dir_of(Path::new(api.database_path()));
+    api.transaction(|api| -> Result<(), Error> {
+        for (category, choice) in new_choices {
+            let shortcut = api::next_free_shortcut(&api.get_choices_in_category(category)?);
+            api.post_choice(choice, &shortcut, category)?;
+        }
+        let labels = Labels::load(api)?;
+        for entry in new_entries {
+            let (category_id, choice_id) = labels.ids(&entry)?;
+            let files: Vec<attachments::StoredFile> = entry
+                .attachments
+                .iter()
+                .filter_map(
+                    |file| match attachments::store_file_in(&attachments_dir, &file.path) {
+                        Ok(stored) => Some(attachments::StoredFile {
+                            file_name: file.file_name.clone(),
+                            ..stored
+                        }),
+                        Err(e) => {
+                            eprintln!("Couldn't attach {}: {}", file.path.display(), e);
+                            None
+                        }
+                    },
+                )
+                .collect();
+            let ids = api.post_multiple_entries(
+                vec![(category_id, choice_id, entry.details)],
+                Some(entry.timestamp),
+            )?;
+            api.post_attachments(ids[0], &files)?;
+            if let Some(ref uuid) = entry.uuid {
+                api.set_entry_uuid(ids[0], uuid)?;
+            }
+            if let Some(tags) = entry.tags {
+                // The #hashtags removed by hand stay removed.
+                let removed: Vec<String> = api
+                    .get_tags_of_entry(ids[0])?
+                    .into_iter()
+                    .filter(|tag| !tags.contains(tag))
+                    .collect();
+                api.remove_tags_from_entry(ids[0], &removed)?;
+                api.add_tags_to_entry(ids[0], &tags)?;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(summary)
+}
+
+/// The time of the entries whose time isn't known.
+fn noon() -> NaiveTime {
+    NaiveTime::from_hms_opt(12, 0, 0).expect("Valid time.")
+}
+
+/// Converts the timestamp to UTC, if it is a local time.
+fn to_utc(timestamp: NaiveDateTime, local_time: bool) -> NaiveDateTime {
+    match local_time {
+        true => Local
+            .from_local_datetime(&timestamp)
+            .earliest()
+            .map(|local| local.naive_utc())
+            .unwrap_or(timestamp),
+        false => timestamp,
+    }
+}
+
+fn to_the_second(timestamp: NaiveDateTime) -> NaiveDateTime {
+    timestamp.with_nanosecond(0).unwrap_or(timestamp)
+}
+
+/// The entry on one line, e.g. `2024-03-01 09:30:00  mood -> great : tired (+1 file)`.
+fn preview(entry: &ImportedEntry) -> String {
+    let mut text = entry.timestamp.format("%Y-%m-%d %H:%M:%S  ").to_string();
+    if let Some(ref category) = entry.category {
+        text.push_str(category);
+        if let Some(ref choice) = entry.choice {
+            text.push_str(&format!(" -> {}", choice));
+        }
+        if entry.details.is_some() {
+            text.push_str(" : ");
+        }
+    }
+    if let Some(ref details) = entry.details {
+        text.push_str(&details.replace('\n', " / "));
+    }
+    if !entry.attachments.is_empty() {
+        text.push_str(&format!(" (+{} file(s))", entry.attachments.len()));
+    }
+    text
+}
+
+/// Returns the file, or the files with one of the extensions in the directory and its subdirectories, sorted.
+fn files_to_import(path: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>, Error> {
+    if !path.exists() {
+        return Err(Error::InvalidInput(format!(
+            "There is nothing at {}.",
+            path.display()
+        )));
+    }
+    if path.is_file() {
+        return Ok(vec![path.to_owned()]);
+    }
+    let mut files = Vec::new();
+    let mut directories = vec![path.to_owned()];
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if path.extension().is_some_and(|extension| {
+                extensions.contains(&extension.to_string_lossy().to_lowercase().as_str())
+            }) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,223 @@
+/// This module contains the logic for the `insights` mode, which relates the choices of an outcome category (e.g. mood)
+/// to the habits logged on the same day (or some days before).
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::api::{self, Api};
+use crate::backend::config;
+use crate::errors::Error;
+
+/// How the outcome was distributed on the days with and without a habit.
+#[derive(Debug)]
+struct HabitInsight {
+    habit_label: String,
+    days_with: usize,
+    days_without: usize,
+    /// For each outcome choice, the fraction of the days with and without the habit on which it was the outcome.
+    fractions: Vec<(String, f64, f64)>,
+}
+
+impl HabitInsight {
+    /// The largest difference between the days with and without the habit, over all the outcome choices.
+    fn largest_difference(&self) -> f64 {
+        self.fractions
+            .iter()
+            .map(|(_, with, without)| (with - without).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+type DayAndOutcome<'a> = (&'a NaiveDate, &'a String);
+
+/// Compares the outcome of the days on which each habit was done `lag` days before with the outcome of the other days.
+/// Returns the insights sorted by the largest difference first, and the habits that didn't have at least `min_samples`
+/// days both with and without them.
+fn compute_insights(
+    outcome_by_day: &BTreeMap<NaiveDate, String>,
+    outcome_labels: &[String],
+    habit_days: &BTreeMap<String, BTreeSet<NaiveDate>>,
+    lag: u32,
+    min_samples: usize,
+) -> (Vec<HabitInsight>, Vec<String>) {
+    let mut insights = Vec::new();
+    let mut not_enough_data = Vec::new();
+
+    for (habit_label, days) in habit_days.iter() {
+        let (with, without): (Vec<DayAndOutcome>, Vec<DayAndOutcome>) = outcome_by_day
+            .iter()
+            .partition(|(day, _)| days.contains(&(**day - Duration::days(lag.into()))));
+
+        if with.len() < min_samples || without.len() < min_samples {
+            not_enough_data.push(habit_label.clone());
+            continue;
+        }
+
+        let fraction = |days: &[DayAndOutcome], label: &String| {
+            days.iter().filter(|(_, outcome)| *outcome == label).count() as f64 / days.len() as f64
+        };
+
+        insights.push(HabitInsight {
+            habit_label: habit_label.clone(),
+            days_with: with.len(),
+            days_without: without.len(),
+            fractions: outcome_labels
+                .iter()
+                .map(|label| {
+                    (
+                        label.clone(),
+                        fraction(&with, label),
+                        fraction(&without, label),
+                    )
+                })
+                .collect(),
+        });
+    }
+
+    insights.sort_by(|a, b| b.largest_difference().total_cmp(&a.largest_difference()));
+    (insights, not_enough_data)
+}
+
+/// Prints how the outcome category's choices differ between the days on which each habit was logged `lag` days before
+/// and the days it wasn't. Only the choices of `habit_category` are considered habits, if given, otherwise the choices
+/// of every other category. Habits with less than `min_samples` days with or without them are left out.
+pub fn print_insights(
+    api: &mut Api,
+    outcome_category: &str,
+    habit_category: &Option<String>,
+    lag: u32,
+    min_samples: usize,
+    period: &Option<String>,
+) -> Result<(), Error> {
+    if outcome_category.is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide an outcome category.".to_owned(),
+        ));
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let start = match period {
+        Some(p) => {
+            let days = super::stats::parse_period(p).ok_or_else(|| {
+                Error::InvalidInput(
+                    "The period must be `week`, `month`, `year` or a number of days.".to_owned(),
+                )
+            })?;
+            // The whole days, today included, so the first day isn't counted from the middle.
+            config::start_of_day(config::today() - Duration::days(i64::from(days) - 1))
+        }
+        None => NaiveDateTime::UNIX_EPOCH,
+    };
+
+    api.get_category_by_label(outcome_category)?;
+    if let Some(habit_category) = habit_category {
+        api.get_category_by_label(habit_category)?;
+    }
+    let entries = api.get_entries_between_dates(start, now)?;
+    let outcome_labels: Vec<String> = api
+        .get_choices_in_category(outcome_category)?
+        .into_iter()
+        .map(|choice| choice.label)
+        .collect();
+    if outcome_labels.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "The category {} has no choices.",
+            outcome_category
+        )));
+    }
+
+    // The entries are sorted by timestamp, so the latest outcome of each day wins.
+    let mut outcome_by_day: BTreeMap<NaiveDate, String> = BTreeMap::new();
+    let mut habit_days: BTreeMap<String, BTreeSet<NaiveDate>> = BTreeMap::new();
+    for api::EntryWithLabelsTuple(entry, category_label, choice_label) in entries {
+        let (Some(category_label), Some(choice_label)) = (category_label, choice_label) else {
+            continue;
+        };
+        if category_label == outcome_category {
+            outcome_by_day.insert(config::local_date(entry.timestamp), choice_label);
+        } else if habit_category.is_none() || habit_category.as_ref() == Some(&category_label) {
+            habit_days
+                .entry(format!("{} -> {}", category_label, choice_label))
+                .or_default()
+                .insert(config::local_date(entry.timestamp));
+        }
+    }
+
+    let (insights, not_enough_data) = compute_insights(
+        &outcome_by_day,
+        &outcome_labels,
+        &habit_days,
+        lag,
+        min_samples,
+    );
+
+    println!(
+        "Insights for {} over {} days with entries (lag: {} day(s), at least {} day(s) with and without each habit):",
+        outcome_category,
+        outcome_by_day.len(),
+        lag,
+        min_samples
+    );
+
+    let padding_length = outcome_labels.iter().map(|l| l.len()).max().unwrap_or(0) + 2;
+    for insight in insights.iter() {
+        println!();
+        println!(
+            "{} (with: {} days, without: {} days)",
+            insight.habit_label, insight.days_with, insight.days_without
+        );
+        println!(
+            "  {:<padding_length$}{:>6} {:>8} {:>6}",
+            "", "with", "without", "diff"
+        );
+        for (label, with, without) in insight.fractions.iter() {
+            println!(
+                "  {:<padding_length$}{:>5.0}% {:>7.0}% {:>+6.0}",
+                label,
+                with * 100.0,
+                without * 100.0,
+                (with - without) * 100.0
+            );
+        }
+    }
+
+    if insights.is_empty() {
+        println!();
+        println!("Not enough data for any habit yet.");
+    }
+    if !not_enough_data.is_empty() {
+        println!();
+        println!("Not enough data for: {}", not_enough_data.join(", "));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compute_insights() {
+    let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+    let labels = vec!["good".to_owned(), "bad".to_owned()];
+
+    let outcome_by_day: BTreeMap<NaiveDate, String> = [
+        (day(2), "good"),
+        (day(3), "bad"),
+        (day(4), "good"),
+        (day(5), "bad"),
+    ]
+    .into_iter()
+    .map(|(d, l)| (d, l.to_owned()))
+    .collect();
+
+    // Gym was done the day before every good day.
+    let habit_days: BTreeMap<String, BTreeSet<NaiveDate>> = [
+        ("gym".to_owned(), [day(1), day(3)].into_iter().collect()),
+        ("rare".to_owned(), [day(2)].into_iter().collect()),
+    ]
+    .into_iter()
+    .collect();
+
+    let (insights, not_enough_data) = compute_insights(&outcome_by_day, &labels, &habit_days, 1, 2);
+    assert_eq!(not_enough_data, vec!["rare".to_owned()]);
+    assert_eq!(insights.len(), 1);
+    assert_eq!(insights[0].days_with, 2);
+    assert_eq!(insights[0].fractions[0], ("good".to_owned(), 1.0, 0.0));
+    assert_eq!(insights[0].largest_difference(), 1.0);
+}
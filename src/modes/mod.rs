@@ -1,5 +1,23 @@
 /// This module contains all the modes of the application.
 pub mod alter;
+pub mod backup;
+pub mod calendar;
+pub mod chart;
+pub mod config;
+pub mod due;
+pub mod encryption;
+pub mod export;
+pub mod goals;
+pub mod import;
+pub mod insights;
+pub mod people;
 pub mod print;
-// pub mod quick_note;
+pub mod profile;
+pub mod quick_note;
+pub mod remind;
 pub mod run_quiz;
+pub mod search;
+pub mod stats;
+pub mod status;
+pub mod sync;
+pub mod tags;
@@ -0,0 +1,101 @@
+/// This module contains the logic for the `people` mode, which lists the people (and places) mentioned in the entries.
+use crate::backend::api::{self, Api};
+use crate::backend::config::{self, local_date};
+use crate::errors::Error;
+
+/// Prints each person (or place, with `places`) with how often and when they were last mentioned, and their reminder.
+/// With `rescan`, first extracts the mentions from the details of every entry again.
+pub fn print_people(api: &mut Api, places: bool, rescan: bool) -> Result<(), Error> {
+    if rescan {
+        let added = api.rescan_mentions()?;
+        println!(
+            "Added {} mention(s) from the details of the entries.",
+            added
+        );
+    }
+
+    let today = config::today();
+    let describe_last = |last: Option<chrono::NaiveDateTime>| match last.map(local_date) {
+        Some(date) => match (today - date).num_days() {
+            0 => format!("{} (today)", date),
+            1 => format!("{} (yesterday)", date),
+            n => format!("{} ({} days ago)", date, n),
+        },
+        None => "never".to_owned(),
+    };
+
+    if places {
+        let places = api.get_places_with_mentions()?;
+        if places.is_empty() {
+            println!("No places yet. Mention them in the details of the entries with +name.");
+            return Ok(());
+        }
+        let padding_length = places.iter().map(|(l, _, _)| l.len()).max().unwrap_or(0) + 3;
+        for (label, count, last) in places {
+            println!(
+                "{:<padding_length$}{:>4}  last: {}",
+                format!("+{}", label),
+                count,
+                describe_last(last)
+            );
+        }
+        return Ok(());
+    }
+
+    let people = api.get_people_with_mentions()?;
+    if people.is_empty() {
+        println!("No people yet. Mention them in the details of the entries with @name.");
+        return Ok(());
+    }
+    let padding_length = people
+        .iter()
+        .map(|(p, _, _)| p.label.len())
+        .max()
+        .unwrap_or(0)
+        + 3;
+    for (person, count, last) in people {
+        let reminder = match person.reminder_timer_in_days {
+            Some(days) => format!("  reminder: every {} days", days),
+            None => String::new(),
+        };
+        println!(
+            "{:<padding_length$}{:>4}  last: {}{}",
+            format!("@{}", person.label),
+            count,
+            describe_last(last),
+            reminder
+        );
+    }
+    Ok(())
+}
+
+/// Sets how many days without mentioning the person before they show up in `due` and `remind`. The person must have
+/// been mentioned already.
+pub fn set_reminder_for_person(api: &mut Api, person: &str, days: u32) -> Result<(), Error> {
+    let Some(label) = api::mentions::normalize_mention(person) else {
+        return Err(Error::InvalidInput(format!(
+            "`{}` isn't a valid name. Names are made of letters, digits, `_` and `-`.",
+            person
+        )));
+    };
+    let days = i32::try_from(days)
+        .map_err(|_| Error::InvalidInput("The number of days is too large.".to_owned()))?;
+    api.set_reminder_for_person(&label, Some(days))?;
+    println!(
+        "You will be reminded of @{} after {} days without a mention.",
+        label, days
+    );
+    Ok(())
+}
+
+pub fn clear_reminder_for_person(api: &mut Api, person: &str) -> Result<(), Error> {
+    let Some(label) = api::mentions::normalize_mention(person) else {
+        return Err(Error::InvalidInput(format!(
+            "`{}` isn't a valid name.",
+            person
+        )));
+    };
+    api.set_reminder_for_person(&label, None)?;
+    println!("No more reminders for @{}.", label);
+    Ok(())
+}
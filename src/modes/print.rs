@@ -1,38 +1,134 @@
 /// This module contains the logic for the print mode.
+use base64::Engine;
 use itertools::Itertools;
+use std::collections::BTreeMap;
 
-use crate::backend::api;
+use crate::backend::api::{self, Api};
+use crate::backend::{attachments, config};
+use crate::errors::Error;
+use crate::models::queryable_or_selectable as m_qos;
 
-/// Prints the entries from the last `days` days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Markdown,
+    Html,
+}
+
+/// Prints the entries from the last `days` days, or `print_days` from the config.
 /// If `output` is `None`, the entries are printed to stdout. Otherwise, they are written to the file at `output`.
-pub fn print(days: u32, output: &Option<String>) {
+/// Only the entries that pass the filter are printed.
+/// The format is `markdown` (the default) or `html`, which embeds the attached images. It defaults to `html` if the
+/// output file ends in `.html`, and then to `print_format` from the config.
+pub fn print(
+    api: &mut Api,
+    days: Option<u32>,
+    output: &Option<String>,
+    format: &Option<String>,
+    filter: &api::EntryFilter,
+) -> Result<(), Error> {
+    let config = config::get();
+    let days = days.unwrap_or(config.print_days.value);
+    let output_is_html = output
+        .as_deref()
+        .is_some_and(|path| path.to_lowercase().ends_with(".html"));
+    let format = match format
+        .clone()
+        .or_else(|| {
+            (!output_is_html)
+                .then(|| config.print_format.value.clone())
+                .flatten()
+        })
+        .map(|f| f.trim().to_lowercase())
+    {
+        Some(f) if f == "markdown" || f == "md" => Format::Markdown,
+        Some(f) if f == "html" => Format::Html,
+        Some(_) => {
+            return Err(Error::InvalidInput(
+                "The format must be `markdown` or `html`.".to_owned(),
+            ))
+        }
+        None if output_is_html => Format::Html,
+        None => Format::Markdown,
+    };
+
     let starting_date = chrono::Utc::now().naive_utc() - chrono::Duration::days(days as i64);
     let end_date = chrono::Utc::now().naive_utc();
 
-    let printable = printable_entries(starting_date, end_date).unwrap();
+    let printable = printable_entries(api, starting_date, end_date, filter, format)?;
     if printable.is_empty() {
-        panic!("No entries found");
+        if filter.is_empty() {
+            println!("No entries in the last {} days.", days);
+        } else {
+            println!("No entries match the filters.");
+        }
+        return Ok(());
     }
 
     match output {
         Some(path) => {
-            std::fs::write(path, printable).unwrap();
-            // TODO: Add a message saying that the entries were written to the file.
-            // TODO: Error handling here if this fails.
+            std::fs::write(path, printable)?;
+            println!("Wrote the entries to {}.", path);
+            if api.is_encrypted()? {
+                eprintln!("Note: the file isn't encrypted, unlike the journal.");
+            }
         }
         None => {
             println!("{}", printable);
         }
     }
+    Ok(())
 }
 
 /// Returns a formatted string containing all the entries between the two dates.
 fn printable_entries(
+    api: &mut Api,
     starting_date: chrono::NaiveDateTime,
     end_date: chrono::NaiveDateTime,
-) -> Result<String, crate::errors::Error> {
-    let response = api::get_entries_between_dates(starting_date, end_date)?;
+    filter: &api::EntryFilter,
+    format: Format,
+) -> Result<String, Error> {
+    let response = api.get_filtered_entries_between_dates(starting_date, end_date, filter)?;
+    if response.is_empty() {
+        return Ok(String::new());
+    }
+    let entry_ids: Vec<i32> = response
+        .iter()
+        .map(|api::EntryWithLabelsTuple(e, _, _)| e.id)
+        .collect();
+    let attachments = api.get_attachments_of_entries(&entry_ids)?;
+
+    match format {
+        Format::Markdown => Ok(markdown_entries(response, &attachments)),
+        Format::Html => Ok(html_entries(response, &attachments)),
+    }
+}
 
+/// The text of the entry, e.g. "mood -> Great! : tired".
+fn entry_text(
+    entry: &m_qos::Entry,
+    category_label: &Option<String>,
+    choice_label: &Option<String>,
+) -> String {
+    let mut text = String::new();
+    if let Some(cat) = category_label {
+        text.push_str(cat);
+        if let Some(choice) = choice_label {
+            text.push_str(format!(" -> {}", choice).as_str());
+        }
+    }
+    if let Some(ref details) = entry.details {
+        if entry.category.is_some() && !details.is_empty() {
+            text.push_str(" : ");
+        }
+        text.push_str(details);
+    }
+    text
+}
+
+fn markdown_entries(
+    response: Vec<api::EntryWithLabelsTuple>,
+    attachments: &BTreeMap<i32, Vec<m_qos::Attachment>>,
+) -> String {
     let mut answer = String::new();
 
     for (date, group) in &response
@@ -48,19 +144,26 @@ fn printable_entries(
         {
             tmp_str.push_str(format!("### {}\n", time).as_str());
             for api::EntryWithLabelsTuple(entry, category_label, choice_label) in group {
-                if let Some(cat) = category_label {
-                    tmp_str.push_str(&cat);
-                    if let Some(choice) = choice_label {
-                        tmp_str.push_str(format!(" -> {}", choice).as_str());
-                    }
+                let text = entry_text(&entry, &category_label, &choice_label);
+                if !text.is_empty() {
+                    tmp_str.push_str(&text);
+                    tmp_str.push_str("  \n");
                 }
-                if let Some(ref details) = entry.details {
-                    if entry.category.is_some() {
-                        tmp_str.push_str(" : ");
-                    }
-                    tmp_str.push_str(details);
+                for attachment in attachments.get(&entry.id).into_iter().flatten() {
+                    let extension = attachment.extension.as_deref();
+                    let path = attachments::stored_path(&attachment.hash, extension);
+                    let is_image = attachments::image_mime_type(extension).is_some();
+                    let image_marker = if is_image { "!" } else { "" };
+                    tmp_str.push_str(
+                        format!(
+                            "{}[{}](<{}>)  \n",
+                            image_marker,
+                            attachment.file_name,
+                            path.display()
+                        )
+                        .as_str(),
+                    );
                 }
-                tmp_str.push_str("  \n");
             }
         }
         answer.push_str(&tmp_str);
@@ -68,5 +171,100 @@ fn printable_entries(
         answer.push('\n');
     }
 
-    Ok(answer.trim().to_owned())
+    answer.trim().to_owned()
+}
+
+/// Renders the entries as a standalone HTML page, with the attached images embedded and links to the other files.
+fn html_entries(
+    response: Vec<api::EntryWithLabelsTuple>,
+    attachments: &BTreeMap<i32, Vec<m_qos::Attachment>>,
+) -> String {
+    let mut body = String::new();
+
+    for (date, group) in &response
+        .into_iter()
+        .group_by(|api::EntryWithLabelsTuple(e, _, _)| e.timestamp.date())
+    {
+        body.push_str(&format!("<h2>{}</h2>\n", date));
+        for (time, group) in
+            &group.group_by(|api::EntryWithLabelsTuple(e, _, _)| e.timestamp.time())
+        {
+            body.push_str(&format!("<h3>{}</h3>\n<ul>\n", time));
+            for api::EntryWithLabelsTuple(entry, category_label, choice_label) in group {
+                body.push_str(&format!(
+                    "<li>{}",
+                    escape_html(&entry_text(&entry, &category_label, &choice_label))
+                ));
+                for attachment in attachments.get(&entry.id).into_iter().flatten() {
+                    body.push_str(&html_attachment(attachment));
+                }
+                body.push_str("</li>\n");
+            }
+            body.push_str("</ul>\n");
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Jurnalo</title>\n\
+        <style>img {{ display: block; max-width: 100%; max-height: 30em; margin: 0.5em 0; }}</style>\n\
+        </head>\n<body>\n{}</body>\n</html>\n",
+        body
+    )
+}
+
+/// Embeds the image, or links to the file if it isn't an image or can't be read.
+fn html_attachment(attachment: &m_qos::Attachment) -> String {
+    let path = attachments::stored_path(&attachment.hash, attachment.extension.as_deref());
+    let name = escape_html(&attachment.file_name);
+    if let Some(mime_type) = attachments::image_mime_type(attachment.extension.as_deref()) {
+        match std::fs::read(&path) {
+            Ok(content) => {
+                return format!(
+                    "<img src=\"data:{};base64,{}\" alt=\"{}\">",
+                    mime_type,
+                    base64::engine::general_purpose::STANDARD.encode(content),
+                    name
+                )
+            }
+            Err(e) => eprintln!("Couldn't read the attachment {}: {}", path.display(), e),
+        }
+    }
+    format!(
+        "<br><a href=\"file://{}\">{}</a>",
+        escape_html(&path.display().to_string()),
+        name
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn test_printable_entries_are_those_inside_the_range() {
+    let mut api = Api::in_memory();
+    let at = |day: u32| {
+        chrono::NaiveDate::from_ymd_opt(2024, 3, day)
+            .and_then(|date| date.and_hms_opt(12, 0, 0))
+            .unwrap()
+    };
+    for (day, details) in [(1, "before"), (3, "inside"), (5, "after")] {
+        api.post_multiple_entries(vec![(None, None, Some(details.to_owned()))], Some(at(day)))
+            .unwrap();
+    }
+
+    let printed = printable_entries(
+        &mut api,
+        at(2),
+        at(4),
+        &api::EntryFilter::default(),
+        Format::Markdown,
+    )
+    .unwrap();
+    assert!(printed.contains("inside"));
+    assert!(!printed.contains("before"));
+    assert!(!printed.contains("after"));
 }
@@ -0,0 +1,109 @@
+/// This module contains the logic for the `profile` mode, which manages the journals and which one is in use.
+use crate::backend::{config, profiles};
+use crate::errors::Error;
+
+/// Lists the profiles, marking the one in use.
+pub fn list_profiles() -> Result<(), Error> {
+    let profiles = profiles::load_profiles()?;
+    if profiles.profiles.is_empty() {
+        println!(
+            "No profiles yet. Create one with `jurnalo profile create <name>`. Until then, the database from the config is used."
+        );
+        return Ok(());
+    }
+    let selected = profiles::selected_profile_name(&profiles).map(|(name, _)| name);
+    let padding_length = profiles.profiles.keys().map(String::len).max().unwrap_or(0) + 3;
+    for (name, profile) in profiles.profiles.iter() {
+        let marker = if selected.as_ref() == Some(name) {
+            "*"
+        } else {
+            " "
+        };
+        let setup = match profile.setup {
+            Some(ref setup) => format!("  setup: {}", setup),
+            None => String::new(),
+        };
+        println!(
+            "{} {:<padding_length$}{}{}",
+            marker, name, profile.database, setup
+        );
+    }
+    Ok(())
+}
+
+/// Adds a profile. The database defaults to `<name>.sqlite` in the data directory.
+/// The first profile created becomes the active one.
+pub fn create_profile(
+    name: &str,
+    database: &Option<String>,
+    setup: &Option<String>,
+) -> Result<(), Error> {
+    let valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if !valid_name {
+        return Err(Error::InvalidInput(format!(
+            "`{}` isn't a valid profile name. Names are made of letters, digits, `_` and `-`.",
+            name
+        )));
+    }
+
+    let mut profiles = profiles::load_profiles()?;
+    if profiles.profiles.contains_key(name) {
+        return Err(Error::ProfileAlreadyExists(name.to_owned()));
+    }
+
+    let database = match database {
+        Some(path) => absolute(path),
+        None => {
+            let directory = config::data_dir();
+            std::fs::create_dir_all(&directory)?;
+            directory
+                .join(format!("{}.sqlite", name))
+                .display()
+                .to_string()
+        }
+    };
+    let setup = setup.as_deref().map(absolute);
+
+    profiles.profiles.insert(
+        name.to_owned(),
+        profiles::Profile {
+            database: database.clone(),
+            setup,
+        },
+    );
+    if profiles.active.is_none() {
+        profiles.active = Some(name.to_owned());
+    }
+    profiles::save_profiles(&profiles)?;
+
+    println!("Created the profile `{}`, using {}.", name, database);
+    if !std::path::Path::new(&database).exists() {
+        println!(
+            "Run `jurnalo --profile {} init` to set up its database.",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Makes the profile the active one, used when no profile is selected with `--profile` or `JURNALO_PROFILE`.
+pub fn use_profile(name: &str) -> Result<(), Error> {
+    let mut profiles = profiles::load_profiles()?;
+    if !profiles.profiles.contains_key(name) {
+        return Err(Error::ProfileNotFound(name.to_owned()));
+    }
+    profiles.active = Some(name.to_owned());
+    profiles::save_profiles(&profiles)?;
+    println!("Now using the profile `{}`.", name);
+    Ok(())
+}
+
+/// The paths are stored as absolute paths, so the profiles work from any directory.
+fn absolute(path: &str) -> String {
+    std::path::absolute(path)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| path.to_owned())
+}
@@ -1,24 +1,84 @@
 /// This module contains the logic for the `quick_note` mode.
-use crate::backend::api;
+use std::path::Path;
+use std::process::Command;
+
+use super::remind::split_command;
+use crate::backend::api::{self, Api};
+use crate::backend::{attachments, config};
 use crate::errors::Error;
 
-/// Parses the content of the note and adds it to the database.
-pub fn parse_note(content: &[String]) -> Result<(), Error> {
-    let message = content.join(" ");
-    if message.is_empty() {
-        println!("You must provide a message with this command.")
+/// Parses the content of the note and adds it to the database, with the files attached to it.
+/// Without a note or a file, the note is written in the editor from the config.
+pub fn parse_note(api: &mut Api, content: &[String], attach: &[String]) -> Result<(), Error> {
+    let mut message = content.join(" ");
+    if message.trim().is_empty() && attach.is_empty() {
+        // Before writing the note, so a wrong passphrase doesn't lose it.
+        api.unlock()?;
+        message = write_in_editor()?;
+        if message.is_empty() {
+            println!("The note is empty, so it wasn't saved.");
+            return Ok(());
+        }
     }
-    match add_note(message) {
+    match add_note(api, message, attach) {
         Ok(_) => Ok(()),
         Err(e) => Err(e),
     }
 }
 
-fn add_note(note: String) -> Result<(), Error> {
+fn add_note(api: &mut Api, note: String, attach: &[String]) -> Result<(), Error> {
+    // The files are stored first, so a missing file doesn't leave a note without its attachments.
+    let files = attach
+        .iter()
+        .map(|path| attachments::store_file(Path::new(path)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    api.transaction(|api| -> Result<(), Error> {
+        let entry_id = api.post_entry(None, None, Some(note.clone()))?;
+        Ok(api.post_attachments(entry_id, &files)?)
+    })?;
+
     if note.is_empty() {
-        todo!();
+        println!("Success! Saved the attachments.");
+    } else {
+        println!("Success! Saved note:\n{}", note);
+    }
+    let tags = api::tags::extract_hashtags(&note);
+    if !tags.is_empty() {
+        println!(
+            "Tagged: {}",
+            tags.iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+    for file in files.iter() {
+        println!("Attached: {}", file.file_name);
     }
-    api::post_entry(None, None, Some(note.clone()))?;
-    println!("Success! Saved note:\n{}", note);
     Ok(())
 }
+
+/// Opens the editor on a temporary file and returns what was written in it, trimmed.
+fn write_in_editor() -> Result<String, Error> {
+    let editor = &config::get().editor.value;
+    let mut args = split_command(editor);
+    if args.is_empty() {
+        return Err(Error::InvalidConfig(
+            "The editor command is empty.".to_owned(),
+        ));
+    }
+    let path = std::env::temp_dir().join(format!("jurnalo-note-{}.md", std::process::id()));
+    std::fs::write(&path, "")?;
+    args.push(path.display().to_string());
+
+    let status = Command::new(&args[0]).args(&args[1..]).status();
+    let note = std::fs::read_to_string(&path);
+    std::fs::remove_file(&path).ok();
+
+    let status = status?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("`{}` exited with {}", args[0], status)).into());
+    }
+    Ok(note?.trim().to_owned())
+}
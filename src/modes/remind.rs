@@ -0,0 +1,209 @@
+/// This module contains the logic for the `remind` mode, which is meant to be run periodically (e.g. from cron or a
+/// systemd timer) and sends a notification for each due item and pending scheduled quiz.
+use chrono::{Duration, NaiveDateTime};
+use std::process::Command;
+
+use super::due::{self, DueItem};
+use super::status::{self, QuizStatus};
+use crate::backend::api::Api;
+use crate::backend::config;
+use crate::errors::Error;
+
+const NOTIFICATION_TITLE: &str = "jurnalo";
+const MISSED_QUIZZES_RANGE: u32 = 7; // missed quizzes are counted over the last 7 days.
+
+/// A notification to send. The key identifies it across runs, so it isn't sent again too soon.
+struct Reminder {
+    key: String,
+    message: String,
+}
+
+/// Sends a notification for each due item and each quiz pending for today, unless it was already sent in the last
+/// `every_hours` hours (or `force` is set).
+///
+/// The notification command and the message template are taken from the arguments, or from `notify_command` and
+/// `notify_template` in the config (or their environment variables, `JURNALO_NOTIFY_COMMAND` and
+/// `JURNALO_NOTIFY_TEMPLATE`). Without a command, the notifications are printed to stdout.
+pub fn remind(
+    api: &mut Api,
+    command: &Option<String>,
+    template: &Option<String>,
+    every_hours: u32,
+    force: bool,
+) -> Result<(), Error> {
+    let config = config::get();
+    let command = command
+        .clone()
+        .or_else(|| config.notify_command.value.clone());
+    let template = template
+        .clone()
+        .unwrap_or_else(|| config.notify_template.value.clone());
+
+    let now = chrono::Utc::now().naive_utc();
+
+    let mut reminders: Vec<Reminder> = due::get_due_items(api, now)?
+        .iter()
+        .map(|item| Reminder {
+            key: item.key(),
+            message: fill_template(&template, item, now),
+        })
+        .collect();
+
+    let today = config::today();
+    reminders.extend(
+        status::get_quiz_statuses(api, today, MISSED_QUIZZES_RANGE)?
+            .iter()
+            .filter(|quiz_status| quiz_status.pending_today)
+            .map(|quiz_status| Reminder {
+                key: format!("quiz:{}", quiz_status.label),
+                message: fill_quiz_template(&template, quiz_status),
+            }),
+    );
+
+    for reminder in reminders {
+        if !force {
+            if let Some(last_sent) = api.get_latest_sent_reminder(&reminder.key)? {
+                if now - last_sent < Duration::hours(every_hours.into()) {
+                    continue;
+                }
+            }
+        }
+
+        match send_notification(command.as_deref(), &reminder.message) {
+            Ok(()) => api.post_sent_reminder(&reminder.key)?,
+            Err(e) => eprintln!("Couldn't send the reminder \"{}\": {}", reminder.message, e),
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the placeholders in the template with the values of the due item.
+/// Available placeholders: `{label}`, `{kind}`, `{category}`, `{choice}`, `{days_overdue}`, `{last_done}` and `{status}`.
+fn fill_template(template: &str, item: &DueItem, now: NaiveDateTime) -> String {
+    template
+        .replace("{label}", &item.display_label())
+        .replace("{kind}", item.kind.label())
+        .replace("{category}", &item.category_label)
+        .replace("{choice}", item.choice_label.as_deref().unwrap_or(""))
+        .replace(
+            "{days_overdue}",
+            &item
+                .days_overdue(now)
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+        )
+        .replace(
+            "{last_done}",
+            &item
+                .last_done
+                .map(|ts| config::local_date(ts).to_string())
+                .unwrap_or("never".to_owned()),
+        )
+        .replace("{status}", &item.describe_status(now))
+}
+
+/// Replaces the placeholders in the template with the values of the quiz pending for today.
+/// The placeholders that only make sense for due items are left empty.
+fn fill_quiz_template(template: &str, quiz_status: &QuizStatus) -> String {
+    let status = match quiz_status.missed.len() {
+        0 => "pending for today".to_owned(),
+        n => format!(
+            "pending for today, missed {} time(s) in the last {} days",
+            n, MISSED_QUIZZES_RANGE
+        ),
+    };
+    template
+        .replace("{label}", &quiz_status.label)
+        .replace("{kind}", "quiz")
+        .replace("{category}", "")
+        .replace("{choice}", "")
+        .replace("{days_overdue}", "")
+        .replace("{last_done}", "")
+        .replace("{status}", &status)
+}
+
+/// Runs the notification command, replacing `{title}` and `{message}` in its arguments.
+/// If the command has no `{message}` placeholder, the message is passed as the last argument.
+/// The command isn't run through a shell, so the message never needs escaping.
+fn send_notification(command: Option<&str>, message: &str) -> Result<(), std::io::Error> {
+    let Some(command) = command else {
+        println!("{}", message);
+        return Ok(());
+    };
+
+    let mut args = split_command(command);
+    if args.is_empty() {
+        return Err(std::io::Error::other("the notification command is empty"));
+    }
+    if !args.iter().any(|arg| arg.contains("{message}")) {
+        args.push("{message}".to_owned());
+    }
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            arg.replace("{title}", NOTIFICATION_TITLE)
+                .replace("{message}", message)
+        })
+        .collect();
+
+    let status = Command::new(&args[0]).args(&args[1..]).status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "`{}` exited with {}",
+            args[0], status
+        )));
+    }
+    Ok(())
+}
+
+/// Splits a command line into arguments, respecting single quotes, double quotes and backslash escapes.
+pub(crate) fn split_command(command: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                in_argument = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_argument = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_argument {
+                    args.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_argument = true;
+            }
+        }
+    }
+    if in_argument {
+        args.push(current);
+    }
+    args
+}
+
+#[test]
+fn test_split_command() {
+    assert_eq!(
+        split_command("notify-send -u low {title} {message}"),
+        vec!["notify-send", "-u", "low", "{title}", "{message}"]
+    );
+    assert_eq!(
+        split_command(r#"  sh -c 'echo "$0" >> log.txt' "it\'s {message}" '' "#),
+        vec!["sh", "-c", r#"echo "$0" >> log.txt"#, "it's {message}", ""]
+    );
+}
@@ -1,49 +1,71 @@
-use crate::backend::api;
-use crate::errors::{Error, ParsingCommandError};
+use crate::backend::api::Api;
+use crate::backend::{attachments, config};
+use crate::errors::Error;
 use crate::models;
 
 use chrono::prelude::*;
 use std::collections::HashMap;
+use std::path::Path;
 
 const STREAK_RANGE: usize = 7; // the last 7 days are used for streaks.
+const ATTACHMENTS_CATEGORY_TYPE: i32 = 6;
 
-fn get_user_input() -> String {
+fn get_user_input() -> Result<String, Error> {
     let mut input = String::new();
-    match std::io::stdin().read_line(&mut input) {
-        Ok(_) => input,
-        Err(_) => {
-            panic!("Couldn't handle user input.")
-        }
-    }
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input)
 }
 
-pub fn quiz_full(quiz_name: &str) -> Result<(), Error> {
+/// Runs the quiz, and saves the answers. If `for_date` is given, the answers are back-filled to that (local) date.
+pub fn quiz_full(api: &mut Api, quiz_name: &str, for_date: Option<NaiveDate>) -> Result<(), Error> {
     assert!(
         !quiz_name.is_empty(),
         "This should never be empty because we're supposed to parse and handle before this point."
     );
     let mut inputs = Vec::new();
     let mut entries: Vec<(Option<i32>, Option<i32>, Option<String>)> = Vec::new();
+    // The files attached in the quiz, with the index of their entry in `entries`.
+    let mut attached_files: Vec<(usize, Vec<attachments::StoredFile>)> = Vec::new();
 
-    let categories_and_choices = api::get_categories_and_choices_from_quiz_label(quiz_name)?;
+    let categories_and_choices = api.get_categories_and_choices_from_quiz_label(quiz_name)?;
 
     if categories_and_choices.is_empty() {
-        return Err(ParsingCommandError::QuizNotFound(quiz_name.to_owned()).into());
+        return Err(Error::QuizNotFound(quiz_name.to_owned()));
+    }
+    // The passphrase of an encrypted journal is asked before the questions, so a wrong one doesn't lose the answers.
+    api.unlock()?;
+
+    if let Some(date) = for_date {
+        println!("Back-filling {} for {}.", quiz_name, date);
     }
 
     for (cat, choices) in categories_and_choices.iter() {
-        println!("{}", cat.prompt);
+        if category_is_due(api, cat)? {
+            println!("*{}*", cat.prompt);
+        } else {
+            println!("{}", cat.prompt);
+        }
+        if cat.category_type == ATTACHMENTS_CATEGORY_TYPE {
+            println!("[paths of the files to attach] : [comment]");
+            let input = get_user_input()?.trim().to_owned();
+            inputs.push(input.clone());
+            if let Some((files, comment)) = parse_attachments_input(&input) {
+                attached_files.push((entries.len(), files));
+                entries.push((Some(cat.id), None, Some(comment)));
+            }
+            continue;
+        }
         if let Some(cs) = choices {
             println!(
                 "{}",
                 cs.iter()
-                    .map(format_choice_and_shortcut)
-                    .collect::<Vec<String>>()
+                    .map(|choice| format_choice_and_shortcut(api, choice))
+                    .collect::<Result<Vec<String>, Error>>()?
                     .join(" ")
             );
         }
 
-        let input = get_user_input().trim().to_owned();
+        let input = get_user_input()?.trim().to_owned();
         inputs.push(input.clone());
 
         let shortcuts: Vec<(i32, String)> = choices
@@ -64,15 +86,61 @@ pub fn quiz_full(quiz_name: &str) -> Result<(), Error> {
     }
 
     println!("{}", inputs.join(" | "));
-    api::post_multiple_entries(entries).expect("Failed to add to the database.");
+
+    // Back-filled entries keep the current time of the day, but on the given date.
+    let timestamp = for_date.and_then(|date| {
+        Local
+            .from_local_datetime(&date.and_time(Local::now().time().with_nanosecond(0).unwrap()))
+            .earliest()
+            .map(|dt| dt.naive_utc())
+    });
+    // The day of the run, which the goals report is for too.
+    let day = for_date.unwrap_or_else(config::today);
+    // The answers are saved all at once, so an error doesn't leave a half-saved quiz.
+    api.transaction(|api| -> Result<(), Error> {
+        let entry_ids = api.post_multiple_entries(entries, timestamp)?;
+        for (index, files) in attached_files {
+            api.post_attachments(entry_ids[index], &files)?;
+        }
+        Ok(api.post_quiz_run(quiz_name, day)?)
+    })?;
 
     // Print streaks table.
-    if let Some(streaks_table) = format_streaks_into_table(fetch_and_process_streaks()) {
+    if let Some(streaks_table) = format_streaks_into_table(fetch_and_process_streaks(api)?) {
         println!("{}", streaks_table);
     }
+    if let Some(goals_report) = super::goals::format_goals_report(api, day)? {
+        println!("{}", goals_report);
+    }
     Ok(())
 }
 
+/// Parses the answer to an attachments category: paths (quoted if they have spaces), then optionally `: ` and a comment.
+/// Stores the files that exist, and returns them with the comment, or `None` if there's nothing to save.
+fn parse_attachments_input(input: &str) -> Option<(Vec<attachments::StoredFile>, String)> {
+    // Only a colon followed by a space separates the comment, so Windows paths like `C:\...` still work.
+    let (paths, comment) = match input.split_once(": ") {
+        Some((paths, comment)) => (paths, comment.trim()),
+        None => (input.strip_suffix(':').unwrap_or(input), ""),
+    };
+
+    let files: Vec<attachments::StoredFile> = super::remind::split_command(paths)
+        .iter()
+        .filter_map(|path| match attachments::store_file(Path::new(path)) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("Couldn't attach {}: {}", path, e);
+                None
+            }
+        })
+        .collect();
+
+    if files.is_empty() && comment.is_empty() {
+        return None;
+    }
+    Some((files, comment.to_owned()))
+}
+
 fn extract_shortcuts_from_input(
     user_input: String,
     shortcuts: Vec<(i32, String)>,
@@ -134,34 +202,36 @@ fn extract_shortcuts_from_input(
     result
 }
 
-fn fetch_and_process_streaks() -> HashMap<String, [bool; STREAK_RANGE]> {
+fn fetch_and_process_streaks(
+    api: &mut Api,
+) -> Result<HashMap<String, [bool; STREAK_RANGE]>, Error> {
     use chrono::Duration;
 
-    let today = Local::now().naive_utc();
+    let today = config::today();
     let last_seven_days = (0..STREAK_RANGE)
-        .map(|offset| (today - Duration::days(offset as i64)).date())
+        .map(|offset| today - Duration::days(offset as i64))
         .collect::<Vec<NaiveDate>>();
 
     // Initialize the labels and booleans per day
     let mut labels_and_bools: HashMap<String, [bool; STREAK_RANGE]> = HashMap::new();
 
-    let response = api::get_timestamps_for_streaks_of_choices().unwrap();
+    let response = api.get_timestamps_for_streaks_of_choices()?;
 
     for (label, timestamp) in response {
         match timestamp {
             Some(ts) => {
-                if last_seven_days.contains(&ts.date()) {
+                let date = config::local_date(ts);
+                if last_seven_days.contains(&date) {
                     // Calculate the day index (0 for today, 6 for 6 days ago)
-                    // IDEA: Currently uses difference from the current time. It could be good to use
-                    let day_index = (today - ts).num_days() as usize;
+                    let day_index = (today - date).num_days() as usize;
                     // Update the boolean array for the label and day
                     let bool_array = labels_and_bools
                         .entry(label.clone())
                         .or_insert([false; STREAK_RANGE]);
-                    if day_index <= bool_array.len() {
+                    if day_index < bool_array.len() {
                         bool_array[day_index] = true;
                     } else {
-                        log::warn!("This should only be dealing with entries/timestamps within the streak range.")
+                        eprintln!("This should only be dealing with entries/timestamps within the streak range.")
                     }
                 }
             }
@@ -172,7 +242,7 @@ fn fetch_and_process_streaks() -> HashMap<String, [bool; STREAK_RANGE]> {
             }
         }
     }
-    labels_and_bools
+    Ok(labels_and_bools)
 }
 
 fn format_streaks_into_table(
@@ -228,27 +298,41 @@ fn format_streaks_into_table(
     Some(table)
 }
 
-fn format_choice_and_shortcut(choice: &models::queryable_or_selectable::Choice) -> String {
-    if !choice_is_due(choice) {
-        format!("[{}] {}", choice.shortcut, choice.label)
+fn format_choice_and_shortcut(
+    api: &mut Api,
+    choice: &models::queryable_or_selectable::Choice,
+) -> Result<String, Error> {
+    if !choice_is_due(api, choice)? {
+        Ok(format!("[{}] {}", choice.shortcut, choice.label))
     } else {
-        format!("[{}] *{}*", choice.shortcut, choice.label)
+        Ok(format!("[{}] *{}*", choice.shortcut, choice.label))
     }
 }
 
-fn choice_is_due(choice: &models::queryable_or_selectable::Choice) -> bool {
-    if choice.reminder_timer_in_days.is_none() {
-        return false;
-    }
+fn choice_is_due(
+    api: &mut Api,
+    choice: &models::queryable_or_selectable::Choice,
+) -> Result<bool, Error> {
+    let Some(reminder_timer_in_days) = choice.reminder_timer_in_days else {
+        return Ok(false);
+    };
 
-    let reminder_timer_in_days = choice.reminder_timer_in_days.unwrap();
     let now = chrono::Utc::now().naive_utc();
-    let timestamp = api::get_latest_timestamp_for_choice(choice.id).unwrap();
+    let timestamp = api.get_latest_timestamp_for_choice(choice.id)?;
 
-    if let Some(ts) = timestamp {
-        let days_since_last_entry = (now - ts).num_days();
-        return days_since_last_entry >= reminder_timer_in_days.into();
-    }
+    Ok(super::due::is_due(reminder_timer_in_days, timestamp, now))
+}
+
+fn category_is_due(
+    api: &mut Api,
+    category: &models::queryable_or_selectable::Category,
+) -> Result<bool, Error> {
+    let Some(reminder_timer_in_days) = category.reminder_timer_in_days else {
+        return Ok(false);
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    let timestamp = api.get_latest_timestamp_for_category(category.id)?;
 
-    true // default behavior, if no entry is found
+    Ok(super::due::is_due(reminder_timer_in_days, timestamp, now))
 }
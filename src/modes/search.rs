@@ -0,0 +1,170 @@
+/// This module contains the logic for the `search` mode, which finds entries with the full-text index.
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+use crate::backend::api::{self, Api};
+use crate::backend::config;
+use crate::errors::Error;
+use crate::models::queryable_or_selectable::SearchResult;
+
+const TERMINAL_HIGHLIGHT: (&str, &str) = ("\x1b[1;4m", "\x1b[0m");
+const PLAIN_HIGHLIGHT: (&str, &str) = ("**", "**");
+
+/// Prints the entries matching the query, best matches first. The query uses the FTS5 syntax:
+/// words (all must match), `"a phrase"`, `prefix*`, `this OR that`, `NOT this`, or `details: word` for a single column.
+/// `from` and `to` are local dates (YYYY-MM-DD), both included. Searching for a `#hashtag` needs the `tag` filter instead.
+/// In an encrypted journal, only the labels, tags and mentions of the entries are searched, for the plain words of the
+/// query.
+pub fn print_search(
+    api: &mut Api,
+    query: &[String],
+    from: &Option<String>,
+    to: &Option<String>,
+    category: &Option<String>,
+    tag: &Option<String>,
+    limit: u32,
+) -> Result<(), Error> {
+    let query = query.join(" ");
+    if query.trim().is_empty() {
+        return Err(Error::InvalidInput(
+            "You must provide something to search for.".to_owned(),
+        ));
+    }
+
+    let starting_date = match from {
+        Some(date) => config::start_of_day(parse_date(date)?),
+        None => NaiveDateTime::UNIX_EPOCH,
+    };
+    let end_date = match to {
+        Some(date) => config::start_of_day(parse_date(date)?.succ_opt().expect("Date in range.")),
+        // Not `NaiveDateTime::MAX`, as the timestamps are compared as text.
+        None => NaiveDate::from_ymd_opt(9999, 12, 31)
+            .expect("Valid date.")
+            .and_time(NaiveTime::MIN),
+    };
+
+    let tag = match tag {
+        Some(t) => Some(
+            api::tags::normalize_tag(t)
+                .ok_or_else(|| Error::InvalidInput(format!("`{}` isn't a valid tag.", t)))?,
+        ),
+        None => None,
+    };
+
+    let highlight = if config::use_color() {
+        TERMINAL_HIGHLIGHT
+    } else {
+        PLAIN_HIGHLIGHT
+    };
+
+    let results = if api.is_encrypted()? {
+        // The index only has the encrypted details, which can't match anything.
+        eprintln!("The details of an encrypted journal can't be searched: only the labels, tags and mentions are.");
+        let words = plain_words(&query);
+        api.get_entries_with_labels_and_mentions(
+            starting_date,
+            end_date,
+            category.as_deref(),
+            tag.as_deref(),
+        )?
+        .into_iter()
+        .filter(|result| matches_all_words(result, &words))
+        .take(limit as usize)
+        .collect()
+    } else {
+        match api.search_entries(
+            &query,
+            starting_date,
+            end_date,
+            category.as_deref(),
+            tag.as_deref(),
+            limit.into(),
+            highlight,
+        ) {
+            Ok(results) => results,
+            Err(diesel::result::Error::DatabaseError(_, info)) => {
+                return Err(Error::InvalidInput(format!(
+                    "Couldn't search for `{}`: {}. Wrap words with punctuation in double quotes.",
+                    query,
+                    info.message()
+                )))
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    if results.is_empty() {
+        println!("No entries match `{}`.", query);
+        return Ok(());
+    }
+
+    for result in results.iter() {
+        let time = Local.from_utc_datetime(&result.timestamp);
+        let labels = match (&result.category_label, &result.choice_label) {
+            (Some(category), Some(choice)) => format!("{} -> {}", category, choice),
+            (Some(category), None) => category.clone(),
+            (None, _) => "note".to_owned(),
+        };
+        println!(
+            "{} [{}] #{}",
+            time.format("%Y-%m-%d %H:%M"),
+            labels,
+            result.id
+        );
+        if !result.snippet.is_empty() {
+            println!("    {}", result.snippet.replace('\n', " "));
+        }
+    }
+    if results.len() == limit as usize {
+        println!(
+            "(Showing the first {} matches. Use --limit to see more.)",
+            limit
+        );
+    }
+    Ok(())
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| {
+        Error::InvalidInput(format!("The date {} must be written as YYYY-MM-DD.", date))
+    })
+}
+
+/// The words of a full-text query, without the FTS5 operators and punctuation, in lowercase.
+fn plain_words(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "AND" | "OR" | "NOT"))
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Whether every word is in the labels, tags or mentions of the entry, ignoring the case.
+fn matches_all_words(result: &SearchResult, words: &[String]) -> bool {
+    let text = [
+        result.category_label.as_deref().unwrap_or_default(),
+        result.choice_label.as_deref().unwrap_or_default(),
+        &result.snippet,
+    ]
+    .join(" ")
+    .to_lowercase();
+    words.iter().all(|word| text.contains(word.as_str()))
+}
+
+#[test]
+fn test_matches_all_words_of_the_labels_tags_and_mentions() {
+    let result = SearchResult {
+        id: 1,
+        timestamp: NaiveDateTime::UNIX_EPOCH,
+        category_label: Some("mood".to_owned()),
+        choice_label: Some("Happy".to_owned()),
+        snippet: "#work @Monica +Central_Perk".to_owned(),
+    };
+    let words = plain_words("\"happy\" AND monica* #work");
+    assert_eq!(words, ["happy", "monica", "work"]);
+    assert!(matches_all_words(&result, &words));
+    assert!(!matches_all_words(&result, &plain_words("happy ross")));
+}
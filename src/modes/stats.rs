@@ -0,0 +1,279 @@
+/// This module contains the logic for the `stats` mode, which summarizes the entries of each category over a period,
+/// compared with the period right before it.
+use chrono::{Duration, NaiveDate};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::api::{self, Api};
+use crate::backend::config;
+use crate::errors::Error;
+use crate::models::queryable_or_selectable as m_qos;
+
+const DEFAULT_PERIOD_IN_DAYS: u32 = 30;
+const DETAILS_ONLY_LABEL: &str = "(details only)";
+
+/// Parses a period like "week", "month", "year" or a number of days (e.g. "14") into a number of days.
+pub fn parse_period(period: &str) -> Option<u32> {
+    match period.trim().to_lowercase().as_str() {
+        "week" => Some(7),
+        "month" => Some(30),
+        "year" => Some(365),
+        other => other
+            .trim_end_matches("days")
+            .trim_end_matches("day")
+            .trim_end_matches('d')
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|days| *days > 0),
+    }
+}
+
+/// The entries of a category in a period.
+#[derive(Debug, Default)]
+struct CategoryStats {
+    /// How many times each choice was picked, in the order of the choices. Includes the choices that were never picked.
+    counts: Vec<(String, u32)>,
+    /// How many entries in the category had no choice (only details).
+    without_choice: u32,
+    /// The days on which the category had any entry.
+    days_with_entries: BTreeSet<NaiveDate>,
+}
+
+impl CategoryStats {
+    fn total_with_choice(&self) -> u32 {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    fn count_of(&self, choice_label: &str) -> u32 {
+        self.counts
+            .iter()
+            .find(|(label, _)| label == choice_label)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+
+    /// Returns the labels of the most and least common choices (all of them, in case of ties).
+    fn most_and_least_common(&self) -> (Vec<&str>, Vec<&str>) {
+        let max = self.counts.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        let min = self.counts.iter().map(|(_, c)| *c).min().unwrap_or(0);
+        let with_count = |n: u32| {
+            self.counts
+                .iter()
+                .filter(|(_, c)| *c == n)
+                .map(|(label, _)| label.as_str())
+                .collect()
+        };
+        (with_count(max), with_count(min))
+    }
+}
+
+/// Groups the entries by category label, counting how many times each choice was picked.
+fn compute_stats(
+    entries: &[api::EntryWithLabelsTuple],
+    choices: &[m_qos::Choice],
+) -> BTreeMap<String, CategoryStats> {
+    let mut stats: BTreeMap<String, CategoryStats> = BTreeMap::new();
+
+    for choice in choices {
+        stats
+            .entry(choice.category_label.clone())
+            .or_default()
+            .counts
+            .push((choice.label.clone(), 0));
+    }
+
+    for api::EntryWithLabelsTuple(entry, category_label, choice_label) in entries {
+        let Some(category_label) = category_label else {
+            continue;
+        };
+        let category_stats = stats.entry(category_label.clone()).or_default();
+        category_stats
+            .days_with_entries
+            .insert(config::local_date(entry.timestamp));
+
+        match choice_label {
+            Some(choice_label) => {
+                match category_stats
+                    .counts
+                    .iter_mut()
+                    .find(|(label, _)| label == choice_label)
+                {
+                    Some((_, count)) => *count += 1,
+                    None => category_stats.counts.push((choice_label.clone(), 1)),
+                }
+            }
+            None => category_stats.without_choice += 1,
+        }
+    }
+
+    stats
+}
+
+/// Prints the stats of each category for the period (e.g. "week", "month", "year" or a number of days),
+/// compared with the period before it. Defaults to the last 30 days. Only the entries that pass the filter are counted.
+pub fn print_stats(
+    api: &mut Api,
+    period: &Option<String>,
+    filter: &api::EntryFilter,
+) -> Result<(), Error> {
+    let days = match period {
+        Some(p) => parse_period(p).ok_or_else(|| {
+            Error::InvalidInput(
+                "The period must be `week`, `month`, `year` or a number of days.".to_owned(),
+            )
+        })?,
+        None => DEFAULT_PERIOD_IN_DAYS,
+    };
+
+    // Whole local days, today included, so an entry counts on the same day as in the rest of jurnalo.
+    let now = chrono::Utc::now().naive_utc();
+    let today = config::today();
+    let first_day = today - Duration::days(i64::from(days) - 1);
+    let start = config::start_of_day(first_day);
+    let previous_start = config::start_of_day(first_day - Duration::days(days.into()));
+
+    let current_entries = api.get_filtered_entries_between_dates(start, now, filter)?;
+    let previous_entries = api.get_filtered_entries_between_dates(previous_start, start, filter)?;
+    let choices = api.get_all_choices()?;
+
+    let current = compute_stats(&current_entries, &choices);
+    let previous = compute_stats(&previous_entries, &choices);
+
+    let period_days: Vec<NaiveDate> = (0..days)
+        .map(|offset| today - Duration::days(offset.into()))
+        .collect();
+
+    println!(
+        "Stats for the last {} days ({} to {}), compared with the {} days before:",
+        days,
+        period_days
+            .last()
+            .expect("The period is at least one day long."),
+        today,
+        days
+    );
+
+    let days_with_any_entry: BTreeSet<NaiveDate> = current_entries
+        .iter()
+        .map(|api::EntryWithLabelsTuple(entry, _, _)| config::local_date(entry.timestamp))
+        .collect();
+    println!(
+        "Entries: {} {}, days without any entries: {} of {}",
+        current_entries.len(),
+        format_difference(current_entries.len() as i64 - previous_entries.len() as i64),
+        period_days
+            .iter()
+            .filter(|day| !days_with_any_entry.contains(day))
+            .count(),
+        days
+    );
+
+    let empty_stats = CategoryStats::default();
+    for (category_label, stats) in current.iter() {
+        let previous_stats = previous.get(category_label).unwrap_or(&empty_stats);
+        if stats.days_with_entries.is_empty() && previous_stats.days_with_entries.is_empty() {
+            continue;
+        }
+
+        println!();
+        println!("{}", category_label);
+
+        let total = stats.total_with_choice();
+        let show_without_choice = stats.without_choice > 0 || previous_stats.without_choice > 0;
+        let padding_length = stats
+            .counts
+            .iter()
+            .map(|(label, _)| label.len())
+            .chain(show_without_choice.then_some(DETAILS_ONLY_LABEL.len()))
+            .max()
+            .unwrap_or(0)
+            + 2;
+
+        for (choice_label, count) in stats.counts.iter() {
+            let percentage = if total > 0 {
+                100.0 * *count as f64 / total as f64
+            } else {
+                0.0
+            };
+            println!(
+                "  {:<padding_length$}{:>4} {:>6.1}% {}",
+                format!("{}:", choice_label),
+                count,
+                percentage,
+                format_difference(*count as i64 - previous_stats.count_of(choice_label) as i64)
+            );
+        }
+        if show_without_choice {
+            println!(
+                "  {:<padding_length$}{:>4}         {}",
+                format!("{}:", DETAILS_ONLY_LABEL),
+                stats.without_choice,
+                format_difference(
+                    stats.without_choice as i64 - previous_stats.without_choice as i64
+                )
+            );
+        }
+
+        if total > 0 {
+            let (most, least) = stats.most_and_least_common();
+            println!(
+                "  Most common: {}; least common: {}",
+                most.join(", "),
+                least.join(", ")
+            );
+        }
+        println!(
+            "  Days without entries: {} of {}",
+            period_days
+                .iter()
+                .filter(|day| !stats.days_with_entries.contains(day))
+                .count(),
+            days
+        );
+    }
+
+    let current_tags = api.get_tag_counts_between_dates(start, now, filter)?;
+    let previous_tags: BTreeMap<String, i64> = api
+        .get_tag_counts_between_dates(previous_start, start, filter)?
+        .into_iter()
+        .collect();
+    if !current_tags.is_empty() {
+        println!();
+        println!("Tags");
+        let padding_length = current_tags
+            .iter()
+            .map(|(tag, _)| tag.len())
+            .max()
+            .unwrap_or(0)
+            + 3;
+        for (tag, count) in current_tags.iter() {
+            println!(
+                "  {:<padding_length$}{:>4} {}",
+                format!("#{}:", tag),
+                count,
+                format_difference(count - previous_tags.get(tag).copied().unwrap_or(0))
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Formats the difference with the previous period, e.g. "(+3)", "(-1)" or "(=)".
+fn format_difference(difference: i64) -> String {
+    match difference {
+        0 => "(=)".to_owned(),
+        d if d > 0 => format!("(+{})", d),
+        d => format!("({})", d),
+    }
+}
+
+#[test]
+fn test_parse_period() {
+    assert_eq!(parse_period("week"), Some(7));
+    assert_eq!(parse_period("Month"), Some(30));
+    assert_eq!(parse_period("14"), Some(14));
+    assert_eq!(parse_period("14d"), Some(14));
+    assert_eq!(parse_period("14 days"), Some(14));
+    assert_eq!(parse_period("0"), None);
+    assert_eq!(parse_period("fortnight"), None);
+}
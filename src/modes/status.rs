@@ -0,0 +1,226 @@
+/// This module contains the logic for quiz schedules and the `status` mode, which reports the scheduled quizzes
+/// that are pending for today or were missed in the last days.
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+
+use crate::backend::api::Api;
+use crate::backend::config;
+use crate::errors::Error;
+
+/// When a quiz should be answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Daily,
+    Weekdays,
+    Weekly(Weekday),
+    /// On this day of the month, or on the last day for shorter months.
+    Monthly(u32),
+}
+
+impl Schedule {
+    /// Parses a schedule like "daily", "weekdays", "weekly sun" or "monthly 15".
+    /// "weekly" and "monthly" without a day default to Sunday and the 1st.
+    pub fn parse(schedule: &str) -> Option<Self> {
+        let lowercase = schedule.trim().to_lowercase();
+        let mut words = lowercase.split_whitespace();
+        let kind = words.next()?;
+        let day = words.next();
+        if words.next().is_some() {
+            return None;
+        }
+
+        match (kind, day) {
+            ("daily", None) => Some(Self::Daily),
+            ("weekdays", None) => Some(Self::Weekdays),
+            ("weekly", None) => Some(Self::Weekly(Weekday::Sun)),
+            ("weekly", Some(day)) => day.parse::<Weekday>().ok().map(Self::Weekly),
+            ("monthly", None) => Some(Self::Monthly(1)),
+            ("monthly", Some(day)) => match day.parse::<u32>() {
+                Ok(n) if (1..=31).contains(&n) => Some(Self::Monthly(n)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The label used for this schedule in the database and in the TOML.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Daily => "daily".to_owned(),
+            Self::Weekdays => "weekdays".to_owned(),
+            Self::Weekly(day) => format!("weekly {}", day.to_string().to_lowercase()),
+            Self::Monthly(day) => format!("monthly {}", day),
+        }
+    }
+
+    pub fn is_scheduled_on(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Daily => true,
+            Self::Weekdays => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            Self::Weekly(day) => date.weekday() == *day,
+            Self::Monthly(day) => {
+                let last_day_of_month = (date + Duration::days(1)).day() == 1;
+                date.day() == *day || (last_day_of_month && date.day() < *day)
+            }
+        }
+    }
+}
+
+/// The scheduled days on which a quiz wasn't answered.
+pub struct QuizStatus {
+    pub label: String,
+    pub pending_today: bool,
+    /// The days before today on which the quiz was missed, oldest first.
+    pub missed: Vec<NaiveDate>,
+}
+
+/// Returns the status of every scheduled quiz, looking `days` days back for missed quizzes.
+pub fn get_quiz_statuses(
+    api: &mut Api,
+    today: NaiveDate,
+    days: u32,
+) -> Result<Vec<QuizStatus>, Error> {
+    let since = today - Duration::days(days.into());
+    let runs: HashSet<(String, NaiveDate)> = api.get_quiz_runs_since(since)?.into_iter().collect();
+
+    let mut statuses = Vec::new();
+    for quiz in api.get_scheduled_quizzes()? {
+        let schedule_label = quiz
+            .schedule
+            .expect("The query only returns quizzes with a schedule.");
+        let Some(schedule) = Schedule::parse(&schedule_label) else {
+            eprintln!(
+                "Ignoring the invalid schedule `{}` of quiz {}.",
+                schedule_label, quiz.label
+            );
+            continue;
+        };
+
+        let is_pending = |date: NaiveDate| {
+            schedule.is_scheduled_on(date) && !runs.contains(&(quiz.label.clone(), date))
+        };
+
+        statuses.push(QuizStatus {
+            pending_today: is_pending(today),
+            missed: (1..=days)
+                .rev()
+                .map(|offset| today - Duration::days(offset.into()))
+                .filter(|date| is_pending(*date))
+                .collect(),
+            label: quiz.label,
+        });
+    }
+    Ok(statuses)
+}
+
+/// Prints which scheduled quizzes are pending for today and which were missed in the last `days` days.
+/// With `short`, prints a single line (or nothing, if everything is done), e.g. for a shell prompt.
+/// Otherwise, if `prompt` is set and the terminal is interactive, offers to run the pending quizzes and back-fill the missed ones.
+pub fn print_status(api: &mut Api, days: u32, short: bool, prompt: bool) -> Result<(), Error> {
+    let today = config::today();
+    let statuses = get_quiz_statuses(api, today, days)?;
+
+    let pending: Vec<&QuizStatus> = statuses.iter().filter(|s| s.pending_today).collect();
+    let missed_count: usize = statuses.iter().map(|s| s.missed.len()).sum();
+
+    if short {
+        let mut parts = Vec::new();
+        if !pending.is_empty() {
+            parts.push(format!(
+                "pending: {}",
+                pending
+                    .iter()
+                    .map(|s| s.label.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if missed_count > 0 {
+            parts.push(format!("missed: {}", missed_count));
+        }
+        if !parts.is_empty() {
+            println!("{}", parts.join("; "));
+        }
+        return Ok(());
+    }
+
+    if statuses.is_empty() {
+        println!("No quizzes are scheduled. Use `jurnalo quiz schedule` to add a schedule.");
+        return Ok(());
+    }
+
+    if pending.is_empty() {
+        println!("All scheduled quizzes are done for today.");
+    } else {
+        println!("Pending today:");
+        for status in pending.iter() {
+            println!("  {}", status.label);
+        }
+    }
+    if missed_count > 0 {
+        println!("Missed in the last {} days:", days);
+        for status in statuses.iter().filter(|s| !s.missed.is_empty()) {
+            println!(
+                "  {}: {}",
+                status.label,
+                status
+                    .missed
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    if !prompt || !std::io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    for status in pending.iter() {
+        if ask_yes_or_no(&format!("Run {} now?", status.label)) {
+            super::run_quiz::quiz_full(api, &status.label, None)?;
+        }
+    }
+    for status in statuses.iter() {
+        for date in status.missed.iter() {
+            if ask_yes_or_no(&format!("Back-fill {} for {}?", status.label, date)) {
+                super::run_quiz::quiz_full(api, &status.label, Some(*date))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn ask_yes_or_no(question: &str) -> bool {
+    use std::io::Write;
+
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush().ok();
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).ok();
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[test]
+fn test_schedules() {
+    let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+
+    assert_eq!(Schedule::parse("Daily"), Some(Schedule::Daily));
+    assert_eq!(
+        Schedule::parse("weekly sunday"),
+        Some(Schedule::Weekly(Weekday::Sun))
+    );
+    assert_eq!(Schedule::parse("monthly 32"), None);
+    assert_eq!(Schedule::parse("weekly sun").unwrap().label(), "weekly sun");
+
+    // 2026-10-17 is a Saturday.
+    assert!(!Schedule::Weekdays.is_scheduled_on(date(10, 17)));
+    assert!(Schedule::Weekdays.is_scheduled_on(date(10, 19)));
+    assert!(Schedule::Weekly(Weekday::Sat).is_scheduled_on(date(10, 17)));
+    // Shorter months fall back to their last day.
+    assert!(Schedule::Monthly(31).is_scheduled_on(date(9, 30)));
+    assert!(!Schedule::Monthly(31).is_scheduled_on(date(9, 29)));
+    assert!(Schedule::Monthly(15).is_scheduled_on(date(10, 15)));
+}
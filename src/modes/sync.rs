@@ -0,0 +1,308 @@
+/// This module contains the logic for the sync mode, which merges two journals, e.g. those of a laptop and a desktop
+/// kept in a synced folder, so that both end up with the same entries.
+///
+/// The entries are matched by their UUID: an entry only in one journal is copied to the other, and an entry edited in
+/// one journal since the last sync replaces its older version in the other. Each journal remembers the hash of every
+/// entry as it was when last in sync, which tells which side edited it. An entry edited in both journals is a conflict,
+/// left as it is and reported, unless a side is preferred. The categories, choices, quizzes and people are merged by
+/// label, like with `import json`. Nothing is removed, so syncing again is harmless.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::backend::api::export::{content_hash, ExportedEntry, Setup, SetupSummary};
+use crate::backend::api::Api;
+use crate::backend::{attachments, backups, config};
+use crate::errors::Error;
+use crate::modes::import::{self, ImportedEntry};
+
+/// One of the two journals: the one from the settings, or the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Here,
+    There,
+}
+
+/// What to change in each journal to bring them in sync.
+#[derive(Debug, Default)]
+struct Plan {
+    /// The entries missing in the journal from the settings.
+    copy_here: Vec<ExportedEntry>,
+    /// The entries missing in the other journal.
+    copy_there: Vec<ExportedEntry>,
+    update_here: Vec<ExportedEntry>,
+    update_there: Vec<ExportedEntry>,
+    /// The versions, here and there, of the entries edited in both journals.
+    conflicts: Vec<(ExportedEntry, ExportedEntry)>,
+    /// The hashes of the entries that are in sync once the plan is applied, by UUID.
+    synced: Vec<(String, String)>,
+}
+
+/// Syncs the journal with the other one at `other`, preferring the versions of `prefer` (`here` or `there`) for the
+/// entries edited in both.
+pub fn sync(
+    api: &mut Api,
+    other: &str,
+    prefer: &Option<String>,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let prefer = match prefer.as_deref().map(|side| side.trim().to_lowercase()) {
+        Some(side) if side == "here" => Some(Side::Here),
+        Some(side) if side == "there" => Some(Side::There),
+        Some(_) => {
+            return Err(Error::InvalidInput(
+                "`--prefer` must be `here` or `there`.".to_owned(),
+            ))
+        }
+        None => None,
+    };
+    let is_same_file = match (
+        std::fs::canonicalize(api.database_path()),
+        std::fs::canonicalize(other),
+    ) {
+        (Ok(here), Ok(there)) => here == there,
+        _ => false,
+    };
+    if is_same_file {
+        return Err(Error::InvalidInput(format!(
+            "{} is the journal itself.",
+            other
+        )));
+    }
+
+    let there = &mut Api::open(other)?;
+    api.unlock()?;
+    there.unlock()?;
+    let here_setup = api.export_setup()?;
+    let there_setup = there.export_setup()?;
+    let here_base = api.get_synced_hashes()?;
+    let there_base = there.get_synced_hashes()?;
+    let plan = plan_sync(
+        &api.export_entries()?,
+        &there.export_entries()?,
+        &here_base,
+        &there_base,
+        prefer,
+    );
+
+    if !plan.conflicts.is_empty() {
+        eprintln!(
+            "These entries were edited in both journals, so they were left as they are. Make them the same in both, or \
+            run again with `--prefer here` or `--prefer there` to keep one version:"
+        );
+        for (here, there) in plan.conflicts.iter() {
+            eprintln!("  here:  {}", describe(here));
+            eprintln!("  there: {}", describe(there));
+        }
+    }
+    let counts = format!(
+        "{} entries here and {} there",
+        plan.copy_here.len(),
+        plan.copy_there.len()
+    );
+    let updates = format!(
+        "{} here and {} there",
+        plan.update_here.len(),
+        plan.update_there.len()
+    );
+    if dry_run {
+        println!("Would copy {}, and update {}.", counts, updates);
+        println!("Nothing was saved: run again without `--dry-run` to sync.");
+        return Ok(());
+    }
+
+    if let Some(backup_path) =
+        backups::automatic_backup(Path::new(other), config::get().backups_to_keep.value, false)?
+    {
+        eprintln!("Saved an automatic backup to {}.", backup_path.display());
+    }
+    let here_attachments = attachments::attachments_dir_of(Path::new(api.database_path()));
+    let there_attachments = attachments::attachments_dir_of(Path::new(other));
+    // The other journal is changed first, as the sync can be run again if this one then fails.
+    let there_summary = apply(
+        there,
+        &here_setup,
+        copies(&plan.copy_there, here_attachments),
+        &plan.update_there,
+        &newly_synced(&plan.synced, &there_base),
+    )?;
+    let here_summary = apply(
+        api,
+        &there_setup,
+        copies(&plan.copy_here, there_attachments),
+        &plan.update_here,
+        &newly_synced(&plan.synced, &here_base),
+    )?;
+
+    import::json::print_setup_summary(&here_summary, "Added here");
+    import::json::print_setup_summary(&there_summary, "Added there");
+    println!("Copied {}, and updated {}.", counts, updates);
+    Ok(())
+}
+
+/// Compares the entries of both journals with their hashes when last in sync, in each journal.
+fn plan_sync(
+    here: &[ExportedEntry],
+    there: &[ExportedEntry],
+    here_base: &HashMap<String, String>,
+    there_base: &HashMap<String, String>,
+    prefer: Option<Side>,
+) -> Plan {
+    let mut plan = Plan::default();
+    let mut there_by_uuid: HashMap<&str, &ExportedEntry> = there
+        .iter()
+        .map(|entry| (entry.id.as_str(), entry))
+        .collect();
+    for entry in here {
+        let hash = content_hash(entry);
+        let Some(other) = there_by_uuid.remove(entry.id.as_str()) else {
+            plan.copy_there.push(entry.clone());
+            plan.synced.push((entry.id.clone(), hash));
+            continue;
+        };
+        let other_hash = content_hash(other);
+        if hash == other_hash {
+            plan.synced.push((entry.id.clone(), hash));
+            continue;
+        }
+        // An entry that was never synced counts as edited, since there is no telling which version is the newer.
+        let edited_here = here_base.get(&entry.id) != Some(&hash);
+        let edited_there = there_base.get(&entry.id) != Some(&other_hash);
+        let newer = match (edited_here, edited_there) {
+            (true, false) => Some(Side::Here),
+            (false, true) => Some(Side::There),
+            _ => prefer,
+        };
+        match newer {
+            Some(Side::Here) => {
+                plan.update_there.push(entry.clone());
+                plan.synced.push((entry.id.clone(), hash));
+            }
+            Some(Side::There) => {
+                plan.update_here.push(other.clone());
+                plan.synced.push((entry.id.clone(), other_hash));
+            }
+            None => plan.conflicts.push((entry.clone(), other.clone())),
+        }
+    }
+    for entry in there {
+        if there_by_uuid.contains_key(entry.id.as_str()) {
+            plan.copy_here.push(entry.clone());
+            plan.synced.push((entry.id.clone(), content_hash(entry)));
+        }
+    }
+    plan
+}
+
+/// Copies the entries and updates the entries of the journal, after adding the labels they use, all at once.
+fn apply(
+    api: &mut Api,
+    setup: &Setup,
+    copies: Vec<ImportedEntry>,
+    updates: &[ExportedEntry],
+    synced: &[(String, String)],
+) -> Result<SetupSummary, Error> {
+    api.transaction(|api| {
+        let summary = api.merge_setup(setup)?;
+        import::save_entries(api, copies, &[], false)?;
+        for entry in updates {
+            api.update_entry_by_uuid(entry)?;
+        }
+        api.mark_synced(synced)?;
+        Ok(summary)
+    })
+}
+
+/// The entries to copy, with their UUID, and their attachments from the attachments directory of their journal.
+fn copies(entries: &[ExportedEntry], attachments_dir: PathBuf) -> Vec<ImportedEntry> {
+    entries
+        .iter()
+        .map(|entry| import::json::to_entry(entry, std::slice::from_ref(&attachments_dir), true))
+        .collect()
+}
+
+/// The hashes that the journal doesn't have yet.
+fn newly_synced(
+    synced: &[(String, String)],
+    base: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    synced
+        .iter()
+        .filter(|(uuid, hash)| base.get(uuid) != Some(hash))
+        .cloned()
+        .collect()
+}
+
+fn describe(entry: &ExportedEntry) -> String {
+    let mut text = entry.timestamp.format("%Y-%m-%d %H:%M:%S  ").to_string();
+    if let Some(ref category) = entry.category {
+        text.push_str(category);
+        if let Some(ref choice) = entry.choice {
+            text.push_str(&format!(" -> {}", choice));
+        }
+        if entry.details.is_some() {
+            text.push_str(" : ");
+        }
+    }
+    if let Some(ref details) = entry.details {
+        text.push_str(&details.replace('\n', " / "));
+    }
+    if !entry.tags.is_empty() {
+        text.push_str(&format!(" [#{}]", entry.tags.join(" #")));
+    }
+    text
+}
+
+#[test]
+fn test_plan_sync_copies_updates_and_finds_conflicts() {
+    let entry = |id: &str, details: &str| ExportedEntry {
+        id: id.to_owned(),
+        timestamp: chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+            .and_then(|date| date.and_hms_opt(9, 30, 0))
+            .unwrap(),
+        category: None,
+        choice: None,
+        details: Some(details.to_owned()),
+        tags: Vec::new(),
+        attachments: Vec::new(),
+    };
+    let base_of = |entries: &[&ExportedEntry]| -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|entry| (entry.id.clone(), content_hash(entry)))
+            .collect()
+    };
+    let (same, edited_here, edited_there, edited_both) = (
+        entry("a", "same"),
+        entry("b", "old"),
+        entry("c", "old"),
+        entry("d", "old"),
+    );
+    let base = base_of(&[&same, &edited_here, &edited_there, &edited_both]);
+    let here = [
+        same.clone(),
+        entry("b", "new"),
+        edited_there.clone(),
+        entry("d", "here"),
+        entry("e", "only here"),
+    ];
+    let there = [
+        entry("f", "only there"),
+        same.clone(),
+        edited_here.clone(),
+        entry("c", "new"),
+        entry("d", "there"),
+    ];
+
+    let plan = plan_sync(&here, &there, &base, &base, None);
+    let ids = |entries: &[ExportedEntry]| entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&plan.copy_here), ["f"]);
+    assert_eq!(ids(&plan.copy_there), ["e"]);
+    assert_eq!(plan.update_there, [entry("b", "new")]);
+    assert_eq!(plan.update_here, [entry("c", "new")]);
+    assert_eq!(plan.conflicts, [(entry("d", "here"), entry("d", "there"))]);
+    assert_eq!(plan.synced.len(), 5);
+
+    let plan = plan_sync(&here, &there, &base, &base, Some(Side::There));
+    assert_eq!(plan.update_here, [entry("c", "new"), entry("d", "there")]);
+    assert!(plan.conflicts.is_empty());
+}
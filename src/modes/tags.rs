@@ -0,0 +1,115 @@
+/// This module contains the logic for the `tags` mode and for tagging existing entries.
+use chrono::{Duration, NaiveDateTime};
+
+use crate::backend::api::{self, Api};
+use crate::backend::config;
+use crate::errors::Error;
+
+/// Parses the entry argument: either an entry id or `last` for the latest entry.
+fn parse_entry_id(api: &mut Api, entry: &str) -> Result<i32, Error> {
+    if entry.trim().eq_ignore_ascii_case("last") {
+        return match api.get_latest_entry_id() {
+            Err(diesel::result::Error::NotFound) => {
+                Err(Error::InvalidInput("There are no entries yet.".to_owned()))
+            }
+            result => Ok(result?),
+        };
+    }
+    entry
+        .trim()
+        .trim_start_matches('#')
+        .parse::<i32>()
+        .map_err(|_| {
+            Error::InvalidInput(format!(
+                "The entry must be an id (as shown by `jurnalo search`) or `last`, not {}.",
+                entry
+            ))
+        })
+}
+
+/// Normalizes the tags given on the command line, e.g. `#Coffee` -> `coffee`.
+pub fn parse_tags(tags: &[String]) -> Result<Vec<String>, Error> {
+    tags.iter()
+        .map(|tag| {
+            api::tags::normalize_tag(tag).ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "`{}` isn't a valid tag. Tags are made of letters, digits, `_` and `-`.",
+                    tag
+                ))
+            })
+        })
+        .collect()
+}
+
+pub fn tag_entry(api: &mut Api, entry: &str, tags: &[String]) -> Result<(), Error> {
+    let entry_id = parse_entry_id(api, entry)?;
+    let tags = parse_tags(tags)?;
+    api.add_tags_to_entry(entry_id, &tags)?;
+    print_tags_of_entry(api, entry_id)
+}
+
+pub fn untag_entry(api: &mut Api, entry: &str, tags: &[String]) -> Result<(), Error> {
+    let entry_id = parse_entry_id(api, entry)?;
+    let tags = parse_tags(tags)?;
+    let removed = api.remove_tags_from_entry(entry_id, &tags)?;
+    if removed == 0 {
+        println!("The entry {} had none of these tags.", entry_id);
+    }
+    print_tags_of_entry(api, entry_id)
+}
+
+fn print_tags_of_entry(api: &mut Api, entry_id: i32) -> Result<(), Error> {
+    let tags = api.get_tags_of_entry(entry_id)?;
+    if tags.is_empty() {
+        println!("The entry {} has no tags.", entry_id);
+    } else {
+        println!(
+            "The entry {} is tagged: {}",
+            entry_id,
+            tags.iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+    Ok(())
+}
+
+/// Prints how many entries have each tag, in the period (e.g. "week", "month" or a number of days) or ever.
+/// With `rescan`, first extracts the hashtags from the details of every entry again.
+pub fn print_tags(
+    api: &mut Api,
+    period: &Option<String>,
+    rescan: bool,
+    filter: &api::EntryFilter,
+) -> Result<(), Error> {
+    if rescan {
+        let added = api.rescan_hashtags()?;
+        println!("Added {} tag(s) from the details of the entries.", added);
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let start = match period {
+        Some(p) => {
+            let days = super::stats::parse_period(p).ok_or_else(|| {
+                Error::InvalidInput(
+                    "The period must be `week`, `month`, `year` or a number of days.".to_owned(),
+                )
+            })?;
+            // The same local days as `stats`: today and the days before it.
+            config::start_of_day(config::today() - Duration::days(i64::from(days) - 1))
+        }
+        None => NaiveDateTime::UNIX_EPOCH,
+    };
+
+    let counts = api.get_tag_counts_between_dates(start, now, filter)?;
+    if counts.is_empty() {
+        println!("No tags yet. Add #hashtags to the details of the entries, or use `jurnalo entries tag`.");
+        return Ok(());
+    }
+    let padding_length = counts.iter().map(|(tag, _)| tag.len()).max().unwrap_or(0) + 2;
+    for (tag, count) in counts {
+        println!("#{:<padding_length$}{:>4}", tag, count);
+    }
+    Ok(())
+}